@@ -0,0 +1,31 @@
+-- Events that the game-master has queued up for delayed execution.
+-- Rows are inserted when an event is enqueued and removed once it has run.
+CREATE TYPE SCHEDULED_EVENT_TYPE AS ENUM (
+  'worker_task',
+  'check_resting_visitors',
+  'check_visitor_hp'
+);
+
+CREATE TABLE scheduled_events (
+    id BIGSERIAL PRIMARY KEY,
+    event_type SCHEDULED_EVENT_TYPE NOT NULL,
+    due TIMESTAMP NOT NULL,
+    -- Exactly one of these is set, depending on the event type
+    task_id BIGINT REFERENCES tasks(id) ON DELETE CASCADE,
+    village_id BIGINT REFERENCES villages(id) ON DELETE CASCADE,
+    hobo_id BIGINT REFERENCES hobos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX scheduled_events_due_idx ON scheduled_events (due);
+
+-- Worker tasks were previously rebuilt on startup from the tasks table.
+-- Store them once so that nothing is lost with this migration.
+INSERT INTO scheduled_events (event_type, due, task_id)
+SELECT 'worker_task', next_start, id
+FROM (
+    SELECT id,
+        LEAD(start_time) OVER (PARTITION BY worker_id ORDER BY start_time) AS next_start,
+        ROW_NUMBER() OVER (PARTITION BY worker_id ORDER BY start_time) AS n
+    FROM tasks
+) AS worker_tasks
+WHERE n = 1 AND next_start IS NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE attacks_to_hobos
+DROP CONSTRAINT attacks_to_hobos_hobo_id_unique;
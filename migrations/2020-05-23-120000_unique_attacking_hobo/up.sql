@@ -0,0 +1,8 @@
+-- A hobo can only take part in one attack at the time.
+-- Rows are deleted together with the attack, therefore a unique hobo ID is sufficient.
+DELETE FROM attacks_to_hobos a
+USING attacks_to_hobos b
+WHERE a.hobo_id = b.hobo_id AND a.attack_id > b.attack_id;
+
+ALTER TABLE attacks_to_hobos
+ADD CONSTRAINT attacks_to_hobos_hobo_id_unique UNIQUE (hobo_id);
@@ -0,0 +1,7 @@
+ALTER TABLE scheduled_events
+DROP COLUMN attack_id;
+DELETE FROM scheduled_events
+    WHERE event_type = 'check_attack';
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'scheduled_event_type'::regtype::oid
+    AND pg_enum.enumlabel = 'check_attack';
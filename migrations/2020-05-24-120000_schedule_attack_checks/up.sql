@@ -0,0 +1,7 @@
+-- Attacks are evaluated by scheduled events instead of polling all villages
+INSERT INTO pg_enum (enumtypid, enumlabel, enumsortorder)
+    SELECT 'scheduled_event_type'::regtype::oid, 'check_attack', 
+    ( SELECT MAX(enumsortorder) + 1 FROM pg_enum WHERE enumtypid = 'scheduled_event_type'::regtype );
+
+ALTER TABLE scheduled_events
+ADD COLUMN attack_id BIGINT REFERENCES attacks(id) ON DELETE CASCADE;
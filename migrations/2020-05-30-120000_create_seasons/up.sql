@@ -0,0 +1,21 @@
+-- Seasons and archived standings of past seasons
+
+CREATE TABLE seasons (
+    id BIGSERIAL PRIMARY KEY,
+    map_seed BIGINT NOT NULL,
+    start_time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    end_time TIMESTAMP
+);
+
+CREATE TABLE season_standings (
+    season_id BIGINT NOT NULL REFERENCES seasons(id) ON DELETE CASCADE,
+    player_id BIGINT NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    rank BIGINT NOT NULL,
+    karma BIGINT NOT NULL,
+    story_state STORY_STATE_TYPE NOT NULL,
+    village_count BIGINT NOT NULL,
+    PRIMARY KEY (season_id, player_id)
+);
+
+-- The map so far has been generated with the server id as seed
+INSERT INTO seasons (map_seed) VALUES (1);
@@ -0,0 +1,5 @@
+ALTER TABLE season_standings DROP COLUMN world_id;
+ALTER TABLE players DROP COLUMN world_id;
+ALTER TABLE villages DROP COLUMN world_id;
+ALTER TABLE streams DROP COLUMN world_id;
+DROP TABLE worlds;
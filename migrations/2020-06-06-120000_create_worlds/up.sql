@@ -0,0 +1,19 @@
+-- Several worlds can be played side by side, each with its own map
+
+CREATE TABLE worlds (
+    id BIGSERIAL PRIMARY KEY,
+    display_name VARCHAR(32) NOT NULL
+);
+
+-- Everything existing so far belongs to the first world
+INSERT INTO worlds (display_name) VALUES ('Paddland');
+
+ALTER TABLE streams ADD COLUMN world_id BIGINT NOT NULL DEFAULT 1 REFERENCES worlds(id);
+ALTER TABLE streams ALTER COLUMN world_id DROP DEFAULT;
+ALTER TABLE villages ADD COLUMN world_id BIGINT NOT NULL DEFAULT 1 REFERENCES worlds(id);
+ALTER TABLE villages ALTER COLUMN world_id DROP DEFAULT;
+ALTER TABLE players ADD COLUMN world_id BIGINT NOT NULL DEFAULT 1 REFERENCES worlds(id);
+ALTER TABLE players ALTER COLUMN world_id DROP DEFAULT;
+-- Standings are ranked per world
+ALTER TABLE season_standings ADD COLUMN world_id BIGINT NOT NULL DEFAULT 1 REFERENCES worlds(id);
+ALTER TABLE season_standings ALTER COLUMN world_id DROP DEFAULT;
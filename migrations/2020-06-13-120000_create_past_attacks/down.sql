@@ -0,0 +1 @@
+DROP TABLE past_attacks;
\ No newline at end of file
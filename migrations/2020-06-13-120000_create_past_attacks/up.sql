@@ -0,0 +1,14 @@
+-- Attacks are deleted when the visit is over, a summary of each of them is kept here
+
+CREATE TABLE past_attacks (
+    id BIGINT PRIMARY KEY,
+    departure TIMESTAMP NOT NULL,
+    arrival TIMESTAMP NOT NULL,
+    finished TIMESTAMP NOT NULL,
+    origin_village_id BIGINT REFERENCES villages(id) ON DELETE SET NULL,
+    destination_village_id BIGINT NOT NULL REFERENCES villages(id) ON DELETE CASCADE,
+    visitors INT NOT NULL,
+    satisfied_visitors INT NOT NULL
+);
+
+CREATE INDEX past_attacks_destination_idx ON past_attacks (destination_village_id);
@@ -0,0 +1 @@
+ALTER TABLE hobos DROP COLUMN returns_at;
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- Anarchists that leave a town unsatisfied need time to swim back to their home village
+ALTER TABLE hobos ADD COLUMN returns_at TIMESTAMP;
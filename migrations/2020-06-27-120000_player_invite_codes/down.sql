@@ -0,0 +1 @@
+ALTER TABLE players DROP COLUMN invite_code;
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- Invite codes used to be the player id, which is easy to guess.
+-- Existing players get a random code, new ones receive a code generated by the game-master.
+ALTER TABLE players ADD COLUMN invite_code VARCHAR(16);
+UPDATE players SET invite_code = substr(md5(random()::text || id::text), 1, 16);
+ALTER TABLE players ALTER COLUMN invite_code SET NOT NULL;
+ALTER TABLE players ADD CONSTRAINT players_invite_code_key UNIQUE (invite_code);
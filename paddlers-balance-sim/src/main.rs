@@ -0,0 +1,260 @@
+//! Offline balance simulator
+//!
+//! Simulates a single village over a number of in-game hours, without any database or network.
+//! The player follows a scripted build order, anarchists visit the village like they would on the game-master.
+//! All game rules are taken from `paddlers_shared_lib::game_mechanics` and the balancing specification.
+//!
+//! Usage: `paddlers-balance-sim <scenario.toml> [--hours N] [--interval-minutes M] [--seed S]`
+//!
+//! The result is written to stdout as CSV, one row per interval.
+
+mod scenario;
+mod village;
+mod visits;
+
+use paddlers_shared_lib::balancing::validate_balancing;
+use paddlers_shared_lib::game_mechanics::hobos::*;
+use paddlers_shared_lib::game_mechanics::town::MAX_VISITOR_QUEUE;
+use paddlers_shared_lib::game_mechanics::town::TOWN_X;
+use paddlers_shared_lib::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use scenario::Scenario;
+use village::{Village, STORY_STATE};
+use visits::{Visit, VisitOutcome};
+
+/// The simulated village, its ID matters for the attack frequency
+const VILLAGE_ID: i64 = 1;
+/// Same as the game cycle of the game-master
+const ATTACK_INTERVAL_SECONDS: i64 = 40;
+/// Visits are evaluated after visitors had time to swim through the town, like the game-master does
+const VISIT_CHECK_DELAY_SECONDS: i64 = 2 * TOWN_X as i64;
+
+struct Options {
+    scenario: String,
+    hours: i64,
+    interval_minutes: i64,
+    seed: u64,
+}
+
+struct Simulation {
+    village: Village,
+    visits: Vec<Visit>,
+    next_hobo_id: i64,
+    arrived: usize,
+    total: VisitOutcome,
+    rng: StdRng,
+}
+
+fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            eprintln!("Usage: paddlers-balance-sim <scenario.toml> [--hours N] [--interval-minutes M] [--seed S]");
+            std::process::exit(1);
+        }
+    };
+    validate_balancing().expect("Invalid balancing specification.");
+    let scenario = Scenario::load(&options.scenario).unwrap_or_else(|msg| {
+        eprintln!("{}", msg);
+        std::process::exit(1);
+    });
+
+    let mut sim = Simulation::new(&scenario, options.seed);
+    let mut steps = scenario.steps.iter().peekable();
+    let end = options.hours * 3600;
+    let interval = options.interval_minutes * 60;
+
+    println!("{}", Simulation::CSV_HEADER);
+    for s in 0..=end {
+        let now = Timestamp::from_seconds(s);
+        sim.village.collect_resources(now);
+        while let Some(step) = steps.peek() {
+            if sim.village.try_step(step, now) {
+                steps.next();
+            } else {
+                break;
+            }
+        }
+        if s % ATTACK_INTERVAL_SECONDS == 0 {
+            sim.maybe_send_attack(now);
+        }
+        sim.release_resting_visitors(now);
+        if scenario.welcome_visitors {
+            sim.welcome_visitor(now);
+        }
+        sim.check_visits(now);
+        if s % interval == 0 {
+            println!("{}", sim.csv_row(now));
+        }
+    }
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut args = std::env::args().skip(1);
+        let mut options = Options {
+            scenario: String::new(),
+            hours: 48,
+            interval_minutes: 60,
+            seed: 0,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
+            match arg.as_str() {
+                "--hours" => options.hours = parse(&value(&arg)?)?,
+                "--interval-minutes" => options.interval_minutes = parse(&value(&arg)?)?,
+                "--seed" => options.seed = parse(&value(&arg)?)?,
+                flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+                path => {
+                    if !options.scenario.is_empty() {
+                        return Err(format!("Unexpected argument {}", path));
+                    }
+                    options.scenario = path.to_owned();
+                }
+            }
+        }
+        if options.scenario.is_empty() {
+            return Err("No scenario given".to_owned());
+        }
+        if options.hours < 0 || options.interval_minutes <= 0 {
+            return Err("Hours and interval must be positive".to_owned());
+        }
+        Ok(options)
+    }
+}
+
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Invalid number {}", s))
+}
+
+impl Simulation {
+    const CSV_HEADER: &'static str = "hour,feathers,sticks,logs,karma,buildings,visitors_arrived,visitors_satisfied,visitors_unsatisfied,visitors_in_town,satisfaction";
+
+    fn new(scenario: &Scenario, seed: u64) -> Self {
+        Simulation {
+            village: Village::new(scenario),
+            visits: vec![],
+            next_hobo_id: 0,
+            arrived: 0,
+            total: VisitOutcome::default(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+    /// Same as the game cycle of the game-master
+    fn maybe_send_attack(&mut self, now: Timestamp) {
+        let random_number = self.rng.next_u64() as usize;
+        if !should_send_attack(self.visits.len(), random_number, VILLAGE_ID) {
+            return;
+        }
+        if let Some(level) = repetitive_attack_strength(STORY_STATE, self.village.karma) {
+            let arrival = Visit::next_arrival(now, &self.visits);
+            let visit = Visit::anarchists(&mut self.rng, &level, arrival, &mut self.next_hobo_id);
+            self.arrived += visit.visitors.len();
+            self.visits.push(visit);
+        }
+    }
+    /// When new visitors arrive, only a limited number of visitors can keep resting in town
+    fn release_resting_visitors(&mut self, now: Timestamp) {
+        let newly_arrived = self
+            .visits
+            .iter()
+            .any(|v| v.arrival == now && v.visitors.iter().any(|visitor| !visitor.hobo.hurried));
+        if !newly_arrived {
+            return;
+        }
+        let mut resting: Vec<_> = self
+            .visits
+            .iter_mut()
+            .flat_map(|v| v.visitors.iter_mut())
+            .filter(|v| v.resting(now))
+            .collect();
+        let n = resting.len().saturating_sub(MAX_VISITOR_QUEUE);
+        for visitor in resting.iter_mut().take(n) {
+            visitor.released = Some(now);
+        }
+    }
+    /// The idle hero welcomes the first visitor in town that has not been welcomed, yet
+    fn welcome_visitor(&mut self, now: Timestamp) {
+        let ability = AbilityType::Welcome;
+        let hero = &mut self.village.hero;
+        if hero.task != TaskType::Idle
+            || hero.welcome_ready > now
+            || hero.mana < ability.mana_cost()
+        {
+            return;
+        }
+        let visitor = self
+            .visits
+            .iter_mut()
+            .filter(|v| v.arrival <= now)
+            .flat_map(|v| v.visitors.iter_mut())
+            .find(|v| v.satisfied.is_none() && !v.welcomed());
+        if let Some(visitor) = visitor {
+            visitor.apply_ability(ability, now);
+            hero.mana -= ability.mana_cost();
+            hero.welcome_ready = now + ability.cooldown();
+        }
+    }
+    /// Evaluates visits and hands out rewards for finished visits, like the game-master does
+    fn check_visits(&mut self, now: Timestamp) {
+        let delay = Timestamp::from_seconds(VISIT_CHECK_DELAY_SECONDS);
+        let village = &mut self.village;
+        let mut i = 0;
+        while i < self.visits.len() {
+            let visit = &mut self.visits[i];
+            i += 1;
+            if visit.arrival + delay >= now {
+                continue;
+            }
+            let outcome = visit.evaluate(village, now);
+            self.total.satisfied += outcome.satisfied;
+            self.total.unsatisfied += outcome.unsatisfied;
+            if !visit.done() {
+                continue;
+            }
+            for hobo in visit.satisfied_visitors() {
+                village.karma += 1;
+                let feathers = reward_feathers(hobo.hp, hobo.speed, hobo.hurried);
+                village.add_resource(ResourceType::Feathers, feathers);
+                village.add_resource(ResourceType::Sticks, reward_sticks(hobo.id));
+                village.add_resource(ResourceType::Logs, reward_logs(hobo.id));
+            }
+            i -= 1;
+            self.visits.remove(i);
+        }
+    }
+    fn csv_row(&self, now: Timestamp) -> String {
+        let v = &self.village;
+        let in_town = self
+            .visits
+            .iter()
+            .filter(|visit| visit.arrival <= now)
+            .flat_map(|visit| visit.visitors.iter())
+            .filter(|visitor| visitor.satisfied.is_none())
+            .count();
+        let finished = self.total.satisfied + self.total.unsatisfied;
+        let satisfaction = if finished > 0 {
+            self.total.satisfied as f32 / finished as f32
+        } else {
+            0.0
+        };
+        format!(
+            "{:.2},{},{},{},{},{},{},{},{},{},{:.3}",
+            now.seconds_float() / 3600.0,
+            v.feathers,
+            v.sticks,
+            v.logs,
+            v.karma,
+            v.buildings.len(),
+            self.arrived,
+            self.total.satisfied,
+            self.total.unsatisfied,
+            in_town,
+            satisfaction,
+        )
+    }
+}
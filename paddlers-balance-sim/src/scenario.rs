@@ -0,0 +1,57 @@
+//! Scripted player behaviour, read from a TOML file.
+
+use paddlers_shared_lib::balancing::ResourceAmounts;
+use paddlers_shared_lib::game_mechanics::town::TileIndex;
+use paddlers_shared_lib::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Resources available at the beginning
+    #[serde(default)]
+    pub start: ResourceAmounts,
+    /// If set, the hero uses the welcome ability on visitors whenever it is idle
+    #[serde(default)]
+    pub welcome_visitors: bool,
+    #[serde(rename = "step", default)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Step {
+    /// Build a building as soon as it is affordable and unlocked
+    Build { build: BuildingType, at: TileIndex },
+    /// Assign the hero to a job as soon as the town supports it
+    Work { work: TaskType },
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let input = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read scenario {}: {}", path, e))?;
+        let scenario: Scenario =
+            toml::from_str(&input).map_err(|e| format!("Invalid scenario {}: {}", path, e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+    fn validate(&self) -> Result<(), String> {
+        for step in &self.steps {
+            match step {
+                Step::Build { build, .. } => {
+                    if *build == BuildingType::Temple {
+                        return Err(
+                            "The temple is part of the story and cannot be simulated".to_owned()
+                        );
+                    }
+                }
+                Step::Work { work } => match work {
+                    TaskType::GatherSticks | TaskType::ChopTree | TaskType::Idle => {}
+                    other => return Err(format!("The hero cannot be assigned to {:?}", other)),
+                },
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! Economy of the simulated village, following the same rules as the game-master.
+
+use crate::scenario::{Scenario, Step};
+use paddlers_shared_lib::api::shop::{Cost, Price};
+use paddlers_shared_lib::game_mechanics::forestry::tree_size;
+use paddlers_shared_lib::game_mechanics::town::*;
+use paddlers_shared_lib::game_mechanics::worker::*;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::story_state::StoryState;
+
+/// The simulation assumes a player that has finished the story, hence anarchists scale with karma
+pub const STORY_STATE: StoryState = StoryState::GatheringSticks;
+
+pub struct Village {
+    pub map: TownMap,
+    pub buildings: Vec<SimBuilding>,
+    pub feathers: i64,
+    pub sticks: i64,
+    pub logs: i64,
+    pub karma: i64,
+    pub hero: Hero,
+    next_building_id: i64,
+}
+
+pub struct SimBuilding {
+    pub id: i64,
+    pub building_type: BuildingType,
+    pub position: TileIndex,
+    pub creation: Timestamp,
+}
+
+pub struct Hero {
+    pub task: TaskType,
+    pub mana: i32,
+    /// Like the worker flags on the game-master, these store until when regular updates have been applied
+    last_mana_update: Timestamp,
+    last_work_update: Timestamp,
+    pub welcome_ready: Timestamp,
+}
+
+impl Village {
+    pub fn new(scenario: &Scenario) -> Self {
+        Village {
+            map: TownMap::new(TownLayout::Basic),
+            buildings: vec![],
+            feathers: scenario.start.feathers,
+            sticks: scenario.start.sticks,
+            logs: scenario.start.logs,
+            karma: 0,
+            hero: Hero {
+                task: TaskType::Idle,
+                mana: 10,
+                last_mana_update: Timestamp::from_us(0),
+                last_work_update: Timestamp::from_us(0),
+                welcome_ready: Timestamp::from_us(0),
+            },
+            next_building_id: 0,
+        }
+    }
+    pub fn buildings_at(&self, time: Timestamp) -> impl Iterator<Item = &SimBuilding> {
+        self.buildings.iter().filter(move |b| b.creation <= time)
+    }
+    /// Same income as the game-master collects for workers, for the single hero of the village
+    pub fn collect_resources(&mut self, now: Timestamp) {
+        let hero = &mut self.hero;
+
+        let (n, until) = accumulated_income(
+            hero.last_mana_update,
+            now,
+            hero_mana_regeneration_per_hour(),
+        );
+        hero.last_mana_update = until;
+        hero.mana = (hero.mana + n as i32).min(hero_max_mana());
+
+        if let Some((res, rate)) = hero_resource_collection_per_hour(hero.task) {
+            let (n, until) = accumulated_income(hero.last_work_update, now, rate);
+            hero.last_work_update = until;
+            self.add_resource(res, n);
+        } else {
+            hero.last_work_update = now;
+        }
+    }
+    /// Tries to perform a step of the build order, returns true if it has been performed
+    pub fn try_step(&mut self, step: &Step, now: Timestamp) -> bool {
+        match step {
+            Step::Build { build, at } => self.try_build(*build, *at, now),
+            Step::Work { work } => self.try_work(*work, now),
+        }
+    }
+    fn try_build(&mut self, bt: BuildingType, at: TileIndex, now: Timestamp) -> bool {
+        let buildable = self.map.tile_type(at).map(TownTileType::is_buildable);
+        if buildable != Some(true) || !bt.player_can_build(self.karma, STORY_STATE) {
+            return false;
+        }
+        let price = bt.price();
+        if !self.can_afford(&price) {
+            return false;
+        }
+        for (res, n) in price.0 {
+            self.add_resource(res, -n);
+        }
+        self.map[at] = TownTileType::BUILDING(bt);
+        self.next_building_id += 1;
+        self.buildings.push(SimBuilding {
+            id: self.next_building_id,
+            building_type: bt,
+            position: at,
+            creation: now,
+        });
+        true
+    }
+    fn try_work(&mut self, task: TaskType, now: Timestamp) -> bool {
+        let workplace = match task {
+            TaskType::GatherSticks => Some(BuildingType::BundlingStation),
+            TaskType::ChopTree => Some(BuildingType::SawMill),
+            _ => None,
+        };
+        if let Some(bt) = workplace {
+            if !self.buildings.iter().any(|b| b.building_type == bt) {
+                return false;
+            }
+        }
+        // The hero is the only worker, hence the full forest is available
+        if self.forest_size(now) < task.required_forest_size() {
+            return false;
+        }
+        self.hero.task = task;
+        self.hero.last_work_update = now;
+        true
+    }
+    fn forest_size(&self, now: Timestamp) -> usize {
+        self.buildings
+            .iter()
+            .filter(|b| b.building_type == BuildingType::Tree)
+            .map(|b| tree_size((now - b.creation).into()))
+            .sum()
+    }
+    fn can_afford(&self, price: &Price) -> bool {
+        price.0.iter().all(|(res, n)| self.resource(*res) >= *n)
+    }
+    pub fn resource(&self, res: ResourceType) -> i64 {
+        match res {
+            ResourceType::Feathers => self.feathers,
+            ResourceType::Sticks => self.sticks,
+            ResourceType::Logs => self.logs,
+        }
+    }
+    pub fn add_resource(&mut self, res: ResourceType, n: i64) {
+        match res {
+            ResourceType::Feathers => self.feathers += n,
+            ResourceType::Sticks => self.sticks += n,
+            ResourceType::Logs => self.logs += n,
+        }
+    }
+}
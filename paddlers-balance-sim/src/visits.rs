@@ -0,0 +1,214 @@
+//! Anarchist visits to the simulated village, following the same rules as the game-master.
+
+use crate::village::Village;
+use paddlers_shared_lib::game_mechanics::attributes::Attributes;
+use paddlers_shared_lib::game_mechanics::hobos::*;
+use paddlers_shared_lib::game_mechanics::town::defence::*;
+use paddlers_shared_lib::game_mechanics::town::*;
+use paddlers_shared_lib::prelude::*;
+use rand::Rng;
+
+/// Minimal time between sending a visitor group and its arrival, like for attacks without origin village
+const TRAVEL_SECONDS: i64 = 15;
+
+pub struct Visit {
+    pub arrival: Timestamp,
+    pub visitors: Vec<Visitor>,
+}
+
+/// The attributes of a hobo that matter for a visit
+pub struct Anarchist {
+    pub id: i64,
+    pub hp: i64,
+    pub speed: f32,
+    pub hurried: bool,
+}
+
+pub struct Visitor {
+    pub hobo: Anarchist,
+    pub arrival: Timestamp,
+    pub released: Option<Timestamp>,
+    /// Some(true) if satisfied, Some(false) if it left unsatisfied
+    pub satisfied: Option<bool>,
+    pub effects: Vec<(Timestamp, HoboAttributeType, i32)>,
+}
+
+/// Number of visitors that were satisfied and that left unhappy, respectively
+#[derive(Default, Clone, Copy)]
+pub struct VisitOutcome {
+    pub satisfied: usize,
+    pub unsatisfied: usize,
+}
+
+impl Visit {
+    /// Creates a group of anarchists like the game-master's AttackSpawner does
+    pub fn anarchists<R: Rng>(
+        rng: &mut R,
+        level: &HoboLevel,
+        arrival: Timestamp,
+        next_id: &mut i64,
+    ) -> Self {
+        let mut new_visitor = |hp: i64, speed: f32, hurried: bool| {
+            *next_id += 1;
+            Visitor {
+                hobo: Anarchist {
+                    id: *next_id,
+                    speed,
+                    hp,
+                    hurried,
+                },
+                arrival,
+                released: None,
+                satisfied: None,
+                effects: vec![],
+            }
+        };
+        let n = rng.gen_range(2, 4);
+        let (min_hp, max_hp) = level.hurried_anarchist_hp_range();
+        let mut visitors: Vec<Visitor> = (0..n)
+            .map(|_| new_visitor(rng.gen_range(min_hp, max_hp), 0.0625, true))
+            .collect();
+        visitors.push(new_visitor(level.unhurried_anarchist_hp(), 0.25, false));
+        Visit { arrival, visitors }
+    }
+    /// Earliest arrival of a new group, visits of the same village must not overlap
+    pub fn next_arrival(now: Timestamp, visits: &[Visit]) -> Timestamp {
+        let mut arrival = now + Timestamp::from_seconds(TRAVEL_SECONDS);
+        for visit in visits {
+            let end = visit.arrival + duration(visit.visitors.len());
+            if arrival < end {
+                arrival = end;
+            }
+        }
+        arrival
+    }
+    pub fn done(&self) -> bool {
+        self.visitors.iter().all(|v| v.satisfied.is_some())
+    }
+    /// Same as `DB::maybe_evaluate_attack` in the game-master
+    pub fn evaluate(&mut self, village: &Village, now: Timestamp) -> VisitOutcome {
+        let mut outcome = VisitOutcome::default();
+        let hp_left = village.hp_left_in_group(&self.visitors, now);
+        for (visitor, hp) in self.visitors.iter_mut().zip(hp_left) {
+            if visitor.satisfied.is_some() {
+                continue;
+            }
+            if hp == 0 {
+                visitor.satisfied = Some(true);
+                outcome.satisfied += 1;
+                if !visitor.hobo.hurried && visitor.released.is_none() {
+                    visitor.released = Some(now);
+                }
+            } else if village.hobo_left_town(visitor, now) {
+                visitor.satisfied = Some(false);
+                outcome.unsatisfied += 1;
+            }
+        }
+        outcome
+    }
+    pub fn satisfied_visitors(&self) -> impl Iterator<Item = &Anarchist> {
+        self.visitors
+            .iter()
+            .filter(|v| v.satisfied == Some(true))
+            .map(|v| &v.hobo)
+    }
+}
+
+impl Visitor {
+    pub fn resting(&self, now: Timestamp) -> bool {
+        !self.hobo.hurried
+            && self.satisfied.is_none()
+            && self.released.is_none()
+            && self.arrival <= now
+    }
+    pub fn apply_ability(&mut self, ability: AbilityType, now: Timestamp) {
+        for (attribute, strength) in ability.apply() {
+            self.effects.push((now, *attribute, *strength));
+        }
+    }
+    pub fn welcomed(&self) -> bool {
+        !self.effects.is_empty()
+    }
+}
+
+fn duration(units: usize) -> Timestamp {
+    Timestamp::from_seconds(((units + 1) * 5 / 2) as i64)
+}
+
+impl IAttackingHobo for Visitor {
+    fn id(&self) -> i64 {
+        self.hobo.id
+    }
+    fn max_hp(&self) -> u32 {
+        self.hobo.hp as u32
+    }
+    fn speed(&self) -> f32 {
+        self.hobo.speed
+    }
+    fn hurried(&self) -> bool {
+        self.hobo.hurried
+    }
+    fn arrival(&self) -> Timestamp {
+        self.arrival
+    }
+    fn released(&self) -> Option<Timestamp> {
+        self.released
+    }
+    fn effects_strength(&self) -> i32 {
+        self.effects
+            .iter()
+            .filter(|(_, attribute, _)| *attribute == HoboAttributeType::Health)
+            .fold(0, |acc, (_, _, strength)| acc + strength)
+    }
+    fn speed_effects(&self) -> Vec<(Timestamp, i32)> {
+        self.effects
+            .iter()
+            .filter(|(_, attribute, _)| *attribute == HoboAttributeType::Speed)
+            .map(|(start, _, strength)| (*start, *strength))
+            .collect()
+    }
+}
+
+impl ITownLayoutMarker for Village {
+    const LAYOUT: TownLayout = TownLayout::Basic;
+}
+
+impl IDefendingTown for Village {
+    type AuraId = i64;
+    fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
+        self.buildings_at(time)
+            .filter(|b| b.building_type.attacks_per_cycle().is_none())
+            .filter_map(|b| {
+                let range = b.building_type.range()?;
+                let ap = b.building_type.attack_power()?;
+                if distance2(b.position, *index) <= range * range {
+                    Some((b.id, ap as i32))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+    fn shooters_in_range(
+        &self,
+        index: &Self::Index,
+        time: Timestamp,
+    ) -> Vec<Shooter<Self::AuraId>> {
+        self.buildings_at(time)
+            .filter_map(|b| {
+                let range = b.building_type.range()?;
+                let ap = b.building_type.attack_power()?;
+                let shots = b.building_type.attacks_per_cycle()?;
+                if distance2(b.position, *index) <= range * range {
+                    Some(Shooter {
+                        id: b.id,
+                        power: ap as i32,
+                        shots_per_cycle: shots as usize,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
@@ -10,11 +10,16 @@ use paddlers_shared_lib::user_authentication::PadlUser;
 use std::sync::Arc;
 
 mod gql_err;
+mod gql_pagination;
 pub mod gql_private;
 mod gql_public;
+mod loaders;
 
 use gql_err::ReadableInterfaceError;
+use gql_pagination::*;
+pub use gql_pagination::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
 use gql_public::*;
+use loaders::Loaders;
 
 pub struct Mutation;
 pub struct Query;
@@ -23,9 +28,11 @@ pub struct AuthenticatedContext {
     db: Arc<DbConn>,
     user: Player,
     villages: Vec<VillageKey>,
+    loaders: Loaders,
 }
 pub struct UnauthenticatedContext {
     db: Arc<DbConn>,
+    loaders: Loaders,
 }
 pub enum Context {
     Public(UnauthenticatedContext),
@@ -48,6 +55,7 @@ impl Context {
                 db: conn,
                 user: player,
                 villages: vids,
+                loaders: Loaders::default(),
             }))
         } else {
             #[cfg(feature = "local")]
@@ -57,7 +65,10 @@ impl Context {
                     return tester_context;
                 }
             }
-            Some(Context::Public(UnauthenticatedContext { db: conn }))
+            Some(Context::Public(UnauthenticatedContext {
+                db: conn,
+                loaders: Loaders::default(),
+            }))
         }
     }
     #[cfg(feature = "local")]
@@ -73,6 +84,7 @@ impl Context {
             db: conn,
             user: player,
             villages: vids,
+            loaders: Loaders::default(),
         }))
     }
     pub fn db(&self) -> &Arc<DbConn> {
@@ -81,6 +93,12 @@ impl Context {
             Context::Public(ctx) => &ctx.db,
         }
     }
+    pub fn loaders(&self) -> &Loaders {
+        match self {
+            Context::Authenticated(ctx) => &ctx.loaders,
+            Context::Public(ctx) => &ctx.loaders,
+        }
+    }
     pub fn authenticated(&self) -> Result<&AuthenticatedContext, ReadableInterfaceError> {
         match self {
             Context::Authenticated(ctx) => Ok(ctx),
@@ -94,6 +112,14 @@ impl Context {
             Err(ReadableInterfaceError::NotAllowed)
         }
     }
+    /// Worlds are selected explicitly or otherwise default to the world of the authenticated player
+    fn world_or_own(&self, world_id: Option<i32>) -> Result<WorldKey, ReadableInterfaceError> {
+        if let Some(id) = world_id {
+            Ok(WorldKey(id as i64))
+        } else {
+            Ok(self.authenticated()?.user.world())
+        }
+    }
     fn check_village_key(&self, key: VillageKey) -> Result<(), ReadableInterfaceError> {
         if self.authenticated()?.villages.contains(&key) {
             Ok(())
@@ -146,17 +172,57 @@ impl Query {
         ))
     }
     // Object Visibility: public
-    fn map(low_x: i32, high_x: i32) -> GqlMapSlice {
-        GqlMapSlice { low_x, high_x }
+    // Without a world specified, the map of the player's own world is returned
+    fn map(
+        ctx: &Context,
+        low_x: i32,
+        high_x: i32,
+        world_id: Option<i32>,
+    ) -> FieldResult<GqlMapSlice> {
+        let world = ctx.world_or_own(world_id)?;
+        Ok(GqlMapSlice {
+            low_x,
+            high_x,
+            world,
+        })
     }
     // Object Visibility: public
-    // Returns up to 100 players starting from the given rank upwards
-    fn scoreboard(ctx: &Context, rank_offset: i32) -> Vec<GqlPlayer> {
-        ctx.db()
-            .players_sorted_by_karma(rank_offset as i64, 100)
-            .into_iter()
-            .map(GqlPlayer)
-            .collect()
+    // Players ranked by karma, the cursors hold the karma and ID of a player
+    // Without a world specified, the players of the player's own world are ranked
+    fn scoreboard(
+        ctx: &Context,
+        first: Option<i32>,
+        after: Option<String>,
+        world_id: Option<i32>,
+    ) -> FieldResult<GqlPlayerConnection> {
+        let world = ctx.world_or_own(world_id)?;
+        let page: PageRequest<RankCursor> = PageRequest::new(first, after)?;
+        let after = page.after.map(|cursor| (cursor.karma, cursor.id));
+        let players = ctx
+            .db()
+            .players_sorted_by_karma(world, after, page.fetch_limit());
+        let page = page
+            .page(players, |p| RankCursor {
+                karma: p.karma,
+                id: p.id,
+            })
+            .map(|players| players.into_iter().map(GqlPlayer).collect());
+        Ok(GqlPlayerConnection::new(page, Total::Players(world)))
+    }
+    // Object Visibility: public
+    fn worlds(ctx: &Context) -> Vec<GqlWorld> {
+        ctx.db().worlds().into_iter().map(GqlWorld).collect()
+    }
+    // Object Visibility: public
+    fn current_season(ctx: &Context) -> FieldResult<GqlSeason> {
+        Ok(GqlSeason(
+            ctx.db().current_season().ok_or("No season running")?,
+        ))
+    }
+    // Object Visibility: public
+    // Seasons that have ended, the most recent first
+    fn past_seasons(ctx: &Context) -> Vec<GqlSeason> {
+        ctx.db().past_seasons().into_iter().map(GqlSeason).collect()
     }
 }
 
@@ -0,0 +1,195 @@
+//! Cursor-based pagination following the connection pattern of Relay.
+//!
+//! A connection holds one page of edges, each edge contains a node and its cursor.
+//! Passing the cursor of the last edge as `after` returns the next page.
+//! Cursors are opaque to the client. Internally, they are the ID of the node
+//! or, for rankings, the ranked value together with the ID of the node.
+//! Either way, a page starts right after its cursor node, even if nodes have been inserted or moved in between.
+
+use super::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// Page size used when `first` is not specified
+pub const DEFAULT_PAGE_SIZE: i32 = 20;
+/// Larger values for `first` are capped to this
+pub const MAX_PAGE_SIZE: i32 = 100;
+
+/// The page selected by the arguments `first` and `after` of a connection field
+pub struct PageRequest<C = i64> {
+    pub after: Option<C>,
+    pub limit: i64,
+}
+
+/// Cursor in the ranking by karma, players with equal karma are ordered by ID
+#[derive(Clone, Copy)]
+pub struct RankCursor {
+    pub karma: i64,
+    pub id: i64,
+}
+
+/// Nodes of one page paired with their cursors
+pub struct Page<T> {
+    edges: Vec<(String, T)>,
+    info: GqlPageInfo,
+}
+
+pub struct GqlPageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+/// Total number of nodes in a connection, only counted when the field is requested
+pub enum Total {
+    Attacks(VillageKey),
+    PastAttacks(VillageKey),
+    Reports(VillageKey),
+    Players(WorldKey),
+}
+
+impl<C: FromStr + ToString> PageRequest<C> {
+    pub fn new(first: Option<i32>, after: Option<String>) -> FieldResult<Self> {
+        let first = first.unwrap_or(DEFAULT_PAGE_SIZE);
+        if first < 0 {
+            return Err("Argument first must not be negative".into());
+        }
+        let after = after
+            .map(|cursor| cursor.parse::<C>().map_err(|_| "Invalid cursor"))
+            .transpose()?;
+        Ok(PageRequest {
+            after,
+            limit: first.min(MAX_PAGE_SIZE) as i64,
+        })
+    }
+    /// One more node than requested is fetched to find out whether there is a next page
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+    /// Cuts the fetched nodes to the page size and assigns cursors
+    pub fn page<T>(&self, mut nodes: Vec<T>, cursor: impl Fn(&T) -> C) -> Page<T> {
+        let has_next_page = nodes.len() as i64 > self.limit;
+        nodes.truncate(self.limit as usize);
+        let edges: Vec<(String, T)> = nodes
+            .into_iter()
+            .map(|node| (cursor(&node).to_string(), node))
+            .collect();
+        let end_cursor = edges.last().map(|(cursor, _)| cursor.clone());
+        Page {
+            edges,
+            info: GqlPageInfo {
+                has_next_page,
+                end_cursor,
+            },
+        }
+    }
+}
+
+impl FromStr for RankCursor {
+    type Err = std::num::ParseIntError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let karma = parts.next().unwrap_or("").parse()?;
+        let id = parts.next().unwrap_or("").parse()?;
+        Ok(RankCursor { karma, id })
+    }
+}
+
+impl fmt::Display for RankCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.karma, self.id)
+    }
+}
+
+impl<T> Page<T> {
+    /// Converts all nodes at once, `f` has to keep the order
+    pub fn map<N>(self, f: impl FnOnce(Vec<T>) -> Vec<N>) -> Page<N> {
+        let (cursors, nodes): (Vec<String>, Vec<T>) = self.edges.into_iter().unzip();
+        Page {
+            edges: cursors.into_iter().zip(f(nodes)).collect(),
+            info: self.info,
+        }
+    }
+}
+
+impl Total {
+    fn count(&self, ctx: &Context) -> i32 {
+        let db = ctx.db();
+        let n = match *self {
+            Total::Attacks(village) => db.attacks_count(village, None) as i64,
+            Total::PastAttacks(village) => db.past_attacks_count(village) as i64,
+            Total::Reports(village) => db.reports_count(village) as i64,
+            Total::Players(world) => db.world_player_count(world),
+        };
+        n as i32
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlPageInfo {
+    fn has_next_page(&self) -> bool {
+        self.has_next_page
+    }
+    /// Cursor of the last edge, null if the page is empty
+    fn end_cursor(&self) -> Option<String> {
+        self.end_cursor.clone()
+    }
+}
+
+/// Defines a connection and its edge type for the given node type
+macro_rules! connection {
+    ($connection:ident, $edge:ident, $node:ident) => {
+        pub struct $connection {
+            edges: Vec<$edge>,
+            page_info: GqlPageInfo,
+            total: Total,
+        }
+        pub struct $edge {
+            cursor: String,
+            node: $node,
+        }
+        impl $connection {
+            pub fn new(page: Page<$node>, total: Total) -> Self {
+                $connection {
+                    edges: page
+                        .edges
+                        .into_iter()
+                        .map(|(cursor, node)| $edge { cursor, node })
+                        .collect(),
+                    page_info: page.info,
+                    total,
+                }
+            }
+        }
+        #[juniper::object (Context = Context)]
+        impl $connection {
+            fn edges(&self) -> &Vec<$edge> {
+                &self.edges
+            }
+            fn page_info(&self) -> &GqlPageInfo {
+                &self.page_info
+            }
+            /// Number of nodes on all pages together
+            fn total_count(&self, ctx: &Context) -> i32 {
+                self.total.count(ctx)
+            }
+        }
+        #[juniper::object (Context = Context)]
+        impl $edge {
+            fn cursor(&self) -> &str {
+                &self.cursor
+            }
+            fn node(&self) -> &$node {
+                &self.node
+            }
+        }
+    };
+}
+
+connection!(GqlAttackConnection, GqlAttackEdge, GqlAttack);
+connection!(GqlPastAttackConnection, GqlPastAttackEdge, GqlPastAttack);
+connection!(
+    GqlAttackReportConnection,
+    GqlAttackReportEdge,
+    GqlAttackReport
+);
+connection!(GqlPlayerConnection, GqlPlayerEdge, GqlPlayer);
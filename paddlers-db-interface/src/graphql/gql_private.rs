@@ -12,9 +12,19 @@ impl GqlAttack {
         self.0.id.to_string().into()
     }
     fn units(&self, ctx: &Context) -> FieldResult<Vec<GqlAttackUnit>> {
-        Ok(ctx
-            .db()
-            .attack_hobos_with_attack_info(&self.0)
+        let loaders = ctx.loaders();
+        Ok(loaders
+            .attack_units
+            .load(self.0.key(), |keys| {
+                let units = ctx.db().attacks_hobos_with_attack_info(keys);
+                loaders
+                    .effects
+                    .register(units.iter().map(|(hobo, _)| hobo.key()));
+                units
+                    .into_iter()
+                    .map(|unit| (AttackKey(unit.1.attack_id), unit))
+                    .collect()
+            })
             .into_iter()
             .map(|(hobo, info)| GqlAttackUnit(GqlHobo(hobo), GqlHoboAttackInfo(info)))
             .collect())
@@ -26,17 +36,51 @@ impl GqlAttack {
         datetime(&self.0.arrival)
     }
     fn attacker(&self, ctx: &Context) -> FieldResult<Option<GqlPlayer>> {
-        let db = ctx.db();
         Ok(self
             .0
-            .origin_village_id
-            .and_then(|vid| db.village(VillageKey(vid)))
-            .and_then(|village| village.owner())
-            .and_then(|pid| db.player(pid))
+            .origin()
+            .and_then(|village| {
+                ctx.loaders()
+                    .village_owners
+                    .load_one(village, |keys| ctx.db().village_owners(keys))
+            })
             .map(|player| GqlPlayer(player)))
     }
 }
 #[juniper::object (Context = Context)]
+impl GqlPastAttack {
+    fn id(&self) -> juniper::ID {
+        self.0.id.to_string().into()
+    }
+    fn departure(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.departure)
+    }
+    fn arrival(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.arrival)
+    }
+    /// When the last visitor was satisfied or left
+    fn finished(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.finished)
+    }
+    fn attacker(&self, ctx: &Context) -> FieldResult<Option<GqlPlayer>> {
+        Ok(self
+            .0
+            .origin()
+            .and_then(|village| {
+                ctx.loaders()
+                    .village_owners
+                    .load_one(village, |keys| ctx.db().village_owners(keys))
+            })
+            .map(|player| GqlPlayer(player)))
+    }
+    fn visitors(&self) -> i32 {
+        self.0.visitors
+    }
+    fn satisfied_visitors(&self) -> i32 {
+        self.0.satisfied_visitors
+    }
+}
+#[juniper::object (Context = Context)]
 impl GqlAttackUnit {
     fn hobo(&self) -> &GqlHobo {
         &self.0
@@ -77,12 +121,6 @@ impl GqlAttackReport {
     }
 }
 impl GqlAttackReport {
-    pub fn load_rewards(&mut self, ctx: &Context) {
-        if self.rewards.is_none() {
-            let db = ctx.db();
-            self.rewards = Some(db.rewards(self.inner.key()))
-        }
-    }
     fn resource(&self, res: ResourceType) -> i32 {
         self.rewards
             .as_ref()
@@ -164,8 +202,15 @@ impl GqlWorker {
         self.0.y
     }
 
-    fn mana(&self) -> Option<i32> {
-        self.0.mana
+    fn mana(&self, ctx: &Context) -> Option<i32> {
+        let flags = ctx.loaders().worker_flags.load(self.0.key(), |keys| {
+            ctx.db()
+                .workers_flags(keys)
+                .into_iter()
+                .map(|flag| (WorkerKey(flag.worker_id), flag))
+                .collect()
+        });
+        ctx.db().mana_with_flags(&self.0, &flags)
     }
 
     // TODO: Proper type handling
@@ -174,16 +219,30 @@ impl GqlWorker {
     }
 
     pub fn tasks(&self, ctx: &Context) -> Vec<GqlTask> {
-        ctx.db()
-            .worker_tasks(WorkerKey(self.0.id))
+        ctx.loaders()
+            .tasks
+            .load(self.0.key(), |keys| {
+                ctx.db()
+                    .workers_tasks(keys)
+                    .into_iter()
+                    .map(|task| (task.worker(), task))
+                    .collect()
+            })
             .into_iter()
             .map(GqlTask::authorized) // Inherited authorization
             .collect()
     }
 
     fn abilities(&self, ctx: &Context) -> Vec<GqlAbility> {
-        ctx.db()
-            .worker_abilities(WorkerKey(self.0.id))
+        ctx.loaders()
+            .abilities
+            .load(self.0.key(), |keys| {
+                ctx.db()
+                    .workers_abilities(keys)
+                    .into_iter()
+                    .map(|ability| (WorkerKey(ability.worker_id), ability))
+                    .collect()
+            })
             .into_iter()
             .map(GqlAbility::authorized) // Inherited authorization
             .collect()
@@ -27,17 +27,21 @@ use paddlers_shared_lib::story::story_state::StoryState;
 pub struct GqlMapSlice {
     pub low_x: i32,
     pub high_x: i32,
+    pub world: WorldKey,
 }
 pub struct GqlStream(pub paddlers_shared_lib::models::Stream);
 pub struct GqlHobo(pub paddlers_shared_lib::models::Hobo);
 pub struct GqlAttackUnit(pub GqlHobo, pub GqlHoboAttackInfo);
 /// Additional information for a hobo that is currently attacking
 pub struct GqlHoboAttackInfo(pub paddlers_shared_lib::models::AttackToHobo);
+pub struct GqlSeason(pub paddlers_shared_lib::models::Season);
+pub struct GqlWorld(pub paddlers_shared_lib::models::World);
 
 // Complete list of public objects with restricted fields access.
 pub struct GqlBuilding(pub paddlers_shared_lib::models::Building);
 pub struct GqlPlayer(pub paddlers_shared_lib::models::Player);
 pub struct GqlVillage(pub paddlers_shared_lib::models::Village);
+pub struct GqlSeasonStanding(pub paddlers_shared_lib::models::SeasonStanding);
 pub type Rewards = Vec<(ResourceType, i64)>; // Note: This is a bit of a botch and should be considered to change
 
 // Complete list of private objects.
@@ -47,6 +51,7 @@ pub type Rewards = Vec<(ResourceType, i64)>; // Note: This is a bit of a botch a
 struct PrivacyGuard;
 pub struct GqlAbility(pub paddlers_shared_lib::models::Ability, PrivacyGuard);
 pub struct GqlAttack(pub paddlers_shared_lib::models::Attack, PrivacyGuard);
+pub struct GqlPastAttack(pub paddlers_shared_lib::models::PastAttack, PrivacyGuard);
 pub struct GqlAttackReport {
     pub inner: paddlers_shared_lib::models::VisitReport,
     pub rewards: Option<Rewards>,
@@ -78,6 +83,12 @@ impl GqlPlayer {
     fn village_count(&self, ctx: &Context) -> i32 {
         ctx.db().player_village_count(PlayerKey(self.0.id)) as i32
     }
+    /// Field Visibility: public
+    fn world(&self, ctx: &Context) -> FieldResult<GqlWorld> {
+        Ok(GqlWorld(
+            ctx.db().world(self.0.world()).ok_or("No such world")?,
+        ))
+    }
     /// Number of (hobo) prophets that are currently owned by the player
     /// Field Visibility: user
     fn prophet_count(&self, ctx: &Context) -> FieldResult<i32> {
@@ -90,6 +101,12 @@ impl GqlPlayer {
         ctx.check_user_key(self.0.key())?;
         Ok(self.0.story_state)
     }
+    /// Code to invite friends, their villages will be placed nearby
+    /// Field Visibility: user
+    fn invite_code(&self, ctx: &Context) -> FieldResult<String> {
+        ctx.check_user_key(self.0.key())?;
+        Ok(self.0.invite_code.clone())
+    }
 }
 
 #[juniper::object (Context = Context)]
@@ -113,27 +130,34 @@ impl GqlVillage {
     /// Field Visibility: user
     fn sticks(&self, ctx: &Context) -> FieldResult<i32> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx.db().resource(ResourceType::Sticks, self.0.key()) as i32)
+        Ok(ctx
+            .db()
+            .resource_with_income(ResourceType::Sticks, self.0.key()) as i32)
     }
     /// Field Visibility: user
     fn feathers(&self, ctx: &Context) -> FieldResult<i32> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx.db().resource(ResourceType::Feathers, self.0.key()) as i32)
+        Ok(ctx
+            .db()
+            .resource_with_income(ResourceType::Feathers, self.0.key()) as i32)
     }
     /// Field Visibility: user
     fn logs(&self, ctx: &Context) -> FieldResult<i32> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx.db().resource(ResourceType::Logs, self.0.key()) as i32)
+        Ok(ctx
+            .db()
+            .resource_with_income(ResourceType::Logs, self.0.key()) as i32)
     }
     /// Field Visibility: user
     fn workers(&self, ctx: &Context) -> FieldResult<Vec<GqlWorker>> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx
-            .db()
-            .workers(self.0.key())
-            .into_iter()
-            .map(GqlWorker::authorized)
-            .collect())
+        let workers = ctx.db().workers(self.0.key());
+        let keys: Vec<WorkerKey> = workers.iter().map(Worker::key).collect();
+        let loaders = ctx.loaders();
+        loaders.tasks.register(keys.iter().copied());
+        loaders.abilities.register(keys.iter().copied());
+        loaders.worker_flags.register(keys);
+        Ok(workers.into_iter().map(GqlWorker::authorized).collect())
     }
     /// Field Visibility: public
     fn buildings(&self, ctx: &Context) -> FieldResult<Vec<GqlBuilding>> {
@@ -144,56 +168,102 @@ impl GqlVillage {
             .map(GqlBuilding)
             .collect())
     }
-    #[graphql(arguments(min_id(
-        description = "Response only contains attacks with id >= min_id",
-    )))]
+    #[graphql(arguments(
+        first(description = "Maximum number of attacks returned, at most 100"),
+        after(description = "Cursor of the last attack received before"),
+    ))]
     /// Field Visibility: user
-    fn attacks(&self, ctx: &Context, min_id: Option<i32>) -> FieldResult<Vec<GqlAttack>> {
+    fn attacks(
+        &self,
+        ctx: &Context,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<GqlAttackConnection> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx
+        let page = PageRequest::new(first, after)?;
+        let attacks = ctx
             .db()
-            .attacks(self.0.key(), min_id.map(i64::from))
-            .into_iter()
-            .map(GqlAttack::authorized)
-            .collect())
+            .attacks_page(self.0.key(), page.after, page.fetch_limit());
+        let page = page.page(attacks, |atk| atk.id);
+        let loaders = ctx.loaders();
+        let attacks = page.map(|attacks| {
+            loaders
+                .attack_units
+                .register(attacks.iter().map(Attack::key));
+            loaders
+                .village_owners
+                .register(attacks.iter().filter_map(Attack::origin));
+            attacks.into_iter().map(GqlAttack::authorized).collect()
+        });
+        Ok(GqlAttackConnection::new(
+            attacks,
+            Total::Attacks(self.0.key()),
+        ))
+    }
+    /// Attacks on the village that are over, the oldest first
+    /// Field Visibility: user
+    fn past_attacks(
+        &self,
+        ctx: &Context,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<GqlPastAttackConnection> {
+        ctx.check_village_key(self.0.key())?;
+        let page = PageRequest::new(first, after)?;
+        let attacks = ctx
+            .db()
+            .past_attacks_page(self.0.key(), page.after, page.fetch_limit());
+        let page = page.page(attacks, |atk| atk.id);
+        let loaders = ctx.loaders();
+        let attacks = page.map(|attacks| {
+            loaders
+                .village_owners
+                .register(attacks.iter().filter_map(PastAttack::origin));
+            attacks.into_iter().map(GqlPastAttack::authorized).collect()
+        });
+        Ok(GqlPastAttackConnection::new(
+            attacks,
+            Total::PastAttacks(self.0.key()),
+        ))
     }
     /// Field Visibility: public
     fn owner(&self, ctx: &Context) -> FieldResult<Option<GqlPlayer>> {
-        Ok(if let Some(owner) = self.0.player_id {
-            let key = PlayerKey(owner as i64);
-            let player = ctx.db().player(key).ok_or("Invalid owner key on village")?;
-            Some(GqlPlayer(player))
-        } else {
-            None
-        })
+        if self.0.player_id.is_none() {
+            return Ok(None);
+        }
+        let player = ctx
+            .loaders()
+            .village_owners
+            .load_one(self.0.key(), |keys| ctx.db().village_owners(keys))
+            .ok_or("Invalid owner key on village")?;
+        Ok(Some(GqlPlayer(player)))
     }
     /// Field Visibility: user
     fn hobos(&self, ctx: &Context) -> FieldResult<Vec<GqlHobo>> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx
-            .db()
-            .village_hobos(self.0.key())
-            .into_iter()
-            .map(GqlHobo)
-            .collect())
+        let hobos = ctx.db().village_hobos(self.0.key());
+        ctx.loaders().effects.register(hobos.iter().map(Hobo::key));
+        Ok(hobos.into_iter().map(GqlHobo).collect())
     }
     /// Field Visibility: user
-    fn reports(&self, ctx: &Context, min_id: Option<i32>) -> FieldResult<Vec<GqlAttackReport>> {
+    fn reports(
+        &self,
+        ctx: &Context,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<GqlAttackReportConnection> {
         ctx.check_village_key(self.0.key())?;
-        Ok(ctx
+        let page = PageRequest::new(first, after)?;
+        let reports = ctx
             .db()
-            .reports(self.0.key(), min_id.map(i64::from))
-            .into_iter()
-            .map(|report| GqlAttackReport {
-                inner: report,
-                rewards: None,
-                _priv: PrivacyGuard,
-            })
-            .map(|mut rep| {
-                rep.load_rewards(ctx);
-                rep
-            })
-            .collect())
+            .reports_page(self.0.key(), page.after, page.fetch_limit());
+        let reports = page
+            .page(reports, |report| report.id)
+            .map(|reports| GqlAttackReport::with_rewards(ctx, reports));
+        Ok(GqlAttackReportConnection::new(
+            reports,
+            Total::Reports(self.0.key()),
+        ))
     }
 }
 
@@ -247,8 +317,15 @@ impl GqlHobo {
     }
     /// Field Visibility: public
     pub fn effects(&self, ctx: &Context) -> Vec<GqlEffect> {
-        ctx.db()
-            .effects_on_hobo(HoboKey(self.0.id))
+        ctx.loaders()
+            .effects
+            .load(self.0.key(), |keys| {
+                ctx.db()
+                    .effects_on_hobos(keys)
+                    .into_iter()
+                    .map(|effect| (HoboKey(effect.hobo_id), effect))
+                    .collect()
+            })
             .into_iter()
             .map(GqlEffect::authorized)
             .collect()
@@ -272,18 +349,25 @@ impl GqlMapSlice {
     /// Field Visibility: public
     fn streams(&self, ctx: &Context) -> Vec<GqlStream> {
         ctx.db()
-            .streams(self.low_x as f32, self.high_x as f32)
+            .streams(self.world, self.low_x as f32, self.high_x as f32)
             .into_iter()
             .map(|t| GqlStream(t))
             .collect()
     }
     /// Field Visibility: public
     fn villages(&self, ctx: &Context) -> Vec<GqlVillage> {
-        ctx.db()
-            .villages(self.low_x as f32, self.high_x as f32)
-            .into_iter()
-            .map(|t| GqlVillage(t))
-            .collect()
+        let villages = ctx
+            .db()
+            .villages(self.world, self.low_x as f32, self.high_x as f32);
+        ctx.loaders()
+            .village_owners
+            .register(villages.iter().map(Village::key));
+        villages.into_iter().map(|t| GqlVillage(t)).collect()
+    }
+    /// Current width of the whole map, it grows when more players join
+    /// Field Visibility: public
+    fn world_width(&self, ctx: &Context) -> i32 {
+        ctx.db().map_width(self.world)
     }
 }
 
@@ -299,6 +383,87 @@ impl GqlStream {
     }
 }
 
+#[juniper::object (Context = Context)]
+impl GqlSeason {
+    /// Field Visibility: public
+    fn id(&self) -> i32 {
+        self.0.id as i32
+    }
+    /// Field Visibility: public
+    fn start_time(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.start_time)
+    }
+    /// Not set for the season that is currently running
+    /// Field Visibility: public
+    fn end_time(&self) -> FieldResult<Option<GqlTimestamp>> {
+        self.0.end_time.as_ref().map(datetime).transpose()
+    }
+    /// Final standings of a past season in one world, up to 100 players starting from the given rank upwards
+    /// Without a world specified, the standings of the player's own world are returned
+    /// Field Visibility: public
+    fn standings(
+        &self,
+        ctx: &Context,
+        rank_offset: i32,
+        world_id: Option<i32>,
+    ) -> FieldResult<Vec<GqlSeasonStanding>> {
+        let world = ctx.world_or_own(world_id)?;
+        Ok(ctx
+            .db()
+            .season_standings(self.0.key(), world, rank_offset as i64, 100)
+            .into_iter()
+            .map(GqlSeasonStanding)
+            .collect())
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlWorld {
+    /// Field Visibility: public
+    fn id(&self) -> i32 {
+        self.0.id as i32
+    }
+    /// Field Visibility: public
+    fn display_name(&self) -> &str {
+        &self.0.display_name
+    }
+    /// Field Visibility: public
+    fn player_count(&self, ctx: &Context) -> i32 {
+        ctx.db().world_player_count(self.0.key()) as i32
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlSeasonStanding {
+    /// Field Visibility: public
+    fn rank(&self) -> i32 {
+        self.0.rank as i32
+    }
+    /// Field Visibility: public
+    fn display_name(&self, ctx: &Context) -> FieldResult<String> {
+        let player = ctx
+            .db()
+            .player(PlayerKey(self.0.player_id))
+            .ok_or("No such player")?;
+        Ok(player.display_name)
+    }
+    /// Karma at the end of the season
+    /// Field Visibility: public
+    fn karma(&self) -> i32 {
+        self.0.karma as i32
+    }
+    /// Field Visibility: public
+    fn village_count(&self) -> i32 {
+        self.0.village_count as i32
+    }
+    /// Story progress reached in the season
+    /// Field Visibility: user
+    fn story_state(&self, ctx: &Context) -> FieldResult<StoryState> {
+        ctx.check_user_key(PlayerKey(self.0.player_id))?;
+        Ok(self.0.story_state)
+    }
+}
+
 /*
  * Constructors to use after authorization.
  * Secrecy model only works if these are only called properly!
@@ -313,6 +478,38 @@ impl GqlAttack {
         GqlAttack(inner, PrivacyGuard)
     }
 }
+impl GqlPastAttack {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::PastAttack) -> Self {
+        GqlPastAttack(inner, PrivacyGuard)
+    }
+}
+impl GqlAttackReport {
+    /// The rewards of all reports are loaded with a single query
+    pub(super) fn with_rewards(
+        ctx: &Context,
+        reports: Vec<paddlers_shared_lib::models::VisitReport>,
+    ) -> Vec<Self> {
+        let keys: Vec<VisitReportKey> = reports.iter().map(VisitReport::key).collect();
+        let mut rewards: std::collections::BTreeMap<i64, Rewards> = Default::default();
+        for reward in ctx.db().reports_rewards(&keys) {
+            let sums = rewards
+                .entry(reward.visit_report_id)
+                .or_insert_with(Vec::new);
+            match sums.iter_mut().find(|(rt, _)| *rt == reward.resource_type) {
+                Some((_, n)) => *n += reward.amount,
+                None => sums.push((reward.resource_type, reward.amount)),
+            }
+        }
+        reports
+            .into_iter()
+            .map(|report| GqlAttackReport {
+                rewards: Some(rewards.remove(&report.id).unwrap_or_default()),
+                inner: report,
+                _priv: PrivacyGuard,
+            })
+            .collect()
+    }
+}
 impl GqlEffect {
     pub(super) fn authorized(inner: paddlers_shared_lib::models::Effect) -> Self {
         GqlEffect(inner, PrivacyGuard)
@@ -0,0 +1,80 @@
+//! Per-request batching of database lookups.
+//!
+//! Juniper resolves one object after the other, thus a plain resolver
+//! issues one query per parent object. To avoid that, resolvers that
+//! return a list register the keys of all listed objects with the
+//! loaders of their child fields. When the first child field is
+//! resolved, the children of all registered parents are loaded with a
+//! single query.
+//!
+//! Loaders live in the context of a single request, nothing is cached
+//! across requests.
+
+use paddlers_shared_lib::prelude::*;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+pub struct Loaders {
+    pub tasks: BatchLoader<WorkerKey, Task>,
+    pub abilities: BatchLoader<WorkerKey, Ability>,
+    pub worker_flags: BatchLoader<WorkerKey, WorkerFlag>,
+    pub attack_units: BatchLoader<AttackKey, (Hobo, AttackToHobo)>,
+    pub effects: BatchLoader<HoboKey, Effect>,
+    pub village_owners: BatchLoader<VillageKey, Player>,
+}
+
+pub struct BatchLoader<K, V> {
+    pending: RefCell<Vec<K>>,
+    loaded: RefCell<BTreeMap<K, Vec<V>>>,
+}
+
+impl<K: Ord, V> Default for BatchLoader<K, V> {
+    fn default() -> Self {
+        BatchLoader {
+            pending: RefCell::new(vec![]),
+            loaded: RefCell::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<K: Ord + Copy, V: Clone> BatchLoader<K, V> {
+    /// Announces keys that will probably be loaded later in the same request
+    pub fn register(&self, keys: impl IntoIterator<Item = K>) {
+        let loaded = self.loaded.borrow();
+        self.pending
+            .borrow_mut()
+            .extend(keys.into_iter().filter(|k| !loaded.contains_key(k)));
+    }
+    /// Returns all values belonging to the key.
+    /// If they have not been loaded, yet, `batch` is called once
+    /// for the key and all pending keys and has to return all
+    /// values found, paired with the key they belong to.
+    pub fn load<F>(&self, key: K, batch: F) -> Vec<V>
+    where
+        F: FnOnce(&[K]) -> Vec<(K, V)>,
+    {
+        if !self.loaded.borrow().contains_key(&key) {
+            let mut keys = std::mem::replace(&mut *self.pending.borrow_mut(), vec![]);
+            keys.push(key);
+            keys.sort();
+            keys.dedup();
+            let mut results: BTreeMap<K, Vec<V>> = keys.iter().map(|k| (*k, vec![])).collect();
+            for (k, v) in batch(&keys) {
+                results.entry(k).or_insert_with(Vec::new).push(v);
+            }
+            self.loaded.borrow_mut().extend(results);
+        }
+        self.loaded.borrow()[&key].clone()
+    }
+    /// Like `load` for keys with at most one value
+    pub fn load_one<F>(&self, key: K, batch: F) -> Option<V>
+    where
+        F: FnOnce(&[K]) -> Vec<(K, V)>,
+    {
+        self.load(key, batch).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod loaders_test;
@@ -0,0 +1,157 @@
+use super::*;
+use crate::graphql::{new_schema, AuthenticatedContext, Context};
+use crate::sql::statement_counter;
+use crate::DbConn;
+use diesel::prelude::*;
+use paddlers_shared_lib::schema::*;
+use paddlers_shared_lib::sql_db::run_db_migrations;
+use std::cell::Cell;
+use std::sync::Arc;
+
+const VILLAGE_UNITS_QUERY: &str =
+    include_str!("../../../../paddlers-frontend/api/queries/village_units_query.graphql");
+const WORKERS: usize = 5;
+
+#[test]
+fn registered_keys_are_loaded_in_one_batch() {
+    let loader = BatchLoader::<WorkerKey, i64>::default();
+    loader.register(vec![WorkerKey(1), WorkerKey(2), WorkerKey(3)]);
+    let batches = Cell::new(0);
+    let batch = |keys: &[WorkerKey]| -> Vec<(WorkerKey, i64)> {
+        batches.set(batches.get() + 1);
+        keys.iter()
+            .filter(|k| k.num() != 2)
+            .map(|k| (*k, k.num() * 10))
+            .collect()
+    };
+
+    assert_eq!(loader.load(WorkerKey(1), batch), vec![10]);
+    assert_eq!(loader.load(WorkerKey(2), batch), Vec::<i64>::new());
+    assert_eq!(loader.load(WorkerKey(3), batch), vec![30]);
+    assert_eq!(batches.get(), 1);
+
+    // Keys that have not been registered are loaded on their own
+    assert_eq!(loader.load(WorkerKey(4), batch), vec![40]);
+    assert_eq!(batches.get(), 2);
+}
+
+/// Requires a database, the test data is rolled back afterwards
+#[test]
+#[ignore]
+fn village_units_query_is_batched() {
+    let conn = test_connection();
+    run_db_migrations(conn.dbconn()).expect("Migrations failed");
+    conn.dbconn()
+        .begin_test_transaction()
+        .expect("Starting test transaction");
+    let (player, village) = test_village(&conn);
+    let schema = new_schema();
+    let ctx = Context::Authenticated(AuthenticatedContext {
+        db: Arc::new(conn),
+        user: player,
+        villages: vec![village],
+        loaders: Loaders::default(),
+    });
+    let mut variables = juniper::Variables::new();
+    variables.insert(
+        "village_id".to_owned(),
+        juniper::InputValue::scalar(village.num() as i32),
+    );
+
+    statement_counter::reset();
+    let (result, errors) = juniper::execute(VILLAGE_UNITS_QUERY, None, &schema, &variables, &ctx)
+        .expect("Query failed");
+    let statements = statement_counter::count();
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    let workers = result
+        .as_object_value()
+        .and_then(|data| data.get_field_value("village"))
+        .and_then(|village| village.as_object_value())
+        .and_then(|village| village.get_field_value("workers"))
+        .and_then(|workers| workers.as_list_value())
+        .expect("No workers in response");
+    assert_eq!(workers.len(), WORKERS);
+    // village + workers + tasks + abilities + worker flags (for mana)
+    assert_eq!(statements, 5);
+}
+
+fn test_connection() -> DbConn {
+    let config = Config::from_env().unwrap_or(Config::default());
+    let rocket_config = rocket::config::Config::build(rocket::config::Environment::Development)
+        .extra("databases", crate::database_config(&config))
+        .finalize()
+        .expect("Check Configuration");
+    let rocket = rocket::custom(rocket_config).attach(DbConn::fairing());
+    DbConn::get_one(&rocket).expect("No database connection")
+}
+
+/// Creates a new player with a village full of workers that have tasks and abilities
+fn test_village(conn: &DbConn) -> (Player, VillageKey) {
+    let db = conn.dbconn();
+    let unique = chrono::Utc::now().timestamp_nanos();
+    let stream: Stream = diesel::insert_into(streams::table)
+        .values(&NewStream {
+            start_x: 0.0,
+            control_points: vec![0.0, 0.0],
+            world_id: 1,
+        })
+        .get_result(db)
+        .expect("Inserting stream");
+    let player: Player = diesel::insert_into(players::table)
+        .values(&NewPlayer {
+            uuid: uuid::Uuid::from_u128(unique as u128),
+            karma: 0,
+            display_name: format!("Loader Test {}", unique),
+            world_id: 1,
+            invite_code: format!("loaders{}", unique),
+        })
+        .get_result(db)
+        .expect("Inserting player");
+    let village: Village = diesel::insert_into(villages::table)
+        .values(&NewVillage {
+            x: 0.0,
+            y: 0.0,
+            stream_id: stream.id,
+            player_id: Some(player.id),
+            faith: None,
+            world_id: 1,
+        })
+        .get_result(db)
+        .expect("Inserting village");
+    for i in 0..WORKERS {
+        let worker: Worker = diesel::insert_into(workers::table)
+            .values(&NewWorker {
+                home: village.id,
+                x: i as i32,
+                y: 0,
+                unit_type: UnitType::Basic,
+                color: Some(UnitColor::Yellow),
+                speed: 0.1,
+                mana: Some(0),
+                level: 1,
+                exp: 0,
+            })
+            .get_result(db)
+            .expect("Inserting worker");
+        diesel::insert_into(tasks::table)
+            .values(&NewTask {
+                worker_id: worker.id,
+                task_type: TaskType::Idle,
+                x: i as i32,
+                y: 0,
+                start_time: None,
+                target_hobo_id: None,
+            })
+            .execute(db)
+            .expect("Inserting task");
+        diesel::insert_into(abilities::table)
+            .values(&NewAbility {
+                ability_type: AbilityType::Work,
+                worker_id: worker.id,
+            })
+            .execute(db)
+            .expect("Inserting ability");
+    }
+    (player, village.key())
+}
@@ -1,14 +1,18 @@
 use super::DbConn;
 
-use juniper::FieldError;
-use juniper_rocket::{self, GraphQLRequest, GraphQLResponse};
+use juniper::http::GraphQLRequest;
+use juniper::{FieldError, InputValue};
+use juniper_rocket::{self, GraphQLResponse};
+use rocket::data::{self, Data, FromDataSimple};
 use rocket::http::Status;
-use rocket::request::{self, FromRequest, Request};
+use rocket::request::{self, Form, FormItems, FromForm, FromRequest, Request};
 use rocket::response::content;
 use rocket::Outcome;
 use rocket::State;
+use std::io::Read;
 
-use crate::graphql::Schema;
+use crate::graphql::{Context, Schema};
+use crate::limits::QueryLimits;
 use paddlers_shared_lib::prelude::{Config, PadlApiError};
 use paddlers_shared_lib::user_authentication::*;
 
@@ -27,33 +31,43 @@ pub fn graphiql() -> content::Html<String> {
     juniper_rocket::graphiql_source("/graphql/")
 }
 
-#[get("/?<request>")]
+#[get("/?<request..>")]
 pub fn get_graphql_handler(
     connection: DbConn,
-    request: GraphQLRequest,
+    request: Form<GraphQLQuery>,
     schema: State<Schema>,
+    limits: State<QueryLimits>,
     user_info: UserInfo,
 ) -> GraphQLResponse {
-    generic_graphql_handler(connection, request, schema, user_info)
+    generic_graphql_handler(connection, request.into_inner(), schema, limits, user_info)
 }
 
 #[post("/", data = "<request>")]
 pub fn post_graphql_handler(
     connection: DbConn,
-    request: GraphQLRequest,
+    request: GraphQLQuery,
     schema: State<Schema>,
+    limits: State<QueryLimits>,
     user_info: UserInfo,
 ) -> GraphQLResponse {
-    generic_graphql_handler(connection, request, schema, user_info)
+    generic_graphql_handler(connection, request, schema, limits, user_info)
 }
 
 fn generic_graphql_handler(
     connection: DbConn,
-    request: GraphQLRequest,
+    request: GraphQLQuery,
     schema: State<Schema>,
+    limits: State<QueryLimits>,
     user_info: UserInfo,
 ) -> GraphQLResponse {
-    if let Some(player_ctx) = crate::graphql::Context::new(connection, user_info.user) {
+    if let Err(e) = limits.check_batch(&schema, &request.documents) {
+        let err = FieldError::new(
+            e.to_string(),
+            graphql_value!({ "internal_error": "Query too complex" }),
+        );
+        return juniper_rocket::GraphQLResponse::error(err);
+    }
+    if let Some(player_ctx) = Context::new(connection, user_info.user) {
         request.execute(&schema, &player_ctx)
     } else {
         // Lookup error code from shared lib that frontend understands
@@ -89,3 +103,109 @@ impl<'a, 'r> FromRequest<'a, 'r> for UserInfo {
         }
     }
 }
+
+/// GraphQL request with a single query or a batch of queries.
+/// Unlike `juniper_rocket::GraphQLRequest`, the query documents remain
+/// readable, which is necessary to check their limits before execution.
+pub struct GraphQLQuery {
+    requests: Vec<GraphQLRequest>,
+    batch: bool,
+    documents: Vec<String>,
+}
+
+impl GraphQLQuery {
+    fn from_json(body: &str) -> Result<Self, String> {
+        let json: serde_json::Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        let (batch, items) = match json {
+            serde_json::Value::Array(batch) => (true, batch),
+            single => (false, vec![single]),
+        };
+        let documents = items
+            .iter()
+            .map(|request| request["query"].as_str().map(str::to_owned))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("Query missing in request")?;
+        let requests = items
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<GraphQLRequest>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(GraphQLQuery {
+            requests,
+            batch,
+            documents,
+        })
+    }
+    /// Same as `juniper_rocket::GraphQLRequest::execute`
+    fn execute(&self, schema: &Schema, ctx: &Context) -> GraphQLResponse {
+        let responses: Vec<_> = self
+            .requests
+            .iter()
+            .map(|request| request.execute(schema, ctx))
+            .collect();
+        let status = if responses.iter().all(|response| response.is_ok()) {
+            Status::Ok
+        } else {
+            Status::BadRequest
+        };
+        let json = if self.batch {
+            serde_json::to_string(&responses)
+        } else {
+            serde_json::to_string(&responses[0])
+        }
+        .expect("Serializing GraphQL response");
+        GraphQLResponse(status, json)
+    }
+}
+
+impl FromDataSimple for GraphQLQuery {
+    type Error = String;
+
+    fn from_data(request: &Request, data: Data) -> data::Outcome<Self, Self::Error> {
+        if !request.content_type().map_or(false, |ct| ct.is_json()) {
+            return Outcome::Forward(data);
+        }
+        let mut body = String::new();
+        if let Err(e) = data.open().read_to_string(&mut body) {
+            return Outcome::Failure((Status::InternalServerError, e.to_string()));
+        }
+        match GraphQLQuery::from_json(&body) {
+            Ok(query) => Outcome::Success(query),
+            Err(e) => Outcome::Failure((Status::BadRequest, e)),
+        }
+    }
+}
+
+impl<'f> FromForm<'f> for GraphQLQuery {
+    type Error = String;
+
+    fn from_form(items: &mut FormItems<'f>, strict: bool) -> Result<Self, String> {
+        let mut query = None;
+        let mut operation_name = None;
+        let mut variables = None;
+        for item in items {
+            let (key, value) = item.key_value_decoded();
+            match key.as_str() {
+                "query" => query = Some(value),
+                "operation_name" => operation_name = Some(value),
+                "variables" => {
+                    let parsed: InputValue =
+                        serde_json::from_str(&value).map_err(|e| e.to_string())?;
+                    variables = Some(parsed);
+                }
+                _ if strict => return Err(format!("Unexpected parameter {}", key)),
+                _ => {}
+            }
+        }
+        let query = query.ok_or("Query missing in request")?;
+        Ok(GraphQLQuery {
+            requests: vec![GraphQLRequest::new(
+                query.clone(),
+                operation_name,
+                variables,
+            )],
+            batch: false,
+            documents: vec![query],
+        })
+    }
+}
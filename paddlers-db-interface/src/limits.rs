@@ -0,0 +1,389 @@
+//! Limits on the depth and the estimated cost of GraphQL queries.
+//!
+//! The schema has cycles (player -> villages -> owner -> villages -> ...),
+//! hence a small query document can describe an exponential amount of work.
+//! Every query document is analyzed before it is executed and rejected if
+//! it exceeds the limits.
+//!
+//! The cost of a query is an estimate of how many values it returns.
+//! Each field costs one, fields returning a list multiply the cost of their
+//! sub-selection by the expected number of elements.
+//! For the edges of a connection, that is the page size requested with `first`.
+//!
+//! The costs of all documents in a batched request add up.
+//!
+//! Fields below `__schema` and `__type` have separate limits, as the standard introspection query
+//! used to generate the schema of the frontend is deeper and more expensive than any regular query.
+//! The introspection types have cycles (`__Type` -> `fields` -> `type` -> `fields` -> ...), too,
+//! hence these limits must not be lifted.
+
+use crate::graphql::{Schema, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use graphql_parser::query::{
+    parse_query, Definition, Document, OperationDefinition, Selection, SelectionSet, TypeCondition,
+    Value,
+};
+use juniper::meta::MetaType;
+use juniper::Type;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+
+const DEFAULT_MAX_DEPTH: usize = 10;
+const DEFAULT_MAX_COST: usize = 10_000;
+/// Expected length of lists without a fixed size
+const DEFAULT_LIST_SIZE: usize = 10;
+/// Just enough for the standard introspection query
+const MAX_INTROSPECTION_DEPTH: usize = 13;
+const MAX_INTROSPECTION_COST: usize = 50_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct QueryLimits {
+    pub max_depth: usize,
+    pub max_cost: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LimitError {
+    TooDeep { depth: usize, max: usize },
+    TooExpensive { cost: usize, max: usize },
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        QueryLimits {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_cost: DEFAULT_MAX_COST,
+        }
+    }
+}
+
+impl QueryLimits {
+    /// Limits can be set with `GRAPHQL_MAX_DEPTH` and `GRAPHQL_MAX_COST`
+    pub fn from_env() -> Self {
+        let defaults = QueryLimits::default();
+        QueryLimits {
+            max_depth: env_or("GRAPHQL_MAX_DEPTH", defaults.max_depth),
+            max_cost: env_or("GRAPHQL_MAX_COST", defaults.max_cost),
+        }
+    }
+    /// Checks all operations of the query document.
+    pub fn check(&self, schema: &Schema, query: &str) -> Result<(), LimitError> {
+        self.check_batch(schema, &[query])
+    }
+    /// Checks all documents of a batched request, the limit on the cost applies to their sum.
+    pub fn check_batch<Q: AsRef<str>>(
+        &self,
+        schema: &Schema,
+        queries: &[Q],
+    ) -> Result<(), LimitError> {
+        let mut total = Measure::default();
+        for query in queries {
+            total = total.sum(self.document_cost(schema, query.as_ref())?);
+            self.check_cost(total)?;
+        }
+        Ok(())
+    }
+    /// Checks the depth of all operations in the document and returns the cost of the most
+    /// expensive one, as only one of them is executed.
+    /// Documents that cannot be parsed are accepted, execution rejects them anyway.
+    fn document_cost(&self, schema: &Schema, query: &str) -> Result<Measure, LimitError> {
+        let document = match parse_query(query) {
+            Ok(document) => document,
+            Err(_) => return Ok(Measure::default()),
+        };
+        let analysis = Analysis::new(schema, &document);
+        let mut max_cost = Measure::default();
+        for definition in &document.definitions {
+            if let Definition::Operation(operation) = definition {
+                let (root, selection_set) = match operation {
+                    OperationDefinition::SelectionSet(selection_set) => ("Query", selection_set),
+                    OperationDefinition::Query(query) => ("Query", &query.selection_set),
+                    OperationDefinition::Mutation(mutation) => {
+                        ("Mutation", &mutation.selection_set)
+                    }
+                    // Not supported by the schema, execution rejects them
+                    OperationDefinition::Subscription(_) => continue,
+                };
+                let depth = analysis.depth(selection_set);
+                if depth.regular > self.max_depth {
+                    return Err(LimitError::TooDeep {
+                        depth: depth.regular,
+                        max: self.max_depth,
+                    });
+                }
+                if depth.introspection > MAX_INTROSPECTION_DEPTH {
+                    return Err(LimitError::TooDeep {
+                        depth: depth.introspection,
+                        max: MAX_INTROSPECTION_DEPTH,
+                    });
+                }
+                let cost = analysis.cost(selection_set, Some(root), 1);
+                self.check_cost(cost)?;
+                max_cost = max_cost.max(cost);
+            }
+        }
+        Ok(max_cost)
+    }
+    fn check_cost(&self, cost: Measure) -> Result<(), LimitError> {
+        if cost.regular > self.max_cost {
+            return Err(LimitError::TooExpensive {
+                cost: cost.regular,
+                max: self.max_cost,
+            });
+        }
+        if cost.introspection > MAX_INTROSPECTION_COST {
+            return Err(LimitError::TooExpensive {
+                cost: cost.introspection,
+                max: MAX_INTROSPECTION_COST,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Depth or cost, counted separately for regular fields and fields of the introspection schema
+#[derive(Debug, Default, Clone, Copy)]
+struct Measure {
+    regular: usize,
+    introspection: usize,
+}
+
+impl Measure {
+    /// Everything below `__schema` and `__type` belongs to the introspection schema
+    fn introspection(n: usize) -> Self {
+        Measure {
+            regular: 0,
+            introspection: n,
+        }
+    }
+    fn total(self) -> usize {
+        self.regular.saturating_add(self.introspection)
+    }
+    fn max(self, other: Self) -> Self {
+        Measure {
+            regular: self.regular.max(other.regular),
+            introspection: self.introspection.max(other.introspection),
+        }
+    }
+    fn sum(self, other: Self) -> Self {
+        Measure {
+            regular: self.regular.saturating_add(other.regular),
+            introspection: self.introspection.saturating_add(other.introspection),
+        }
+    }
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LimitError::TooDeep { depth, max } => write!(
+                f,
+                "Query is nested {} levels deep, the maximum is {}.",
+                depth, max
+            ),
+            LimitError::TooExpensive { cost, max } => write!(
+                f,
+                "Query has an estimated cost of {}, the maximum is {}.",
+                cost, max
+            ),
+        }
+    }
+}
+
+/// Fragments are analyzed once and their results are reused for each spread,
+/// otherwise spreads of fragments that spread other fragments would take exponential time.
+struct Analysis<'a> {
+    schema: &'a Schema,
+    fragments: BTreeMap<&'a str, (&'a str, &'a SelectionSet)>,
+    fragment_depths: RefCell<BTreeMap<&'a str, Measure>>,
+    fragment_costs: RefCell<BTreeMap<&'a str, Measure>>,
+    visiting: RefCell<Vec<&'a str>>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(schema: &'a Schema, document: &'a Document) -> Self {
+        let fragments = document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Fragment(fragment) => {
+                    let TypeCondition::On(condition) = &fragment.type_condition;
+                    Some((
+                        fragment.name.as_str(),
+                        (condition.as_str(), &fragment.selection_set),
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        Analysis {
+            schema,
+            fragments,
+            fragment_depths: Default::default(),
+            fragment_costs: Default::default(),
+            visiting: Default::default(),
+        }
+    }
+    /// Deepest nesting of fields
+    fn depth(&self, selections: &'a SelectionSet) -> Measure {
+        selections
+            .items
+            .iter()
+            .map(|selection| match selection {
+                Selection::Field(field) => {
+                    let sub = self.depth(&field.selection_set);
+                    if introspection_type(&field.name).is_some() {
+                        Measure::introspection(1 + sub.total())
+                    } else {
+                        Measure {
+                            regular: 1 + sub.regular,
+                            introspection: sub.introspection,
+                        }
+                    }
+                }
+                Selection::InlineFragment(fragment) => self.depth(&fragment.selection_set),
+                Selection::FragmentSpread(spread) => {
+                    self.fragment(&spread.fragment_name, &self.fragment_depths, |_, sub| {
+                        self.depth(sub)
+                    })
+                }
+            })
+            .fold(Measure::default(), Measure::max)
+    }
+    /// Estimated number of values returned for the selections on an object of the given type.
+    /// `page` is the number of edges expected if the object is a connection.
+    fn cost(&self, selections: &'a SelectionSet, type_name: Option<&str>, page: usize) -> Measure {
+        let meta_type = type_name.and_then(|name| self.schema.schema.concrete_type_by_name(name));
+        let in_connection = type_name.map_or(false, is_connection);
+        selections
+            .items
+            .iter()
+            .map(|selection| match selection {
+                Selection::Field(field) => {
+                    if let Some(t) = introspection_type(&field.name) {
+                        let sub = self.cost(&field.selection_set, Some(t), 1);
+                        return Measure::introspection(sub.total().saturating_add(1));
+                    }
+                    let field_type = meta_type.and_then(|t| field_type(t, &field.name));
+                    let field_type_name = field_type.map(Type::innermost_name);
+                    let sub_page = match field_type_name {
+                        Some(t) if is_connection(t) => page_size(
+                            field
+                                .arguments
+                                .iter()
+                                .find(|(name, _)| name == "first")
+                                .map(|(_, value)| value),
+                        ),
+                        _ => 1,
+                    };
+                    let sub = self.cost(&field.selection_set, field_type_name, sub_page);
+                    let elements = match field_type {
+                        Some(t) if is_list(t) && in_connection => page,
+                        Some(t) if is_list(t) => DEFAULT_LIST_SIZE,
+                        _ => 1,
+                    };
+                    Measure {
+                        regular: elements.saturating_mul(sub.regular).saturating_add(1),
+                        introspection: elements.saturating_mul(sub.introspection),
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let condition = fragment
+                        .type_condition
+                        .as_ref()
+                        .map(|TypeCondition::On(t)| t.as_str())
+                        .or(type_name);
+                    self.cost(&fragment.selection_set, condition, page)
+                }
+                // Fragments are analyzed only once, connections in them are assumed to return full pages
+                Selection::FragmentSpread(spread) => self.fragment(
+                    &spread.fragment_name,
+                    &self.fragment_costs,
+                    |condition, sub| self.cost(sub, Some(condition), MAX_PAGE_SIZE as usize),
+                ),
+            })
+            .fold(Measure::default(), Measure::sum)
+    }
+    /// Analyzes a fragment, or takes the result from the cache.
+    /// Unknown fragments and cycles count as empty, validation rejects them before execution.
+    fn fragment<F>(
+        &self,
+        name: &'a str,
+        cache: &RefCell<BTreeMap<&'a str, Measure>>,
+        f: F,
+    ) -> Measure
+    where
+        F: FnOnce(&'a str, &'a SelectionSet) -> Measure,
+    {
+        if let Some(result) = cache.borrow().get(name) {
+            return *result;
+        }
+        let (condition, selections) = match self.fragments.get(name) {
+            Some(fragment) => *fragment,
+            None => return Measure::default(),
+        };
+        if self.visiting.borrow().contains(&name) {
+            return Measure::default();
+        }
+        self.visiting.borrow_mut().push(name);
+        let result = f(condition, selections);
+        self.visiting.borrow_mut().pop();
+        cache.borrow_mut().insert(name, result);
+        result
+    }
+}
+
+fn field_type<'a>(
+    meta_type: &'a MetaType<'a, juniper::DefaultScalarValue>,
+    field_name: &str,
+) -> Option<&'a Type<'a>> {
+    meta_type
+        .field_by_name(field_name)
+        .map(|field| &field.field_type)
+}
+
+fn is_list(t: &Type) -> bool {
+    match t {
+        Type::List(_) | Type::NonNullList(_) => true,
+        Type::Named(_) | Type::NonNullNamed(_) => false,
+    }
+}
+
+/// Type returned by the introspection fields of the query root
+fn introspection_type(field_name: &str) -> Option<&'static str> {
+    match field_name {
+        "__schema" => Some("__Schema"),
+        "__type" => Some("__Type"),
+        _ => None,
+    }
+}
+
+fn is_connection(type_name: &str) -> bool {
+    type_name.ends_with("Connection")
+}
+
+/// Number of edges requested by the `first` argument of a connection field.
+/// Values given by variables are unknown, they count as the maximum.
+fn page_size(first: Option<&Value>) -> usize {
+    let size = match first {
+        None => DEFAULT_PAGE_SIZE as i64,
+        Some(Value::Int(n)) => n
+            .as_i64()
+            .map_or(MAX_PAGE_SIZE as i64, |n| n.max(0).min(MAX_PAGE_SIZE as i64)),
+        Some(_) => MAX_PAGE_SIZE as i64,
+    };
+    size as usize
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    match env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid {}: '{}', using {}", name, value, default);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+#[cfg(test)]
+mod limits_test;
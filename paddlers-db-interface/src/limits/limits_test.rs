@@ -0,0 +1,155 @@
+use super::*;
+use crate::graphql::new_schema;
+
+const FRONTEND_QUERIES: &[&str] = &[
+    include_str!("../../../paddlers-frontend/api/queries/attacks_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/buildings_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/hobos_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/leaderboard_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/map_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/player_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/player_villages_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/reports_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/village_units_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/volatile_village_info_query.graphql"),
+    include_str!("../../../paddlers-frontend/api/queries/worker_tasks_query.graphql"),
+    // Sent by `update_gql_schema.sh` to generate the schema of the frontend
+    include_str!("introspection_query.graphql"),
+];
+
+#[test]
+fn frontend_queries_are_accepted() {
+    let schema = new_schema();
+    let limits = QueryLimits::default();
+    for query in FRONTEND_QUERIES {
+        assert_eq!(limits.check(&schema, query), Ok(()), "{}", query);
+    }
+}
+
+#[test]
+fn deep_cycles_are_rejected() {
+    let schema = new_schema();
+    let query = "{ player(playerId: 1) { villages { owner { villages { owner { villages { owner \
+                 { villages { owner { villages { owner { displayName } } } } } } } } } } } }";
+    assert_eq!(
+        QueryLimits::default().check(&schema, query),
+        Err(LimitError::TooDeep { depth: 12, max: 10 })
+    );
+}
+
+#[test]
+fn expanded_scoreboard_is_rejected() {
+    let schema = new_schema();
+    let query =
+        "{ scoreboard(first: 100) { edges { node { villages { owner { villages { id } } } } } } }";
+    // 1 + 1 + 100 * (1 + 1 + 10 * (1 + (1 + 10 * 1)))
+    assert_eq!(
+        QueryLimits::default().check(&schema, query),
+        Err(LimitError::TooExpensive {
+            cost: 12202,
+            max: 10_000
+        })
+    );
+}
+
+#[test]
+fn fragments_are_resolved() {
+    let schema = new_schema();
+    let query = "query { scoreboard(first: 100) { edges { node { ...Expanded } } } }
+        fragment Expanded on GqlPlayer { villages { ... on GqlVillage { owner { ...Villages } } } }
+        fragment Villages on GqlPlayer { villages { id } }";
+    assert_eq!(
+        QueryLimits::default().check(&schema, query),
+        Err(LimitError::TooExpensive {
+            cost: 12202,
+            max: 10_000
+        })
+    );
+}
+
+#[test]
+fn limits_are_configurable() {
+    let schema = new_schema();
+    let query = "{ scoreboard(first: 100) { edges { node { displayName } } } }";
+    let strict = QueryLimits {
+        max_depth: 3,
+        max_cost: 10_000,
+    };
+    assert_eq!(
+        strict.check(&schema, query),
+        Err(LimitError::TooDeep { depth: 4, max: 3 })
+    );
+    let cheap = QueryLimits {
+        max_depth: 10,
+        max_cost: 200,
+    };
+    assert_eq!(
+        cheap.check(&schema, query),
+        Err(LimitError::TooExpensive {
+            cost: 202,
+            max: 200
+        })
+    );
+    assert_eq!(QueryLimits::default().check(&schema, query), Ok(()));
+}
+
+#[test]
+fn page_size_is_taken_from_first() {
+    let schema = new_schema();
+    let free = QueryLimits {
+        max_depth: 10,
+        max_cost: 0,
+    };
+    let cost = |query| match free.check(&schema, query) {
+        Err(LimitError::TooExpensive { cost, .. }) => cost,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(cost("{ scoreboard { edges { cursor } } }"), 22);
+    assert_eq!(cost("{ scoreboard(first: 5) { edges { cursor } } }"), 7);
+    assert_eq!(
+        cost("{ scoreboard(first: 1000) { edges { cursor } } }"),
+        102
+    );
+    assert_eq!(
+        cost("query($n: Int) { scoreboard(first: $n) { edges { cursor } } }"),
+        102
+    );
+}
+
+#[test]
+fn introspection_cycles_are_rejected() {
+    let schema = new_schema();
+    let limits = QueryLimits::default();
+    let deep = "{ __type(name: \"GqlPlayer\") { fields { type { fields { type { fields { type { \
+                fields { type { fields { type { fields { type { fields { name } } } } } } } } } } } } } } }";
+    assert_eq!(
+        limits.check(&schema, deep),
+        Err(LimitError::TooDeep { depth: 15, max: 13 })
+    );
+    let wide = "{ __type(name: \"GqlPlayer\") { fields { type { fields { type { fields { type { \
+                fields { type { fields { name } } } } } } } } } } }";
+    // Five nested lists of fields: 1 + (1 + 10 * (1 + ... (1 + 10 * 1)))
+    assert_eq!(
+        limits.check(&schema, wide),
+        Err(LimitError::TooExpensive {
+            cost: 122_222,
+            max: 50_000
+        })
+    );
+}
+
+#[test]
+fn batch_costs_add_up() {
+    let schema = new_schema();
+    let limits = QueryLimits::default();
+    // Costs 202 on its own
+    let query = "{ scoreboard(first: 100) { edges { node { displayName } } } }";
+    assert_eq!(limits.check_batch(&schema, &vec![query; 49]), Ok(()));
+    assert_eq!(
+        limits.check_batch(&schema, &vec![query; 50]),
+        Err(LimitError::TooExpensive {
+            cost: 10_100,
+            max: 10_000
+        })
+    );
+}
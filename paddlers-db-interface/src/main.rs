@@ -9,6 +9,7 @@ extern crate juniper;
 
 mod graphql;
 mod hooks;
+mod limits;
 mod sql;
 
 use paddlers_shared_lib::config::Config;
@@ -31,18 +32,10 @@ fn main() {
     #[cfg(not(feature = "local"))]
     let allowed_origins = AllowedOrigins::some_exact(&[origin]);
 
-    let mut databse_config_table = std::collections::BTreeMap::new();
-    let mut inner_table = std::collections::BTreeMap::<std::string::String, String>::new();
-    inner_table.insert("url".to_owned(), config.db_url.to_owned().into());
-    databse_config_table.insert("game_db".to_owned(), inner_table.into());
-
     let rocket_config = rocket::config::Config::build(rocket::config::Environment::Production)
         .address(config.graphql_service_name.clone())
         .port(config.graphql_port)
-        .extra(
-            "databases",
-            rocket::config::Value::Table(databse_config_table),
-        )
+        .extra("databases", database_config(&config))
         .finalize()
         .expect("Check Configuration");
 
@@ -66,6 +59,7 @@ fn main() {
     rocket::custom(rocket_config)
         .manage(graphql::new_schema())
         .manage(config)
+        .manage(limits::QueryLimits::from_env())
         .attach(DbConn::fairing())
         .attach(cors)
         .mount(
@@ -74,3 +68,12 @@ fn main() {
         )
         .launch();
 }
+
+/// Rocket configuration value that points `DbConn` to the game database
+fn database_config(config: &Config) -> rocket::config::Value {
+    let mut databse_config_table = std::collections::BTreeMap::new();
+    let mut inner_table = std::collections::BTreeMap::<std::string::String, String>::new();
+    inner_table.insert("url".to_owned(), config.db_url.to_owned().into());
+    databse_config_table.insert("game_db".to_owned(), inner_table.into());
+    rocket::config::Value::Table(databse_config_table)
+}
@@ -3,6 +3,29 @@ use diesel::prelude::*;
 
 impl paddlers_shared_lib::sql::GameDB for DbConn {
     fn dbconn(&self) -> &PgConnection {
+        #[cfg(test)]
+        statement_counter::increment();
         &**self
     }
 }
+
+/// Counts the SQL statements executed on the current thread.
+/// Each `GameDB` query fetches the connection exactly once, hence counting
+/// how often that happens is equivalent to counting statements.
+#[cfg(test)]
+pub(crate) mod statement_counter {
+    use std::cell::Cell;
+
+    thread_local! {
+        static STATEMENTS: Cell<usize> = Cell::new(0);
+    }
+    pub(super) fn increment() {
+        STATEMENTS.with(|n| n.set(n.get() + 1));
+    }
+    pub(crate) fn reset() {
+        STATEMENTS.with(|n| n.set(0));
+    }
+    pub(crate) fn count() -> usize {
+        STATEMENTS.with(Cell::get)
+    }
+}
@@ -155,6 +155,7 @@ impl Game<'_, '_> {
             self.map_mut().update();
         }
         self.update_time_reference();
+        self.update_shots();
         self.dispatcher.dispatch(&mut self.world);
         if self.total_updates % 300 == 15 {
             self.reaper(&Rectangle::new_sized(
@@ -2,11 +2,16 @@
 //! Heroes have this ability from the start, other working units
 //! may learn it as well.
 //! The effect of the ability is an increased satisfaction,
+//! welcomed visitors also slow down to enjoy the town.
 
 use crate::game::components::*;
 use crate::game::game_event_manager::EventPool;
+use crate::game::movement::Moving;
+use crate::game::units::attackers::Visitor;
 use crate::logging::error::*;
-use paddlers_shared_lib::models::AbilityType;
+use crate::prelude::*;
+use paddlers_shared_lib::game_mechanics::town::defence::speed_factor;
+use paddlers_shared_lib::models::{AbilityType, HoboAttributeType};
 use specs::prelude::*;
 
 pub fn use_welcome_ability<'a>(
@@ -16,6 +21,8 @@ pub fn use_welcome_ability<'a>(
     status_effects: &mut WriteStorage<StatusEffects>,
     mana: &mut WriteStorage<Mana>,
     ep: &EventPool,
+    lazy: &LazyUpdate,
+    now: Timestamp,
 ) -> PadlResult<()> {
     let h = health
         .get_mut(target)
@@ -32,12 +39,35 @@ pub fn use_welcome_ability<'a>(
         )))?;
 
     let a = AbilityType::Welcome;
-    let strength = a.apply().1;
     let mana_cost = a.mana_cost();
 
-    h.make_happy(strength as i64, target, ep);
-    se.add_health_reduction(strength);
+    for (attribute, strength) in a.apply() {
+        match attribute {
+            HoboAttributeType::Health => {
+                h.make_happy(*strength as i64, target, ep);
+                se.add_health_reduction(*strength);
+            }
+            HoboAttributeType::Speed => {
+                let percent = *strength;
+                lazy.exec_mut(move |world| change_visitor_speed(world, target, percent, now));
+            }
+        }
+    }
     m.mana -= mana_cost;
 
     Ok(())
 }
+
+/// Applies a speed effect to a visitor, from the given moment on
+fn change_visitor_speed(world: &mut World, target: Entity, percent: i32, now: Timestamp) {
+    let mut visitors = world.write_storage::<Visitor>();
+    if let Some(visitor) = visitors.get_mut(target) {
+        let factor =
+            speed_factor(visitor.speed_effects + percent) / speed_factor(visitor.speed_effects);
+        visitor.speed_effects += percent;
+        visitor.speed *= factor;
+        if let Some(mov) = world.write_storage::<Moving>().get_mut(target) {
+            mov.change_speed(now, factor);
+        }
+    }
+}
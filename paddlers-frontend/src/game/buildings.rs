@@ -68,13 +68,18 @@ impl Town {
         // No (None) attacks per cycle && Some ap => Aura effect
         if attacks_per_cycle.is_none() && ap.is_some() {
             if let Some(r) = range {
-                builder = builder.with(Aura::new(r, ap.unwrap(), tile_index, self));
+                builder = builder.with(Aura::new(r, ap.unwrap(), tile_index, created, None, self));
                 if r > self.distance_to_lane(tile_index) {
                     self.total_ambience += ap.unwrap();
                 }
             }
         }
 
+        // Some attacks per cycle && Some ap => Discrete shots
+        if let (Some(n), Some(ap), Some(r)) = (attacks_per_cycle, ap, range) {
+            builder = builder.with(Shots::new(r, ap, n, tile_index, self));
+        }
+
         match bt {
             BuildingType::Temple => {
                 builder = builder.with(UiMenu::new_shop_menu());
@@ -1,5 +1,5 @@
 pub use super::buildings::Building;
-pub use super::fight::{Health, Range};
+pub use super::fight::{Health, Range, Shots};
 pub use super::forestry::ForestComponent;
 pub use super::level::Level;
 pub use super::mana::Mana;
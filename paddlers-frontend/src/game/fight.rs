@@ -26,9 +26,20 @@ impl Range {
 pub struct Aura {
     pub affected_tiles: Vec<(usize, usize)>,
     pub effect: i64,
+    /// Visitors passing by earlier are not affected
+    pub since: crate::Timestamp,
+    /// Visitors passing by later are not affected, set for defending workers with a next task
+    pub until: Option<crate::Timestamp>,
 }
 impl Aura {
-    pub fn new(range: f32, ap: i64, tile: (usize, usize), town: &Town) -> Self {
+    pub fn new(
+        range: f32,
+        ap: i64,
+        tile: (usize, usize),
+        since: crate::Timestamp,
+        until: Option<crate::Timestamp>,
+        town: &Town,
+    ) -> Self {
         let mut tiles = town.lane_in_range(tile, range);
         if !tiles.is_sorted() {
             tiles.sort();
@@ -36,6 +47,30 @@ impl Aura {
         Aura {
             affected_tiles: tiles,
             effect: ap,
+            since,
+            until,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+#[storage(HashMapStorage)]
+/// Like an aura but only hits a limited number of visitors per cycle
+pub struct Shots {
+    pub affected_tiles: Vec<(usize, usize)>,
+    pub effect: i64,
+    pub per_cycle: i64,
+}
+impl Shots {
+    pub fn new(range: f32, ap: i64, per_cycle: i64, tile: (usize, usize), town: &Town) -> Self {
+        let mut tiles = town.lane_in_range(tile, range);
+        if !tiles.is_sorted() {
+            tiles.sort();
+        }
+        Shots {
+            affected_tiles: tiles,
+            effect: ap,
+            per_cycle,
         }
     }
 }
@@ -74,6 +109,8 @@ impl Health {
     }
 }
 
+/// Applies auras to visitors passing by.
+/// Shots of buildings are applied by `Game::update_shots` instead, they depend on the whole group of visitors.
 #[derive(Clone)]
 pub struct FightSystem {
     counter: usize,
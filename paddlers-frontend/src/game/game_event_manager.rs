@@ -106,6 +106,7 @@ impl GameState {
                 self.game.switch_view(UiView::Dialogue);
             }
             StoryAction::StoryProgress(new_state) => {
+                self.game.check_story_transition(new_state)?;
                 let t = StoryStateTransition {
                     before: self.game.story_state(),
                     after: new_state,
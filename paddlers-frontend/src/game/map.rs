@@ -50,6 +50,8 @@ pub struct GlobalMapSharedState {
     /// Offset in map coordinates (1.0 = one village width)
     x_offset: f32,
     scaling: f32,
+    /// Width of the world as reported by the server, in map coordinates
+    world_width: Option<i32>,
 }
 
 impl<'a> GlobalMap<'a> {
@@ -75,6 +77,7 @@ impl<'a> GlobalMap<'a> {
         let shared = GlobalMapSharedState {
             x_offset: 0.0,
             scaling,
+            world_width: None,
         };
         (map, shared)
     }
@@ -98,7 +101,8 @@ impl<'a> GlobalMap<'a> {
     const LOAD_STEP: i32 = 10;
     pub fn update(&mut self) {
         let x = -self.shared.x_offset as i32;
-        if self.private.loaded.0 > x - Self::LOAD_AHEAD {
+        // Nothing to load left of the world's start
+        if self.private.loaded.0 > 0 && self.private.loaded.0 > x - Self::LOAD_AHEAD {
             let (low, high) = (
                 self.private.loaded.0 - 1 - Self::LOAD_STEP,
                 self.private.loaded.0 - 1,
@@ -108,7 +112,10 @@ impl<'a> GlobalMap<'a> {
             self.private.loaded.0 = self.private.loaded.0.min(low);
             self.private.loaded.1 = self.private.loaded.1.max(high);
         }
-        if self.private.loaded.1 < x + self.private.view_width + Self::LOAD_AHEAD {
+        let world_end = self.shared.world_width.unwrap_or(std::i32::MAX);
+        if self.private.loaded.1 < world_end
+            && self.private.loaded.1 < x + self.private.view_width + Self::LOAD_AHEAD
+        {
             let (low, high) = (
                 self.private.loaded.1 + 1,
                 self.private.loaded.1 + 1 + Self::LOAD_STEP,
@@ -258,6 +265,16 @@ impl GlobalMapPrivateState {
 impl GlobalMapSharedState {
     pub fn drag(&mut self, v: Vector) {
         self.x_offset += v.x;
+        if let Some(w) = self.world_width {
+            // Keep the view inside the world
+            let (view_w, _) = GlobalMap::display_shape();
+            let min_offset = (view_w - w).min(0) as f32;
+            self.x_offset = self.x_offset.max(min_offset).min(0.0);
+        }
+    }
+    /// The world grows over time, the latest width is sent along with each map slice
+    pub fn set_world_width(&mut self, w: i32) {
+        self.world_width = Some(w);
     }
     pub fn left_click_on_main_area<'a>(
         &mut self,
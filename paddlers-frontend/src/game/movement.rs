@@ -86,6 +86,13 @@ impl Moving {
     pub fn position(&self, t: Timestamp) -> Vector {
         self.start_pos + self.momentum * (t - self.start_ts).micros() as f32 / 1_000_000
     }
+    /// Continue moving in the same direction with the speed scaled by the given factor
+    pub fn change_speed(&mut self, timestamp: Timestamp, factor: f32) {
+        self.start_pos = self.position(timestamp);
+        self.start_ts = timestamp;
+        self.momentum = self.momentum * factor;
+        self.max_speed *= factor;
+    }
     pub fn stand_still(&mut self, timestamp: Timestamp) {
         self.start_pos = self.position(timestamp);
         self.start_ts = timestamp;
@@ -1,6 +1,6 @@
 use crate::game::{
-    components::*, town::new_temple_menu, units::worker_factory::create_worker_entities,
-    units::workers::Worker,
+    components::*, fight::Aura, town::new_temple_menu,
+    units::worker_factory::create_worker_entities, units::workers::Worker,
 };
 use crate::init::loading::LoadingState;
 use crate::init::quicksilver_integration::{GameState, Signal};
@@ -101,6 +101,7 @@ impl GameState {
                     }
                     NetMsg::Map(response, min, max) => {
                         if let Some(data) = response.data {
+                            let world_width = data.map.world_width as i32;
                             let streams = data
                                 .map
                                 .streams
@@ -120,6 +121,9 @@ impl GameState {
                                 .collect();
                             let (map, world) = (self.game.map.as_mut(), &mut self.game.world);
                             map.map(|map| map.add_segment(world, streams, villages, min, max));
+                            world
+                                .write_resource::<crate::game::map::GlobalMapSharedState>()
+                                .set_world_width(world_width);
                         } else {
                             println!("No map data available");
                         }
@@ -174,6 +178,16 @@ impl GameState {
                                 }
                             }
                         }
+                        // A defending worker leaves its position when the next task starts
+                        let mut auras = self.game.world.write_storage::<Aura>();
+                        if let Some(aura) = auras.get_mut(entity) {
+                            let since = aura.since;
+                            aura.until = worker
+                                .tasks
+                                .iter()
+                                .map(|task| task.start_time)
+                                .find(|start| *start > since);
+                        }
                     }
                     NetMsg::Reports(data) => {
                         self.viewer.global_event(
@@ -223,9 +237,14 @@ impl<'a, 'b> Game<'a, 'b> {
     }
     pub fn load_attacking_hobos(&mut self, response: AttacksResponse) -> PadlResult<()> {
         if let Some(data) = response.data {
-            for atk in data.village.attacks {
-                atk.create_entities(self)?;
-            }
+            let attacks = data
+                .village
+                .attacks
+                .edges
+                .into_iter()
+                .map(|edge| edge.node)
+                .collect();
+            self.create_attack_entities(attacks)?;
         }
         Ok(())
     }
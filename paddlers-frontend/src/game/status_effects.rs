@@ -41,8 +41,8 @@ impl StatusEffects {
                     ))?;
                     status.add_health_reduction(strength as i32);
                 }
-                _ => {
-                    return PadlErrorCode::InvalidGraphQLData("Unknown Effect").dev();
+                HoboAttributeType::Speed => {
+                    // Speed effects are visible through the movement, no status displayed
                 }
             }
         }
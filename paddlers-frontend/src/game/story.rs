@@ -1,11 +1,12 @@
 pub mod entity_trigger;
 pub mod scene;
 
-use crate::game::{player_info::PlayerInfo, story::scene::SceneIndex, Game};
+use crate::game::{player_info::PlayerInfo, story::scene::SceneIndex, town::Town, Game};
 use crate::gui::input::UiView;
 use crate::prelude::*;
 use crate::view::entry_view;
 use paddlers_shared_lib::story::story_state::StoryState;
+use paddlers_shared_lib::story::story_transitions::IStoryProgress;
 use scene::*;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -21,6 +22,15 @@ impl Game<'_, '_> {
     pub fn story_state(&self) -> StoryState {
         self.world.fetch::<PlayerInfo>().story_state()
     }
+    /// Verifies with the shared story graph that the player may currently progress to the given state
+    pub fn check_story_transition(&self, to: StoryState) -> PadlResult<()> {
+        let view = StoryView {
+            player: &self.player(),
+            town: &self.town(),
+        };
+        view.check_story_transition(to)?;
+        Ok(())
+    }
     pub fn load_story_state(&mut self) -> PadlResult<()> {
         let story_state = self.story_state();
         if let Some((scene, slide)) = select_dialogue_scene(story_state) {
@@ -37,6 +47,30 @@ impl Game<'_, '_> {
     }
 }
 
+struct StoryView<'a> {
+    player: &'a PlayerInfo,
+    town: &'a Town,
+}
+
+impl IStoryProgress for StoryView<'_> {
+    fn story_state(&self) -> StoryState {
+        self.player.story_state()
+    }
+    fn karma(&self) -> i64 {
+        self.player.karma()
+    }
+    fn has_building(&self, building_type: BuildingType) -> bool {
+        self.town.has_building(building_type)
+    }
+    fn has_worker_on_task(&self, task_type: TaskType) -> bool {
+        match task_type {
+            TaskType::GatherSticks => self.town.building_in_use(BuildingType::BundlingStation),
+            TaskType::ChopTree => self.town.building_in_use(BuildingType::SawMill),
+            _ => false,
+        }
+    }
+}
+
 pub fn select_dialogue_scene(story_state: StoryState) -> Option<(SceneIndex, SlideIndex)> {
     match story_state {
         StoryState::Initialized
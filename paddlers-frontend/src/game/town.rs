@@ -4,9 +4,12 @@ pub mod task_factory;
 mod temple_shop;
 pub mod tiling;
 pub mod town_defence;
+#[cfg(test)]
+mod town_defence_test;
 mod town_frame;
 pub mod town_input;
 pub mod town_render;
+
 pub use default_shop::*;
 pub(crate) use temple_shop::*;
 pub(crate) use town_frame::*;
@@ -152,6 +155,27 @@ impl Town {
         }
     }
 
+    pub fn has_building(&self, bt: BuildingType) -> bool {
+        self.building_tiles(bt).next().is_some()
+    }
+    /// Whether any building of the given type currently has a worker inside
+    pub fn building_in_use(&self, bt: BuildingType) -> bool {
+        self.building_tiles(bt).any(|i| {
+            self.state
+                .get(&i)
+                .map(|s| s.building_state.entity_count > 0)
+                .unwrap_or(false)
+        })
+    }
+    fn building_tiles<'a>(&'a self, bt: BuildingType) -> impl Iterator<Item = TileIndex> + 'a {
+        self.map.0.iter().enumerate().flat_map(move |(x, col)| {
+            col.iter()
+                .enumerate()
+                .filter(move |(_, tile)| **tile == TileType::BUILDING(bt))
+                .map(move |(y, _)| (x, y))
+        })
+    }
+
     pub fn add_entity_to_building(&mut self, i: &TileIndex) -> PadlResult<()> {
         match self.state.get_mut(i) {
             None => PadlErrorCode::NoStateForTile(*i).dev(),
@@ -50,7 +50,11 @@ impl Town {
                     return PadlErrorCode::NotEnoughMana.usr();
                 }
             }
-            TaskType::Defend => panic!("NIY"),
+            TaskType::Defend => {
+                if !self.map.can_defend_at(destination) {
+                    return PadlErrorCode::CannotDefendHere.usr();
+                }
+            }
             TaskType::Idle | TaskType::CollectReward | TaskType::Walk => {}
         }
 
@@ -71,6 +75,9 @@ impl Town {
                 BuildingType::PresentA | BuildingType::PresentB => vec![TaskType::CollectReward],
                 _ => vec![],
             },
+            TileType::EMPTY if self.map.can_defend_at(i) => {
+                vec![TaskType::Defend, TaskType::Idle]
+            }
             TileType::EMPTY => vec![TaskType::Idle],
             TileType::LANE => {
                 // TODO: Check for welcoming ability
@@ -103,15 +110,16 @@ fn raw_job_execution_tasks(job: &NewTaskDescriptor, place: TileIndex) -> Vec<Raw
 
     // tasks required afterwards
     match job.0 {
-        TaskType::ChopTree | TaskType::GatherSticks | TaskType::Idle | TaskType::Walk => {
+        TaskType::ChopTree
+        | TaskType::GatherSticks
+        | TaskType::Defend
+        | TaskType::Idle
+        | TaskType::Walk => {
             // NOP
         }
         TaskType::CollectReward | TaskType::WelcomeAbility => {
             tasks.push(RawTask::new(TaskType::Idle, place));
         }
-        TaskType::Defend => {
-            panic!("Not implemented");
-        }
     }
 
     tasks
@@ -1,18 +1,119 @@
 use super::*;
 use crate::game::buildings::Building;
-use crate::game::fight::Aura;
-use crate::game::visits::attacks::Attack;
-use crate::net::graphql::attacks_query::{AttacksQueryVillageAttacksUnits, HoboAttributeType};
+use crate::game::fight::{Aura, Health, Shots};
+use crate::gui::ui_state::Now;
+use crate::net::graphql::attacks_query::{
+    AttacksQueryVillageAttacksEdgesNodeUnits, HoboAttributeType,
+};
+use paddlers_shared_lib::game_mechanics::town::defence::{
+    aura_active, Shooter, SHOT_CYCLE_SECONDS,
+};
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::graphql_types::*;
 use specs::prelude::*;
 
-pub(crate) struct AttackingHobo<'a> {
-    pub unit: AttacksQueryVillageAttacksUnits,
-    pub attack: &'a Attack,
+pub(crate) struct AttackingHobo {
+    pub unit: AttacksQueryVillageAttacksEdgesNodeUnits,
+    pub arrival: Timestamp,
 }
 
-impl<'a> IAttackingHobo for AttackingHobo<'a> {
+/// Visitors in town with their entities.
+/// Buildings share their shots among all of them, hence shots are always simulated for the entire group.
+#[derive(Default)]
+pub(crate) struct TownVisitors {
+    visitors: Vec<AttackingHobo>,
+    entities: Vec<Entity>,
+    /// Shot damage of each visitor that has already been applied to its `Health`
+    shot_damage: Vec<i32>,
+    /// Last shot cycle that has been simulated
+    cycle: i64,
+}
+
+impl TownVisitors {
+    pub fn visitors(&self) -> &[AttackingHobo] {
+        &self.visitors
+    }
+    pub fn push(&mut self, visitor: AttackingHobo, entity: Entity, shot_damage: i32) {
+        self.visitors.push(visitor);
+        self.entities.push(entity);
+        self.shot_damage.push(shot_damage);
+    }
+    /// Takes over the release of a reloaded visitor.
+    /// Returns the entity of the visitor if it has been released since it was loaded.
+    pub fn update_released(
+        &mut self,
+        unit: AttacksQueryVillageAttacksEdgesNodeUnits,
+    ) -> Option<Entity> {
+        let i = self
+            .visitors
+            .iter()
+            .position(|v| v.unit.hobo.id == unit.hobo.id)?;
+        let info = &mut self.visitors[i].unit.info;
+        if info.released.is_none() && unit.info.released.is_some() {
+            info.released = unit.info.released;
+            Some(self.entities[i])
+        } else {
+            None
+        }
+    }
+    fn retain(&mut self, mut keep: impl FnMut(&AttackingHobo, Entity) -> bool) {
+        let mut i = 0;
+        while i < self.visitors.len() {
+            if keep(&self.visitors[i], self.entities[i]) {
+                i += 1;
+            } else {
+                self.visitors.remove(i);
+                self.entities.remove(i);
+                self.shot_damage.remove(i);
+            }
+        }
+    }
+}
+
+impl Game<'_, '_> {
+    /// Takes the visitors out of the world, visitors that have left town are dropped.
+    pub(crate) fn take_town_visitors(&self, now: Timestamp) -> TownVisitors {
+        let mut town_visitors = std::mem::take(&mut *self.world.write_resource::<TownVisitors>());
+        let entities = self.world.entities();
+        town_visitors.retain(|v, e| entities.is_alive(e) && !self.hobo_left_town(v, now));
+        town_visitors
+    }
+    /// Applies the shots fired since the last update to all visitors in town.
+    /// Called every frame, the simulation only runs once a new shot cycle has started.
+    pub(crate) fn update_shots(&mut self) {
+        let now = self.world.fetch::<Now>().0;
+        let cycle = now
+            .micros()
+            .div_euclid(Timestamp::from_seconds(SHOT_CYCLE_SECONDS).micros());
+        if self.world.fetch::<TownVisitors>().cycle == cycle {
+            return;
+        }
+        let mut town_visitors = self.take_town_visitors(now);
+        town_visitors.cycle = cycle;
+        let damage = self.shot_damage(town_visitors.visitors(), now);
+        self.apply_shot_damage(&mut town_visitors, damage);
+        *self.world.write_resource::<TownVisitors>() = town_visitors;
+    }
+    /// Updates the `Health` of each visitor to the shot damage computed for it.
+    /// Damage may also decrease when newly loaded visitors took shots in the past.
+    pub(crate) fn apply_shot_damage(&self, town_visitors: &mut TownVisitors, damage: Vec<i32>) {
+        let mut health = self.world.write_storage::<Health>();
+        let applied = town_visitors.shot_damage.iter_mut();
+        for ((entity, applied), dmg) in town_visitors.entities.iter().zip(applied).zip(damage) {
+            if dmg != *applied {
+                if let Some(h) = health.get_mut(*entity) {
+                    h.make_happy((dmg - *applied) as i64, *entity, &self.event_pool);
+                }
+                *applied = dmg;
+            }
+        }
+    }
+}
+
+impl IAttackingHobo for AttackingHobo {
+    fn id(&self) -> i64 {
+        self.unit.hobo.id.parse().expect("Parsing id")
+    }
     fn max_hp(&self) -> u32 {
         self.unit.hobo.hp as u32
     }
@@ -23,7 +124,7 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
         self.unit.hobo.hurried
     }
     fn arrival(&self) -> Timestamp {
-        self.attack.arrival
+        self.arrival
     }
     fn released(&self) -> Option<Timestamp> {
         self.unit
@@ -41,6 +142,18 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
             .filter(|e| e.strength.is_some())
             .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
     }
+    fn speed_effects(&self) -> Vec<(Timestamp, i32)> {
+        self.unit
+            .hobo
+            .effects
+            .iter()
+            .filter(|e| e.attribute == HoboAttributeType::SPEED)
+            .filter_map(|e| {
+                let start = GqlTimestamp::from_string(&e.start_time).unwrap();
+                e.strength.map(|s| (start.to_chrono().into(), s as i32))
+            })
+            .collect()
+    }
 }
 
 impl<'a, 'b> ITownLayoutMarker for Game<'a, 'b> {
@@ -52,10 +165,10 @@ impl<'a, 'b> IDefendingTown for Game<'a, 'b> {
         let mut out = vec![];
 
         let auras = self.world.read_component::<Aura>();
-        let buildings = self.world.read_component::<Building>();
         let entities = self.world.entities();
-        for (aura, e, b) in (&auras, &entities, &buildings).join() {
-            if time < b.built {
+        // Auras of buildings start when they are built, those of defending workers last while they are defending
+        for (aura, e) in (&auras, &entities).join() {
+            if !aura_active(aura.since, aura.until, time) {
                 continue;
             }
             for tile in &aura.affected_tiles {
@@ -66,4 +179,28 @@ impl<'a, 'b> IDefendingTown for Game<'a, 'b> {
         }
         out
     }
+    fn shooters_in_range(
+        &self,
+        index: &Self::Index,
+        time: Timestamp,
+    ) -> Vec<Shooter<Self::AuraId>> {
+        let mut out = vec![];
+
+        let shots = self.world.read_component::<Shots>();
+        let buildings = self.world.read_component::<Building>();
+        let entities = self.world.entities();
+        for (shots, e, b) in (&shots, &entities, &buildings).join() {
+            if time < b.built {
+                continue;
+            }
+            if shots.affected_tiles.binary_search(index).is_ok() {
+                out.push(Shooter {
+                    id: e.id(),
+                    power: shots.effect as i32,
+                    shots_per_cycle: shots.per_cycle as usize,
+                })
+            }
+        }
+        out
+    }
 }
@@ -0,0 +1,55 @@
+use super::town_defence::{AttackingHobo, TownVisitors};
+use crate::net::graphql::attacks_query::AttacksQueryVillageAttacksEdgesNodeUnits;
+use crate::prelude::*;
+use specs::prelude::*;
+
+const RELEASED: &str = "60000000";
+
+fn unit(id: i64, released: Option<&str>) -> AttacksQueryVillageAttacksEdgesNodeUnits {
+    serde_json::from_value(serde_json::json!({
+        "hobo": {
+            "id": id.to_string(),
+            "color": "YELLOW",
+            "hp": 10,
+            "speed": 0.5,
+            "hurried": false,
+            "effects": [],
+        },
+        "info": { "released": released },
+    }))
+    .expect("Parsing unit")
+}
+
+fn visitor(id: i64) -> AttackingHobo {
+    AttackingHobo {
+        unit: unit(id, None),
+        arrival: Timestamp::from_seconds(0),
+    }
+}
+
+#[test]
+fn reloaded_visitor_is_released() {
+    let mut world = World::new();
+    let resting = world.create_entity().build();
+    let other = world.create_entity().build();
+    let mut town_visitors = TownVisitors::default();
+    town_visitors.push(visitor(1), resting, 0);
+    town_visitors.push(visitor(2), other, 0);
+
+    // Unchanged visitors and visitors that are not in town are left alone
+    assert_eq!(town_visitors.update_released(unit(2, None)), None);
+    assert_eq!(town_visitors.update_released(unit(3, Some(RELEASED))), None);
+
+    assert_eq!(
+        town_visitors.update_released(unit(1, Some(RELEASED))),
+        Some(resting)
+    );
+    assert_eq!(
+        town_visitors.visitors()[0].unit.info.released,
+        Some(RELEASED.to_owned())
+    );
+    assert_eq!(town_visitors.visitors()[1].unit.info.released, None);
+
+    // Loading the same release again does not set the visitor moving twice
+    assert_eq!(town_visitors.update_released(unit(1, Some(RELEASED))), None);
+}
@@ -2,7 +2,7 @@ mod visitor_maintenance;
 
 pub use visitor_maintenance::*;
 
-use crate::game::town::town_defence::AttackingHobo;
+use crate::game::town::town_defence::{AttackingHobo, TownVisitors};
 use crate::game::{
     components::NetObj,
     fight::Health,
@@ -13,12 +13,13 @@ use crate::game::{
 };
 use crate::gui::ui_state::Now;
 use crate::gui::{render::Renderable, sprites::*, utils::*, z::Z_VISITOR};
-use crate::net::graphql::query_types::HoboEffect;
+use crate::net::graphql::query_types::{HoboAttribute, HoboEffect};
 use crate::prelude::*;
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::graphql_types::*;
 use quicksilver::geom::Vector;
 use specs::prelude::*;
+use std::collections::HashSet;
 
 const ATTACKER_SIZE_FACTOR_X: f32 = 0.6;
 const ATTACKER_SIZE_FACTOR_Y: f32 = 0.4;
@@ -28,7 +29,10 @@ const ATTACKER_SIZE_FACTOR_Y: f32 = 0.4;
 /// A visitor is an attacking hobo
 pub struct Visitor {
     pub hurried: bool,
+    /// Current speed in pixels per second, including speed effects
     pub speed: f32,
+    /// Sum of all speed effects applied so far, in percent
+    pub speed_effects: i32,
     pub arrival: Timestamp,
     pub rank_offset: usize,
 }
@@ -85,6 +89,11 @@ pub fn build_new_duck_entity<'a>(
 ) -> PadlResult<specs::EntityBuilder<'a>> {
     let size: Vector = Vector::new(ATTACKER_SIZE_FACTOR_X * ul, ATTACKER_SIZE_FACTOR_Y * ul).into();
     let status_effects = StatusEffects::from_gql_query(effects)?;
+    let speed_effects = effects
+        .iter()
+        .filter(|e| e.attribute == HoboAttribute::SPEED)
+        .filter_map(|e| e.strength)
+        .sum::<i64>() as i32;
     let mut renderable = Renderable::new(hobo_sprite_sad(color));
     if hp.hp == 0 {
         change_duck_sprite_to_happy(&mut renderable);
@@ -99,6 +108,7 @@ pub fn build_new_duck_entity<'a>(
         .with(Visitor {
             hurried,
             speed,
+            speed_effects,
             arrival,
             rank_offset,
         })
@@ -107,16 +117,10 @@ pub fn build_new_duck_entity<'a>(
     Ok(builder)
 }
 
-use crate::net::graphql::attacks_query::AttacksQueryVillageAttacks;
-impl AttacksQueryVillageAttacks {
-    pub(crate) fn create_entities<'a, 'b>(
-        self,
-        game: &mut Game<'a, 'b>,
-    ) -> PadlResult<Vec<Entity>> {
-        let ul = game.world.fetch::<ScreenResolution>().unit_length();
+use crate::net::graphql::attacks_query::AttacksQueryVillageAttacksEdgesNode;
+impl AttacksQueryVillageAttacksEdgesNode {
+    fn into_visitors(self) -> (Attack, Vec<AttackingHobo>) {
         let birth_time = GqlTimestamp::from_string(&self.arrival).unwrap().into();
-        let now = game.world.fetch::<Now>().0;
-
         let description = self
             .attacker
             .as_ref()
@@ -124,43 +128,113 @@ impl AttacksQueryVillageAttacks {
             .map(|player| format!("From {}", player))
             .unwrap_or("Anarchists".to_owned());
         let size = self.units.len() as u32;
-        let atk = Attack::new(birth_time, description, size);
-
-        let mut out = vec![];
-        for (i, unit) in self.units.into_iter().enumerate() {
-            let unit_rep = AttackingHobo { unit, attack: &atk };
-            let effects = game.touched_auras(&unit_rep, now);
-            let builder = unit_rep.create_entity(
-                game.world.create_entity(),
-                now,
-                birth_time,
-                i,
-                ul,
-                effects,
-            )?;
-            out.push(builder.build());
+        let id = self.id.parse().expect("Parsing id");
+        let atk = Attack::new(id, birth_time, description, size);
+
+        let units = self
+            .units
+            .into_iter()
+            .map(|unit| AttackingHobo {
+                unit,
+                arrival: birth_time,
+            })
+            .collect();
+        (atk, units)
+    }
+}
+impl Game<'_, '_> {
+    /// Creates the entities for newly loaded attacks.
+    /// Attacks that have been loaded before only update the release of their resting visitors.
+    ///
+    /// Shots of buildings are shared among all visitors in town, hence the
+    /// visitors of previously loaded attacks take part in the simulation, too.
+    pub(crate) fn create_attack_entities(
+        &mut self,
+        attacks: Vec<AttacksQueryVillageAttacksEdgesNode>,
+    ) -> PadlResult<()> {
+        let ul = self.world.fetch::<ScreenResolution>().unit_length();
+        let now = self.world.fetch::<Now>().0;
+
+        let mut town_visitors = self.take_town_visitors(now);
+        let known = town_visitors.visitors().len();
+        let loaded: HashSet<i64> = (&self.world.read_storage::<Attack>())
+            .join()
+            .map(|atk| atk.id)
+            .collect();
+        let mut new_attacks = vec![];
+        let mut new_visitors = vec![];
+        let mut released = vec![];
+        for attack in attacks {
+            let (atk, units) = attack.into_visitors();
+            if loaded.contains(&atk.id) {
+                released.extend(
+                    units
+                        .into_iter()
+                        .filter_map(|v| town_visitors.update_released(v.unit)),
+                );
+                continue;
+            }
+            new_attacks.push((atk, units.len()));
+            new_visitors.extend(units);
         }
+        // Simulated without entities first, these are only created with the result
+        let mut all_visitors: Vec<&AttackingHobo> = town_visitors.visitors().iter().collect();
+        all_visitors.extend(&new_visitors);
+        let shot_damage = self.shot_damage(&all_visitors, now);
+        std::mem::drop(all_visitors);
 
-        game.world.create_entity().with(atk).build();
+        let mut new_units = new_visitors.into_iter().zip(shot_damage[known..].to_vec());
+        for (atk, size) in new_attacks {
+            for (i, (unit_rep, shots)) in new_units.by_ref().take(size).enumerate() {
+                let effects = self.touched_auras(&unit_rep, now);
+                let builder = unit_rep.create_entity(
+                    self.world.create_entity(),
+                    now,
+                    i,
+                    ul,
+                    effects,
+                    shots,
+                )?;
+                town_visitors.push(unit_rep, builder.build(), shots);
+            }
+            self.world.create_entity().with(atk).build();
+        }
+        // Shots taken by the new visitors in the past may change those of the known visitors
+        self.apply_shot_damage(&mut town_visitors, shot_damage);
 
-        Ok(out)
+        *self.world.write_resource::<TownVisitors>() = town_visitors;
+        self.move_released_visitors(&released)
+    }
+    /// Sets visitors moving again that have been released by the game-master while resting
+    fn move_released_visitors(&self, released: &[Entity]) -> PadlResult<()> {
+        let visitors = self.world.read_storage::<Visitor>();
+        let mut moving = self.world.write_storage::<Moving>();
+        for e in released {
+            if moving.get(*e).is_none() {
+                if let Some(visitor) = visitors.get(*e) {
+                    moving.insert(*e, self.release_and_move_visitor(visitor))?;
+                }
+            }
+        }
+        Ok(())
     }
 }
-impl<'a> AttackingHobo<'a> {
-    fn create_entity(
+impl AttackingHobo {
+    fn create_entity<'a>(
         &self,
         mut builder: specs::EntityBuilder<'a>,
         now: Timestamp,
-        birth: Timestamp,
         pos_rank: usize,
         ul: f32,
         auras: Vec<(<Game<'_, '_> as IDefendingTown>::AuraId, i32)>,
+        shot_damage: i32,
     ) -> PadlResult<specs::EntityBuilder<'a>> {
-        let v = self.unit.hobo.speed as f32 * ul;
+        let v = self.speed_at(now) * ul;
         let w = TOWN_X as f32 * ul;
         let x = w - ul * ATTACKER_SIZE_FACTOR_X;
         let y = TOWN_LANE_Y as f32 * ul;
         let mut pos = Vector::new(x, y) + attacker_position_rank_offset(pos_rank, ul);
+        let birth = self.arrival;
         let mut t0 = birth;
         let hp = self.unit.hobo.hp;
         let netid = self.unit.hobo.id.parse().expect("Parsing id");
@@ -174,7 +248,9 @@ impl<'a> AttackingHobo<'a> {
         let time_until_resting = self.time_until_resting();
 
         // Simulate all interactions with buildings for the visitor which happened in the past
-        let dmg = <Game<'_, '_> as IDefendingTown>::damage(&auras) + self.effects_strength();
+        let dmg = <Game<'_, '_> as IDefendingTown>::damage(&auras)
+            + self.effects_strength()
+            + shot_damage;
         let hp_left = (hp - dmg as i64).max(0);
         let aura_ids = auras.into_iter().map(|a| a.0).collect();
         let health = Health::new(hp, hp_left, aura_ids);
@@ -192,6 +268,11 @@ impl<'a> AttackingHobo<'a> {
         let can_rest = !self.unit.hobo.hurried && self.unit.info.released.is_none();
         let resting = can_rest && birth + time_until_resting <= now;
         if !resting {
+            // Start moving from the current position, speed may have changed on the way
+            if t0 < now {
+                pos.x -= self.distance_walked(t0, now) * ul;
+                t0 = now;
+            }
             builder = builder.with(Moving::new(t0, pos, Vector::new(-v, 0.0), v));
             if can_rest {
                 let final_pos = Vector::new(TOWN_RESTING_X as f32 * ul, pos.y);
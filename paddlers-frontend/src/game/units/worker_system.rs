@@ -2,6 +2,7 @@ use crate::game::game_event_manager::EventPool;
 use crate::game::{
     abilities::use_welcome_ability,
     components::*,
+    fight::Aura,
     movement::Moving,
     town::{TileIndex, Town},
     units::workers::*,
@@ -13,6 +14,7 @@ use crate::gui::render::Renderable;
 use crate::gui::utils::*;
 use crate::logging::ErrorQueue;
 use crate::prelude::*;
+use paddlers_shared_lib::game_mechanics::worker::{defender_aura_strength, DEFENDER_RANGE};
 use quicksilver::geom::about_equal;
 use specs::prelude::*;
 
@@ -68,6 +70,9 @@ impl<'a> System<'a> for WorkerSystem {
             (&entities, &mut workers, &mut velocities, &mut animations).join()
         {
             if let Some(task) = worker.poll(now.0) {
+                if task.task_type != TaskType::Defend {
+                    lazy.remove::<Aura>(e);
+                }
                 match task.task_type {
                     TaskType::Walk => {
                         let position_now = mov.position(task.start_time);
@@ -110,6 +115,8 @@ impl<'a> System<'a> for WorkerSystem {
                             &mut status_effects,
                             &mut mana,
                             &self.event_pool,
+                            &lazy,
+                            task.start_time,
                         );
                         if let Err(e) = err {
                             errq.push(e);
@@ -126,6 +133,20 @@ impl<'a> System<'a> for WorkerSystem {
                             errq.push(e)
                         }
                     }
+                    TaskType::Defend => {
+                        mov.stand_still(task.start_time);
+                        anim.direction = Direction::Undirected;
+                        let lvl = levels.get(e).map(|l| l.lvl).unwrap_or(1);
+                        let aura = Aura::new(
+                            DEFENDER_RANGE,
+                            defender_aura_strength(lvl) as i64,
+                            task.position,
+                            task.start_time,
+                            worker.tasks.front().map(|next| next.start_time),
+                            &town,
+                        );
+                        lazy.insert(e, aura);
+                    }
                     _ => debug_assert!(false, "Unexpected task"),
                 }
             }
@@ -20,6 +20,7 @@ use stdweb::web::{HtmlElement, IElement, INode, Node};
 #[derive(Component, Debug)]
 #[storage(HashMapStorage)]
 pub struct Attack {
+    pub id: i64,
     pub arrival: Timestamp,
     size: u32,
     description: String,
@@ -45,8 +46,9 @@ impl Game<'_, '_> {
 }
 
 impl Attack {
-    pub fn new(arrival: Timestamp, description: String, size: u32) -> Self {
+    pub fn new(id: i64, arrival: Timestamp, description: String, size: u32) -> Self {
         Attack {
+            id,
             arrival,
             dom_node: None,
             description,
@@ -142,7 +142,8 @@ impl<'a, 'b> Frame for ReportFrame<'a, 'b> {
     fn event(&mut self, state: &mut Self::State, event: &Self::Event) -> Result<(), Self::Error> {
         match event {
             PadlEvent::Network(NetMsg::Reports(data)) => {
-                for r in &data.village.reports {
+                for edge in &data.village.reports.edges {
+                    let r = &edge.node;
                     self.add_report(
                         Report {
                             id: VisitReportKey(r.id.parse().unwrap()),
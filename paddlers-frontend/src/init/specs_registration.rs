@@ -4,6 +4,7 @@ use crate::prelude::*;
 use crate::view::entry_view;
 use specs::prelude::*;
 
+use crate::game::town::town_defence::TownVisitors;
 use crate::game::town::DefaultShop;
 use crate::game::{
     components::*, player_info::PlayerInfo, town::Town, town_resources::TownResources,
@@ -28,6 +29,7 @@ pub(super) fn insert_resources(
     world.insert(resolution);
     world.insert(tb);
     world.insert(Town::new(resolution));
+    world.insert(TownVisitors::default());
     world.insert(TownResources::default());
     world.insert(player_info);
     let view = entry_view(player_info.story_state());
@@ -54,6 +56,7 @@ pub fn register_components(world: &mut World) {
     world.register::<Position>();
     world.register::<Range>();
     world.register::<Renderable>();
+    world.register::<Shots>();
     world.register::<StatusEffects>();
     world.register::<TargetPosition>();
     world.register::<UiMenu>();
@@ -2,6 +2,7 @@ use crate::game::town::{TileIndex, TileType};
 use crate::net::ajax::AjaxError;
 use crate::prelude::*;
 use crate::stdweb::unstable::TryInto;
+use paddlers_shared_lib::story::story_transitions::StoryTransitionError;
 use std::fmt;
 use std::sync::mpsc::SendError;
 
@@ -64,6 +65,10 @@ pub enum PadlErrorCode {
     NotEnoughUnits,
     NotReadyYet,
     PathBlocked,
+    CannotDefendHere,
+    CannotBuildHere,
+    BuildingLocked,
+    UnitsBusy,
     NoNetwork,
     // Dev only
     DevMsg(&'static str),
@@ -89,6 +94,7 @@ pub enum PadlErrorCode {
     NoDataFromBrowser(&'static str),
     BrowserError(String),
     DialogueEmpty,
+    InvalidStoryTransition(StoryTransitionError),
     UserNotInDB,
     AuthorizationRequired,
 }
@@ -110,6 +116,12 @@ impl fmt::Display for PadlErrorCode {
             PadlErrorCode::NotEnoughKarma => write!(f, "Not enough karma."),
             PadlErrorCode::NotEnoughUnits => write!(f, "Require more units."),
             PadlErrorCode::PathBlocked => write!(f, "The path is blocked."),
+            PadlErrorCode::CannotDefendHere => {
+                write!(f, "Defenders must stand right next to the river.")
+            }
+            PadlErrorCode::CannotBuildHere => write!(f, "Cannot build here."),
+            PadlErrorCode::BuildingLocked => write!(f, "This building has not been unlocked."),
+            PadlErrorCode::UnitsBusy => write!(f, "Some units are busy elsewhere."),
             PadlErrorCode::NoNetwork => write!(f, "Connection to server dropped."),
             // Dev
             PadlErrorCode::DevMsg(msg) => write!(f, "Dev Error Msg: {}", msg),
@@ -161,6 +173,9 @@ impl fmt::Display for PadlErrorCode {
             }
             PadlErrorCode::BrowserError(s) => write!(f, "Unexpected browser error: {}", s),
             PadlErrorCode::DialogueEmpty => write!(f, "No scene loaded in dialogue"),
+            PadlErrorCode::InvalidStoryTransition(e) => {
+                write!(f, "Story transition rejected: {}", e)
+            }
             PadlErrorCode::UserNotInDB => {
                 write!(f, "The user logged in is not present in the game database.")
             }
@@ -252,3 +267,9 @@ impl From<paddlers_shared_lib::game_mechanics::town::TownError> for PadlError {
         }
     }
 }
+
+impl From<StoryTransitionError> for PadlError {
+    fn from(error: StoryTransitionError) -> Self {
+        PadlError::dev_err(PadlErrorCode::InvalidStoryTransition(error))
+    }
+}
@@ -30,6 +30,7 @@ pub fn main() {
     setup_wasm();
     let version = env!("CARGO_PKG_VERSION");
     println!("Paddlers {}", version);
+    paddlers_shared_lib::balancing::validate_balancing().expect("Invalid balancing specification");
 
     // Initialize panes, enabling HTML access
     let resolution = crate::window::estimate_screen_size();
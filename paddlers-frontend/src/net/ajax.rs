@@ -25,7 +25,7 @@ pub fn send(
                     reject({ "text": xhr.statusText, "code": xhr.status, "errors": response.errors, "data": response.data});
                 }
                 else if (xhr.status != 200) {
-                    reject({ "text": xhr.statusText, "code": xhr.status, "data": response.data});
+                    reject({ "text": xhr.statusText, "code": xhr.status, "data": response.data, "padlcode": response.padlcode, "message": response.message});
                 }
                 else {
                     resolve(xhr.responseText);
@@ -95,12 +95,37 @@ fn padl_error_from_js_array(val: stdweb::Value) -> (Option<PadlErrorCode>, Optio
         .and_then(|inner_obj| inner_obj.to_iter().find(|(key, _val)| key == "padlcode"))
         .and_then(|(_key, n)| n.try_into().ok())
         .and_then(PadlApiError::try_from_num)
-        .map(|api_err| match api_err {
-            PadlApiError::PlayerNotCreated => PadlErrorCode::UserNotInDB,
-        });
+        .and_then(padl_error_from_api);
     (error_code, error_message)
 }
 
+/// Errors the player should know about, other codes are only relevant for debugging
+fn padl_error_from_api(api_err: PadlApiError) -> Option<PadlErrorCode> {
+    match api_err {
+        PadlApiError::PlayerNotCreated => Some(PadlErrorCode::UserNotInDB),
+        PadlApiError::NotEnoughResources => Some(PadlErrorCode::NotEnoughResources),
+        PadlApiError::NotEnoughKarma => Some(PadlErrorCode::NotEnoughKarma),
+        PadlApiError::NotEnoughMana => Some(PadlErrorCode::NotEnoughMana),
+        PadlApiError::NotEnoughSupply => Some(PadlErrorCode::NotEnoughSupply),
+        PadlApiError::BuildingFull => Some(PadlErrorCode::BuildingFull(None)),
+        PadlApiError::InvalidPlacement => Some(PadlErrorCode::CannotBuildHere),
+        PadlApiError::BuildingLocked => Some(PadlErrorCode::BuildingLocked),
+        PadlApiError::AbilityNotReady | PadlApiError::TooManyRequests => {
+            Some(PadlErrorCode::NotReadyYet)
+        }
+        PadlApiError::PathBlocked => Some(PadlErrorCode::PathBlocked),
+        PadlApiError::CannotDefendHere => Some(PadlErrorCode::CannotDefendHere),
+        PadlApiError::HoboBusy => Some(PadlErrorCode::UnitsBusy),
+        PadlApiError::NotOwned
+        | PadlApiError::StoryViolation
+        | PadlApiError::NotFound
+        | PadlApiError::InvalidTask
+        | PadlApiError::InvalidRequest
+        | PadlApiError::Internal
+        | PadlApiError::AdminRequired => None,
+    }
+}
+
 impl std::convert::From<stdweb::Value> for AjaxError {
     fn from(val: stdweb::Value) -> Self {
         if let Some(obj) = val.as_object() {
@@ -120,6 +145,19 @@ impl std::convert::From<stdweb::Value> for AjaxError {
                         padl_error = code;
                         text = msg;
                     }
+                    // Game-master errors come with a JSON body of type `PadlApiErrorBody`
+                    "padlcode" => {
+                        padl_error = v
+                            .try_into()
+                            .ok()
+                            .and_then(PadlApiError::try_from_num)
+                            .and_then(padl_error_from_api);
+                    }
+                    "message" => {
+                        if let Some(msg) = v.into_string() {
+                            text = Some(msg);
+                        }
+                    }
                     _ => { /* NOP */ }
                 }
             }
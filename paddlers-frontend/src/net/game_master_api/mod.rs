@@ -124,7 +124,13 @@ impl RestApiState {
     pub fn http_create_player(&mut self) -> PadlResult<()> {
         if !SENT_PLAYER_CREATION.load(std::sync::atomic::Ordering::Relaxed) {
             let display_name = read_jwt_preferred_username().unwrap_or("Unnamed Player".to_owned());
-            let msg = PlayerInitData { display_name };
+            let world = crate::net::url::read_world_id().ok();
+            let invited_by = crate::net::url::read_invite_code().ok();
+            let msg = PlayerInitData {
+                display_name,
+                world,
+                invited_by,
+            };
             let request_string = &serde_json::to_string(&msg).unwrap();
             let promise = ajax::send(
                 "POST",
@@ -203,13 +209,20 @@ impl RestApiState {
         while let Some((promise, afterwards)) = self.queue.pop_front() {
             let error_chan = error.clone_sender();
             stdweb::spawn_local(promise.map(move |r| {
-                if r.is_err() {
-                    let err: PadlResult<()> =
-                        PadlErrorCode::RestAPI(format!("Rest API Error: {:?}", r.unwrap_err()))
-                            .dev();
-                    error_chan
-                        .send(err.unwrap_err())
-                        .expect("sending over mpsc");
+                if let Err(ajax) = r {
+                    let err = match ajax {
+                        // Actions rejected by the game-master are explained to the player
+                        AjaxError {
+                            padl_error: Some(code),
+                            status_code,
+                            ..
+                        } if status_code != 401 => PadlError::user_err(code),
+                        ajax => PadlError::dev_err(PadlErrorCode::RestAPI(format!(
+                            "Rest API Error: {:?}",
+                            ajax
+                        ))),
+                    };
+                    error_chan.send(err).expect("sending over mpsc");
                 } else {
                     if let Some(req) = afterwards {
                         match req {
@@ -6,11 +6,11 @@ use graphql_client::GraphQLQuery;
 use paddlers_shared_lib::prelude::*;
 
 pub(super) fn http_read_incoming_attacks(
-    min_attack_id: Option<i64>,
+    after: Option<String>,
     village_id: VillageKey,
 ) -> PadlResult<impl Future<Output = PadlResult<AttacksResponse>>> {
     let request_body = AttacksQuery::build_query(attacks_query::Variables {
-        min_attack_id,
+        after,
         village_id: village_id.num(),
     });
     let request_string = &serde_json::to_string(&request_body)?;
@@ -145,7 +145,7 @@ pub(super) fn http_read_player_info(
 
 pub(super) fn http_read_leaderboard(
 ) -> PadlResult<impl Future<Output = PadlResult<LeaderboardResponse>>> {
-    let request_body = LeaderboardQuery::build_query(leaderboard_query::Variables { offset: 0 });
+    let request_body = LeaderboardQuery::build_query(leaderboard_query::Variables);
     let request_string = &serde_json::to_string(&request_body)?;
     let promise = ajax::send("POST", &graphql_url()?, request_string)?;
     Ok(promise.map(|x| {
@@ -156,17 +156,17 @@ pub(super) fn http_read_leaderboard(
                 .ok_or(PadlError::dev_err(PadlErrorCode::InvalidGraphQLData(
                     "leaderboard",
                 )))?;
-        let response = response.scoreboard;
+        let response = response.scoreboard.edges;
         Ok(response)
     }))
 }
 
 pub(super) fn http_read_reports(
-    min_report_id: Option<i64>,
+    after: Option<String>,
     village_id: VillageKey,
 ) -> PadlResult<impl Future<Output = PadlResult<ReportsResponse>>> {
     let request_body = ReportsQuery::build_query(reports_query::Variables {
-        min_report_id,
+        after,
         village_id: village_id.num(),
     });
     let request_string = &serde_json::to_string(&request_body)?;
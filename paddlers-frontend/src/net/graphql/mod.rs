@@ -11,18 +11,19 @@ use futures::Future;
 use futures_util::future::FutureExt;
 use futures_util::try_future::TryFutureExt;
 use paddlers_shared_lib::prelude::VillageKey;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::cell::RefCell;
 
+/// Cursors of the last attack and report received, only newer ones are queried
 pub struct GraphQlState {
-    next_attack_id: AtomicI64,
-    next_report_id: AtomicI64,
+    attacks_cursor: RefCell<Option<String>>,
+    reports_cursor: RefCell<Option<String>>,
 }
 
 impl GraphQlState {
     pub(super) const fn new() -> GraphQlState {
         GraphQlState {
-            next_attack_id: AtomicI64::new(0),
-            next_report_id: AtomicI64::new(0),
+            attacks_cursor: RefCell::new(None),
+            reports_cursor: RefCell::new(None),
         }
     }
 
@@ -31,28 +32,32 @@ impl GraphQlState {
     ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
         current_village_async().map(|fut| {
             fut.and_then(move |village: VillageKey| {
-                http_read_incoming_attacks(
-                    Some(self.next_attack_id.load(Ordering::Relaxed)),
-                    village,
-                )
-                .expect("Query building error")
+                http_read_incoming_attacks(self.attacks_cursor.borrow().clone(), village)
+                    .expect("Query building error")
             })
             .map(move |response| {
                 let r: AttacksResponse = response?;
                 if let Some(data) = &r.data {
-                    let max_id = data
-                        .village
-                        .attacks
-                        .iter()
-                        .map(|atk| atk.id.parse().unwrap())
-                        .fold(0, i64::max);
-                    let next = self.next_attack_id.load(Ordering::Relaxed).max(max_id + 1);
-                    self.next_attack_id.store(next, Ordering::Relaxed);
+                    let page_info = &data.village.attacks.page_info;
+                    if let Some(cursor) = &page_info.end_cursor {
+                        self.attacks_cursor.replace(Some(cursor.clone()));
+                    }
+                    if page_info.has_next_page {
+                        crate::net::request_attacks_page();
+                    }
                 }
                 Ok(NetMsg::Attacks(r))
             })
         })
     }
+    /// Loads all attacks again, including those before the stored cursor.
+    /// Resting visitors of loaded attacks may have been released since they were loaded.
+    pub(super) fn reload_attacks_query(
+        &'static self,
+    ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        self.attacks_cursor.replace(None);
+        self.attacks_query()
+    }
     pub(super) fn resource_query(&self) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
         current_village_async().map(|fut| {
             fut.and_then(move |village: VillageKey| {
@@ -126,7 +131,7 @@ impl GraphQlState {
                 1,
                 response?
                     .into_iter()
-                    .map(|player| (player.display_name, player.karma))
+                    .map(|edge| (edge.node.display_name, edge.node.karma))
                     .collect(),
             ))
         }))
@@ -136,19 +141,18 @@ impl GraphQlState {
     ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
         current_village_async().map(|fut| {
             fut.and_then(move |village: VillageKey| {
-                http_read_reports(Some(self.next_report_id.load(Ordering::Relaxed)), village)
+                http_read_reports(self.reports_cursor.borrow().clone(), village)
                     .expect("Query building error")
             })
             .map(move |response| {
                 let data: ReportsResponse = response?;
-                let max_id = data
-                    .village
-                    .reports
-                    .iter()
-                    .map(|r| r.id.parse().unwrap())
-                    .fold(0, i64::max);
-                let next = self.next_report_id.load(Ordering::Relaxed).max(max_id + 1);
-                self.next_report_id.store(next, Ordering::Relaxed);
+                let page_info = &data.village.reports.page_info;
+                if let Some(cursor) = &page_info.end_cursor {
+                    self.reports_cursor.replace(Some(cursor.clone()));
+                }
+                if page_info.has_next_page {
+                    crate::net::request_reports_page();
+                }
                 Ok(NetMsg::Reports(data))
             })
         })
@@ -22,10 +22,10 @@ fn timestamp(s: &String) -> graphql_types::GqlTimestamp {
 )]
 pub struct AttacksQuery;
 pub type AttacksResponse = Response<attacks_query::ResponseData>;
-pub type HoboEffect = attacks_query::AttacksQueryVillageAttacksUnitsHoboEffects;
+pub type HoboEffect = attacks_query::AttacksQueryVillageAttacksEdgesNodeUnitsHoboEffects;
 pub type HoboAttribute = attacks_query::HoboAttributeType;
 
-impl attacks_query::AttacksQueryVillageAttacks {
+impl attacks_query::AttacksQueryVillageAttacksEdgesNode {
     #[allow(dead_code)]
     pub fn departure(&self) -> chrono::NaiveDateTime {
         timestamp(&self.departure).to_chrono()
@@ -233,7 +233,7 @@ impl Into<UnitColor> for &attacks_query::UnitColor {
 )]
 pub struct LeaderboardQuery;
 pub type LeaderboardRawResponse = Response<leaderboard_query::ResponseData>;
-pub type LeaderboardResponse = Vec<leaderboard_query::LeaderboardQueryScoreboard>;
+pub type LeaderboardResponse = Vec<leaderboard_query::LeaderboardQueryScoreboardEdges>;
 
 use paddlers_shared_lib::story::story_state::StoryState;
 impl Into<StoryState> for &PlayerStoryState {
@@ -2,12 +2,14 @@ pub mod ajax;
 pub mod authentication;
 pub mod game_master_api;
 pub mod graphql;
+pub mod notifications;
 pub mod state;
 pub mod url;
 
 use crate::game::player_info::PlayerInfo;
 use game_master_api::RestApiState;
 use graphql::{query_types::*, GraphQlState};
+use paddlers_shared_lib::api::notifications::Notification;
 use std::sync::Arc;
 
 use stdweb::spawn_local;
@@ -15,7 +17,7 @@ use stdweb::spawn_local;
 use futures::future::TryFutureExt;
 use futures::Future;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU32, Ordering},
     mpsc::Sender,
     Mutex,
 };
@@ -42,10 +44,17 @@ pub enum NetUpdateRequest {
     PlayerInfo,
 }
 
+/// While notifications are pushed, resources are still reloaded every n-th interval.
+/// Income of workers accumulates without any notification.
+const RESOURCE_REFRESH_INTERVALS: u32 = 6;
+
 struct NetState {
     interval_ms: u32,
     logged_in: AtomicBool,
     game_ready: AtomicBool,
+    /// Polling is only necessary while the notification WebSocket is not connected
+    push_connected: AtomicBool,
+    intervals: AtomicU32,
     chan: Option<Mutex<Sender<NetMsg>>>,
     gql_state: GraphQlState,
     rest: Option<Arc<Mutex<RestApiState>>>,
@@ -54,6 +63,8 @@ static mut STATIC_NET_STATE: NetState = NetState {
     interval_ms: 5_000,
     logged_in: AtomicBool::new(false),
     game_ready: AtomicBool::new(false),
+    push_connected: AtomicBool::new(false),
+    intervals: AtomicU32::new(0),
     chan: None,
     gql_state: GraphQlState::new(),
     rest: None,
@@ -88,6 +99,7 @@ pub fn start_network_thread() {
         }
         STATIC_NET_STATE.work();
     }
+    notifications::connect();
 }
 /// Sends all requests out necessary for the client state
 pub fn request_client_state() {
@@ -107,6 +119,50 @@ pub fn request_client_state() {
         }
     }
 }
+/// Reloads everything that changes without interaction of the player
+fn request_volatile_state() {
+    unsafe {
+        if STATIC_NET_STATE.game_ready.load(Ordering::Relaxed) {
+            STATIC_NET_STATE.poll();
+        }
+    }
+}
+/// Reloads what the game-master has notified to have changed
+fn handle_notification(notification: Notification) {
+    unsafe {
+        let state = &STATIC_NET_STATE;
+        if !state.game_ready.load(Ordering::Relaxed) {
+            return;
+        }
+        match notification {
+            Notification::NewAttack(_) => {
+                state.spawn(state.gql_state.attacks_query());
+            }
+            Notification::VisitorReleased(_) => {
+                state.spawn(state.gql_state.reload_attacks_query());
+            }
+            Notification::ReportCreated(_) => {
+                state.spawn(state.gql_state.reports_query());
+            }
+            Notification::ResourcesChanged(_) => {
+                state.spawn(state.gql_state.resource_query());
+                state.spawn(GraphQlState::player_info_query());
+            }
+        }
+    }
+}
+/// Loads the attacks after the stored cursor, called again while there are more pages
+pub(super) fn request_attacks_page() {
+    unsafe {
+        STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.attacks_query());
+    }
+}
+/// Loads the reports after the stored cursor, called again while there are more pages
+pub(super) fn request_reports_page() {
+    unsafe {
+        STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.reports_query());
+    }
+}
 pub fn request_map_read(min: i32, max: i32) {
     unsafe {
         if STATIC_NET_STATE.logged_in.load(Ordering::Relaxed) {
@@ -141,13 +197,22 @@ impl NetState {
     // For frequent updates
     fn work(&'static self) {
         if self.game_ready.load(Ordering::Relaxed) {
-            self.spawn(self.gql_state.attacks_query());
-            self.spawn(self.gql_state.reports_query());
-            self.spawn(self.gql_state.resource_query());
-            self.spawn(GraphQlState::player_info_query());
+            if !self.push_connected.load(Ordering::Relaxed) {
+                self.poll();
+            } else if self.intervals.fetch_add(1, Ordering::Relaxed) % RESOURCE_REFRESH_INTERVALS
+                == 0
+            {
+                self.spawn(self.gql_state.resource_query());
+            }
         }
         self.register_networking();
     }
+    fn poll(&'static self) {
+        self.spawn(self.gql_state.attacks_query());
+        self.spawn(self.gql_state.reports_query());
+        self.spawn(self.gql_state.resource_query());
+        self.spawn(GraphQlState::player_info_query());
+    }
 
     fn get_channel(&self) -> Sender<NetMsg> {
         match self.chan.as_ref().unwrap().lock() {
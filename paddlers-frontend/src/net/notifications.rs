@@ -0,0 +1,66 @@
+//! Change notifications pushed by the game-master over a WebSocket.
+//!
+//! While the WebSocket is connected, notifications replace the periodic polling in `NetState::work`.
+//! After a disconnect, polling takes over again until the connection is re-established.
+
+use super::{authentication::read_jwt, url::game_master_url, STATIC_NET_STATE};
+use crate::prelude::*;
+use paddlers_shared_lib::api::notifications::{Notification, NOTIFICATION_PROTOCOL};
+use std::sync::atomic::Ordering;
+use stdweb::web::event::{IMessageEvent, SocketCloseEvent, SocketMessageEvent, SocketOpenEvent};
+use stdweb::web::{IEventTarget, WebSocket};
+
+const RECONNECT_DELAY_MS: u32 = 10_000;
+
+/// Opens the WebSocket, retries later if that is not possible right now
+pub fn connect() {
+    if let Err(e) = try_connect() {
+        unsafe { STATIC_NET_STATE.net_msg_to_game_thread(super::NetMsg::Error(e)) };
+        stdweb::web::set_timeout(connect, RECONNECT_DELAY_MS);
+    }
+}
+
+fn try_connect() -> PadlResult<()> {
+    let token = read_jwt().ok_or(PadlError::dev_err(PadlErrorCode::NoDataFromBrowser("JWT")))?;
+    // http -> ws, https -> wss
+    let url = format!(
+        "{}notifications",
+        game_master_url()?.replacen("http", "ws", 1)
+    );
+    // The token is offered as sub-protocol to keep it out of URLs and thus out of logs
+    let ws = WebSocket::new_with_protocols(&url, &[NOTIFICATION_PROTOCOL, token.as_str()])
+        .map_err(|e| {
+            PadlError::dev_err(PadlErrorCode::BrowserError(format!(
+                "Opening WebSocket failed: {:?}",
+                e
+            )))
+        })?;
+    ws.add_event_listener(|_: SocketOpenEvent| {
+        unsafe {
+            STATIC_NET_STATE
+                .push_connected
+                .store(true, Ordering::Relaxed)
+        };
+        // Changes may have been missed while disconnected
+        super::request_volatile_state();
+    });
+    ws.add_event_listener(|event: SocketMessageEvent| {
+        if let Some(text) = event.data().into_text() {
+            match serde_json::from_str::<Notification>(&text) {
+                Ok(notification) => super::handle_notification(notification),
+                Err(e) => unsafe {
+                    STATIC_NET_STATE.net_msg_to_game_thread(super::NetMsg::Error(e.into()))
+                },
+            }
+        }
+    });
+    ws.add_event_listener(|_: SocketCloseEvent| {
+        unsafe {
+            STATIC_NET_STATE
+                .push_connected
+                .store(false, Ordering::Relaxed)
+        };
+        stdweb::web::set_timeout(connect, RECONNECT_DELAY_MS);
+    });
+    Ok(())
+}
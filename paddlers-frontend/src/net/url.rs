@@ -26,6 +26,22 @@ pub fn read_current_village_id() -> PadlResult<VillageKey> {
     })
 }
 
+/// Parses the location (URL) of the browser to look up the world selected for a new player
+///
+/// There is no world picker in the game itself, links to a specific world carry it as `?world=<id>`.
+/// Without it, the game-master picks the world of the inviting friend or the least populated one.
+pub fn read_world_id() -> PadlResult<WorldKey> {
+    query_param("world").and_then(|s| match s.parse() {
+        Ok(num) => Ok(WorldKey(num)),
+        Err(e) => PadlErrorCode::UrlParseError(format!("{}", e)).dev(),
+    })
+}
+
+/// Parses the location (URL) of the browser to look up the invite code of a friend
+pub fn read_invite_code() -> PadlResult<String> {
+    query_param("invite")
+}
+
 fn hostname() -> PadlResult<String> {
     stdweb::web::window()
         .location()
@@ -1,19 +1,20 @@
+pub(crate) mod admin;
 mod attacks;
 mod reports;
 mod shop;
 mod story;
 
-pub(crate) use attacks::visitor_satisfied_notification;
+pub(crate) use attacks::{visitor_satisfied_notification, VisitorNotificationLimiter};
 pub(crate) use reports::collect_report_rewards;
 pub(crate) use story::story_transition;
 
 use crate::authentication::Authentication;
+use crate::error::{GameMasterError, GameMasterResult};
 use crate::game_master::attack_funnel::PlannedAttack;
 use crate::setup::initialize_new_player_account;
-use crate::StringErr;
 use actix_web::error::BlockingError;
-use actix_web::{web, HttpResponse, Responder};
-use futures::future::join_all;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use futures::future::{join_all, Either};
 use futures::Future;
 use paddlers_shared_lib::api::{
     attacks::AttackDescriptor,
@@ -30,7 +31,6 @@ pub fn index() -> impl Responder {
 
 pub(crate) fn purchase_prophet(
     pool: web::Data<crate::db::Pool>,
-    actors: web::Data<crate::ActorAddresses>,
     body: web::Json<ProphetPurchase>,
     mut auth: Authentication,
 ) -> impl Future<Item = HttpResponse, Error = ()> {
@@ -38,18 +38,13 @@ pub(crate) fn purchase_prophet(
     std::mem::drop(body);
     web::block(move || {
         let db: crate::db::DB = pool.get_ref().into();
-        check_owns_village0(&db, &auth, village)?;
-        let result = db.try_buy_prophet(
-            village,
-            &actors,
-            auth.player_object(&db).ok_or("No such player".to_owned())?,
-        );
-        result
+        check_owns_village(&db, &auth, village)?;
+        let player = auth.player_key(&db)?;
+        db.try_buy_prophet(village, player)
     })
     .then(
-        |result: Result<(), BlockingError<std::string::String>>| match result {
-            Err(BlockingError::Error(msg)) => Ok(HttpResponse::Forbidden().body(msg).into()),
-            Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().into()),
+        |result: Result<(), BlockingError<GameMasterError>>| match result {
+            Err(e) => Ok(GameMasterError::from(e).error_response()),
             Ok(()) => Ok(HttpResponse::Ok().into()),
         },
     )
@@ -60,54 +55,43 @@ pub(crate) fn purchase_building(
     body: web::Json<BuildingPurchase>,
     mut auth: Authentication,
     addr: web::Data<crate::ActorAddresses>,
-) -> impl Responder {
+) -> GameMasterResult<HttpResponse> {
     let db: crate::db::DB = pool.get_ref().into();
 
     let building = body.building_type.into();
-    if let Err(err) = check_owns_village(&db, &auth, body.village) {
-        return err;
-    }
-    if !db.player_allowed_to_build(
-        building,
-        body.village,
-        &auth.player_object(&db).expect("no player"),
-    ) {
-        return HttpResponse::BadRequest()
-            .body("Player not allowed to build")
-            .into();
+    check_owns_village(&db, &auth, body.village)?;
+    let player = auth
+        .player_object(&db)
+        .ok_or(GameMasterError::PlayerNotCreated)?;
+    if !db.player_allowed_to_build(building, body.village, player) {
+        return Err(GameMasterError::BuildingLocked(building));
     }
+    let player = player.key();
 
-    db.try_buy_building(building, (body.x, body.y), body.village)
-        .and_then(|_| {
-            let player = auth.player_key(&db)?;
-            db.building_insertion_triggers(building, player, addr)
-        })
-        .map_or_else(|e| HttpResponse::from(&e), |_| HttpResponse::Ok().into())
+    db.try_buy_building(building, (body.x, body.y), body.village)?;
+    db.building_insertion_triggers(building, player, addr)?;
+    Ok(HttpResponse::Ok().into())
 }
 
 pub fn delete_building(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<BuildingDeletion>,
     auth: Authentication,
-) -> impl Responder {
+) -> GameMasterResult<HttpResponse> {
     let db: crate::db::DB = pool.get_ref().into();
 
-    if let Err(err) = check_owns_village(&db, &auth, body.village) {
-        return err;
-    }
+    check_owns_village(&db, &auth, body.village)?;
 
-    if let Some(building) =
-        db.find_building_by_coordinates(body.x as i32, body.y as i32, body.village)
-    {
-        if building.building_type.can_be_deleted() {
-            db.delete_building(&building);
-            HttpResponse::Ok().into()
-        } else {
-            HttpResponse::BadRequest().body("This building cannot be deleted".to_owned())
-        }
-    } else {
-        HttpResponse::BadRequest().body(format!("No building at {}|{}", body.x, body.y))
+    let building = db
+        .find_building_by_coordinates(body.x as i32, body.y as i32, body.village)
+        .ok_or(GameMasterError::NotFound("Building"))?;
+    if !building.building_type.can_be_deleted() {
+        return Err(GameMasterError::InvalidRequest(
+            "This building cannot be deleted".to_owned(),
+        ));
     }
+    db.delete_building(&building);
+    Ok(HttpResponse::Ok().into())
 }
 
 pub(super) fn overwrite_tasks(
@@ -115,31 +99,25 @@ pub(super) fn overwrite_tasks(
     body: web::Json<TaskList>,
     addr: web::Data<crate::ActorAddresses>,
     auth: Authentication,
-) -> impl Responder {
+) -> GameMasterResult<HttpResponse> {
     let db: crate::db::DB = pool.get_ref().into();
-    if let Err(err) = check_owns_worker(&db, &auth, body.worker_id) {
-        return err;
+    check_owns_worker(&db, &auth, body.worker_id)?;
+
+    let validated = crate::worker_actions::validate_task_list(&db, &body.0).map_err(|e| {
+        println!("Task creation failed. {} \n Body: {:?}", e, body.0);
+        e
+    })?;
+    for upd in validated.update_tasks {
+        db.update_task(&upd);
     }
-
-    match crate::worker_actions::validate_task_list(&db, &body.0) {
-        Ok(validated) => {
-            for upd in validated.update_tasks {
-                db.update_task(&upd);
-            }
-            crate::worker_actions::replace_worker_tasks(
-                &db,
-                &addr.town_worker,
-                body.worker_id,
-                &validated.new_tasks,
-                validated.village_id,
-            );
-        }
-        Err(e) => {
-            println!("Task creation failed. {} \n Body: {:?}", e, body.0);
-            return HttpResponse::BadRequest().body("Couldn't create tasks");
-        }
-    }
-    HttpResponse::Ok().into()
+    crate::worker_actions::replace_worker_tasks(
+        &db,
+        &addr.town_worker,
+        body.worker_id,
+        &validated.new_tasks,
+        validated.village_id,
+    );
+    Ok(HttpResponse::Ok().into())
 }
 
 /// Must be called by an identified user (via JWT) before using any other Game-Master or GQL services
@@ -147,13 +125,10 @@ pub(super) fn new_player(
     pool: web::Data<crate::db::Pool>,
     auth: Authentication,
     body: web::Json<PlayerInitData>,
-) -> impl Responder {
+) -> GameMasterResult<HttpResponse> {
     let db: crate::db::DB = pool.get_ref().into();
-    if let Err(msg) = initialize_new_player_account(&db, auth.user.uuid, &body) {
-        HttpResponse::InternalServerError().body(msg)
-    } else {
-        HttpResponse::Ok().into()
-    }
+    initialize_new_player_account(&db, auth.user.uuid, &body)?;
+    Ok(HttpResponse::Ok().into())
 }
 pub(crate) fn create_attack(
     pool: web::Data<crate::db::Pool>,
@@ -162,63 +137,55 @@ pub(crate) fn create_attack(
     auth: Authentication,
 ) -> impl Future<Item = HttpResponse, Error = actix_web::Error> {
     let pool0 = pool.clone();
-    let pool1 = pool.clone();
     let attack = body.0;
     let (x, y) = attack.to;
     let from_key = attack.from;
     let home_id = from_key.num();
     let attack_funnel = actors.attack_funnel.clone();
 
+    let mut units = attack.units.clone();
+    units.sort();
+    units.dedup();
+    if units.len() != attack.units.len() {
+        return Either::A(futures::future::ok(
+            GameMasterError::InvalidRequest("Hobo listed more than once".to_owned())
+                .error_response(),
+        ));
+    }
+
     let future_hobos = attack
         .units
         .into_iter()
         .map(move |hobo_key| {
             let db: crate::db::DB = pool.clone().get_ref().into();
-            web::block(move || match db.hobo(hobo_key) {
-                Some(hobo) => Ok(hobo),
-                None => Err("Invalid hobo"),
-            })
-            .map_err(|e: BlockingError<_>| match e {
-                BlockingError::Error(msg) => HttpResponse::Forbidden().body(msg).into(),
-                BlockingError::Canceled => internal_server_error("Canceled"),
-            })
-            .and_then(move |hobo| {
-                if hobo.home != home_id {
-                    Err(HttpResponse::Forbidden()
-                        .body("Hobo not from this village")
-                        .into())
-                } else {
-                    Ok(hobo)
-                }
-            })
+            web::block(move || db.hobo(hobo_key).ok_or(GameMasterError::NotFound("Hobo")))
+                .map_err(|e: BlockingError<_>| actix_web::Error::from(GameMasterError::from(e)))
+                .and_then(move |hobo| {
+                    if hobo.home != home_id {
+                        Err(GameMasterError::NotOwned("Hobo").into())
+                    } else {
+                        Ok(hobo)
+                    }
+                })
         })
         .collect::<Vec<_>>();
     let future_hobos = join_all(future_hobos);
 
     let future_villages = web::block(move || {
         let db: crate::db::DB = pool0.get_ref().into();
-        check_owns_village0(&db, &auth, from_key)?;
-        let destination = db.village_at(x as f32, y as f32);
-        if destination.is_none() {
-            Err("Invalid target village".to_owned())
-        } else {
-            Ok(destination.unwrap())
-        }
+        check_owns_village(&db, &auth, from_key)?;
+        let origin_village = db.village(from_key).ok_or(GameMasterError::Internal(
+            "Owned village doesn't exist".to_owned(),
+        ))?;
+        // Only villages in the same world can be reached
+        let target_village = db
+            .village_at(origin_village.world(), x as f32, y as f32)
+            .ok_or(GameMasterError::NotFound("Target village"))?;
+        Ok((origin_village, target_village))
     })
-    .map_err(|e: BlockingError<std::string::String>| match e {
-        BlockingError::Error(msg) => HttpResponse::Forbidden().body(msg).into(),
-        BlockingError::Canceled => internal_server_error("Canceled"),
-    })
-    .and_then(move |target_village| {
-        let db: crate::db::DB = pool1.get_ref().into();
-        if let Some(origin_village) = db.village(from_key) {
-            Ok((origin_village, target_village))
-        } else {
-            Err(internal_server_error("Owned village doesn't exist"))
-        }
-    });
+    .map_err(|e: BlockingError<GameMasterError>| actix_web::Error::from(GameMasterError::from(e)));
     let joined = future_hobos.join(future_villages);
-    joined
+    let response = joined
         .map(
             |(hobos, (origin_village, destination_village))| PlannedAttack {
                 origin_village: Some(origin_village),
@@ -226,38 +193,37 @@ pub(crate) fn create_attack(
                 hobos,
             },
         )
-        .and_then(move |pa| attack_funnel.try_send(pa).map_err(internal_server_error))
-        .map(|()| HttpResponse::Ok().into())
+        .and_then(move |pa| attack_funnel.send(pa).map_err(internal_server_error))
+        .map(|result| match result {
+            Ok(()) => HttpResponse::Ok().into(),
+            Err(e) => e.error_response(),
+        });
+    Either::B(response)
 }
 
 fn check_owns_worker(
     db: &crate::db::DB,
     auth: &Authentication,
     v: WorkerKey,
-) -> Result<(), HttpResponse> {
+) -> GameMasterResult<()> {
     if db.worker_owned_by(v, auth.user.uuid) {
         Ok(())
     } else {
-        Err(HttpResponse::Forbidden().body(format!("Worker not owned by player")))
-    }
-}
-fn check_owns_village0(db: &crate::db::DB, auth: &Authentication, v: VillageKey) -> StringErr {
-    if db.village_owned_by(v, auth.user.uuid) {
-        Ok(())
-    } else {
-        Err("Village not owned by player".to_owned())
+        Err(GameMasterError::NotOwned("Worker"))
     }
 }
 fn check_owns_village(
     db: &crate::db::DB,
     auth: &Authentication,
     v: VillageKey,
-) -> Result<(), HttpResponse> {
-    check_owns_village0(db, auth, v).map_err(|msg| HttpResponse::Forbidden().body(msg))
+) -> GameMasterResult<()> {
+    if db.village_owned_by(v, auth.user.uuid) {
+        Ok(())
+    } else {
+        Err(GameMasterError::NotOwned("Village"))
+    }
 }
 
 fn internal_server_error(e: impl ToString) -> actix_web::Error {
-    HttpResponse::InternalServerError()
-        .body(e.to_string())
-        .into()
+    GameMasterError::Internal(e.to_string()).into()
 }
@@ -0,0 +1,133 @@
+//! Game master API for operating the game, only available to users with the admin role
+
+use crate::authentication::Admin;
+use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
+use crate::game_master::attack_spawn::SendAnarchistAttack;
+use crate::game_master::town_worker::ListScheduledEvents;
+use crate::notifications::notify;
+use actix_web::{web, HttpResponse};
+use futures::Future;
+use paddlers_shared_lib::api::admin::*;
+use paddlers_shared_lib::api::notifications::Notification;
+use paddlers_shared_lib::game_mechanics::hobos::HoboLevel;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::strum::IntoEnumIterator;
+
+pub(crate) fn inspect_player(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<PlayerKey>,
+    _admin: Admin,
+) -> GameMasterResult<HttpResponse> {
+    let db: DB = pool.get_ref().into();
+    let player = db
+        .player(body.0)
+        .ok_or(GameMasterError::NotFound("Player"))?;
+    let villages = db
+        .player_villages(player.key())
+        .iter()
+        .map(|v| VillageOverview {
+            village: v.key(),
+            x: v.x,
+            y: v.y,
+            resources: ResourceType::iter()
+                .map(|res| (res, db.resource_with_income(res, v.key())))
+                .collect(),
+            workers: db.workers(v.key()).iter().map(|w| w.key()).collect(),
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(PlayerOverview {
+        player: player.key(),
+        uuid: player.uuid.to_string(),
+        display_name: player.display_name,
+        karma: player.karma,
+        story_state: player.story_state,
+        villages,
+    }))
+}
+
+pub(crate) fn grant_resources(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<ResourceGrant>,
+    _admin: Admin,
+) -> GameMasterResult<HttpResponse> {
+    let db: DB = pool.get_ref().into();
+    let village = body.village;
+    db.village(village)
+        .ok_or(GameMasterError::NotFound("Village"))?;
+    // Stored amounts must be up to date, otherwise collected income would be added on top of a subtraction
+    db.collect_income(village);
+    db.transaction(|| {
+        for (res, n) in &body.resources {
+            db.add_resource(*res, village, *n)?;
+        }
+        Ok::<(), GameMasterError>(())
+    })?;
+    notify(Notification::ResourcesChanged(village));
+    Ok(HttpResponse::Ok().into())
+}
+
+pub(crate) fn grant_karma(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<KarmaGrant>,
+    _admin: Admin,
+) -> GameMasterResult<HttpResponse> {
+    let db: DB = pool.get_ref().into();
+    db.player(body.player)
+        .ok_or(GameMasterError::NotFound("Player"))?;
+    db.add_karma(body.player, body.karma)?;
+    Ok(HttpResponse::Ok().into())
+}
+
+/// Sets the story state without checking transitions or triggering story actions
+pub(crate) fn reset_story_state(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<StoryStateReset>,
+    _admin: Admin,
+) -> GameMasterResult<HttpResponse> {
+    let db: DB = pool.get_ref().into();
+    db.player(body.player)
+        .ok_or(GameMasterError::NotFound("Player"))?;
+    db.set_story_state(body.player, body.story_state)?;
+    Ok(HttpResponse::Ok().into())
+}
+
+pub(crate) fn send_anarchist_attack(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<AnarchistAttackOrder>,
+    addr: web::Data<crate::ActorAddresses>,
+    _admin: Admin,
+) -> GameMasterResult<HttpResponse> {
+    let db: DB = pool.get_ref().into();
+    let village = body.village;
+    db.village(village)
+        .ok_or(GameMasterError::NotFound("Village"))?;
+    // Villages without owner are attacked with the weakest level
+    let level = db
+        .player_by_village(village)
+        .map(|p| HoboLevel::anarchist(p.karma))
+        .unwrap_or_else(HoboLevel::zero);
+    addr.attack_worker
+        .try_send(SendAnarchistAttack { village, level })
+        .map_err(|e| GameMasterError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().into())
+}
+
+pub(crate) fn list_scheduled_events(
+    addr: web::Data<crate::ActorAddresses>,
+    _admin: Admin,
+) -> impl Future<Item = HttpResponse, Error = actix_web::Error> {
+    addr.town_worker
+        .send(ListScheduledEvents)
+        .map_err(super::internal_server_error)
+        .map(|events| {
+            let events: Vec<ScheduledEventInfo> = events
+                .into_iter()
+                .map(|(event, time)| ScheduledEventInfo {
+                    event: format!("{:?}", event),
+                    time: time.naive_utc(),
+                })
+                .collect();
+            HttpResponse::Ok().json(events)
+        })
+}
@@ -1,16 +1,81 @@
-use crate::game_master::event::Event;
-use crate::game_master::town_worker::TownWorkerEventMsg;
-use actix_web::Responder;
-use actix_web::{web, HttpResponse};
+use crate::authentication::Authentication;
+use crate::db::DB;
+use crate::error::GameMasterError;
+use crate::game_master::town_worker::EvaluateAttackMsg;
+use actix_web::{web, HttpResponse, ResponseError};
+use chrono::{Duration, NaiveDateTime};
+use futures::future::{ok, Either};
+use futures::Future;
+use paddlers_shared_lib::api::attacks::VisitorSatisfaction;
 use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::sql::GameDB;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Minimal time between two notifications about the same visitor that are evaluated
+const NOTIFICATION_COOLDOWN_MS: i64 = 1000;
+
+/// Remembers when notifications about visitors have been evaluated, to limit the load clients can cause
+#[derive(Default)]
+pub(crate) struct VisitorNotificationLimiter {
+    last_evaluation: Mutex<HashMap<i64, NaiveDateTime>>,
+}
 
 pub(crate) fn visitor_satisfied_notification(
+    pool: web::Data<crate::db::Pool>,
     body: web::Json<HoboKey>,
+    auth: Authentication,
+    limiter: web::Data<VisitorNotificationLimiter>,
     addr: web::Data<crate::ActorAddresses>,
-) -> impl Responder {
-    let event = Event::CheckVisitorHp { hobo_id: body.0 };
-    addr.town_worker
-        .try_send(TownWorkerEventMsg(event, chrono::Utc::now()))
-        .expect("Sending event failed");
-    HttpResponse::Ok()
+) -> impl Future<Item = HttpResponse, Error = actix_web::Error> {
+    let db: DB = pool.get_ref().into();
+    let hobo = body.0;
+    let attack = db
+        .hobo_attack_info(hobo)
+        .into_iter()
+        .map(|(atk, _info)| atk)
+        .find(|atk| db.village_owned_by(atk.destination(), auth.user.uuid));
+    let attack = match attack {
+        Some(atk) => atk,
+        None => {
+            return Either::A(ok(
+                GameMasterError::NotOwned("Visited village").error_response()
+            ))
+        }
+    };
+    if !limiter.allow(hobo, db.now()) {
+        return Either::A(ok(GameMasterError::TooManyRequests.error_response()));
+    }
+    let msg = EvaluateAttackMsg {
+        attack: attack.key(),
+        hobo,
+    };
+    let response = addr
+        .town_worker
+        .send(msg)
+        .map_err(super::internal_server_error)
+        .map(|outcome| {
+            let answer = match outcome {
+                Some(true) => VisitorSatisfaction::Satisfied,
+                Some(false) => VisitorSatisfaction::LeftUnsatisfied,
+                None => VisitorSatisfaction::NotSatisfied,
+            };
+            HttpResponse::Ok().json(answer)
+        });
+    Either::B(response)
+}
+
+impl VisitorNotificationLimiter {
+    /// Returns false if the visitor has been checked less than a cooldown ago
+    fn allow(&self, hobo: HoboKey, now: NaiveDateTime) -> bool {
+        let cooldown = Duration::milliseconds(NOTIFICATION_COOLDOWN_MS);
+        let mut last_evaluation = self.last_evaluation.lock().unwrap();
+        // Forget about visitors that could be checked again anyway
+        last_evaluation.retain(|_, t| *t + cooldown > now);
+        if last_evaluation.contains_key(&hobo.num()) {
+            return false;
+        }
+        last_evaluation.insert(hobo.num(), now);
+        true
+    }
 }
@@ -1,8 +1,9 @@
 use crate::authentication::Authentication;
 use crate::db::CollectReportRewardsMessage;
+use crate::error::GameMasterError;
 use actix::prelude::*;
 use actix_web::error::BlockingError;
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpResponse, ResponseError};
 use paddlers_shared_lib::api::reports::ReportCollect;
 use paddlers_shared_lib::prelude::*;
 
@@ -16,16 +17,15 @@ pub(crate) fn collect_report_rewards(
         // Check that request is valid and forward request to actor
         let db: crate::db::DB = pool.get_ref().into();
         for rid in body.0.reports {
-            let report = db.report(rid).ok_or("No such report".to_owned())?;
-            super::check_owns_village0(&db, &auth, report.village())?;
+            let report = db.report(rid).ok_or(GameMasterError::NotFound("Report"))?;
+            super::check_owns_village(&db, &auth, report.village())?;
             spawn_report_collection(&addr, report);
         }
         Ok(())
     })
     .then(
-        |result: Result<(), BlockingError<std::string::String>>| match result {
-            Err(BlockingError::Error(msg)) => Ok(HttpResponse::Forbidden().body(msg).into()),
-            Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().into()),
+        |result: Result<(), BlockingError<GameMasterError>>| match result {
+            Err(e) => Ok(GameMasterError::from(e).error_response()),
             Ok(()) => Ok(HttpResponse::Ok().into()),
         },
     )
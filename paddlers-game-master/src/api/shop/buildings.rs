@@ -1,7 +1,10 @@
 use crate::buildings::BuildingFactory;
 use crate::db::DB;
-use crate::StringErr;
+use crate::error::{GameMasterError, GameMasterResult};
+use crate::notifications::notify;
+use paddlers_shared_lib::api::notifications::Notification;
 use paddlers_shared_lib::story::story_state::StoryState;
+use paddlers_shared_lib::story::story_transitions::IStoryProgress;
 use paddlers_shared_lib::{api::shop::*, game_mechanics::attributes::Attributes, prelude::*};
 
 impl DB {
@@ -10,12 +13,15 @@ impl DB {
         typ: BuildingType,
         pos: (usize, usize),
         village: VillageKey,
-    ) -> StringErr {
-        self.building_has_space(typ, pos, village)
-            .map(|_| self.try_spend(&typ.price(), village))
-            .map(|_| {
-                self.insert_building(&BuildingFactory::new(typ, pos, village));
-            })
+    ) -> GameMasterResult<()> {
+        self.transaction(|| {
+            self.building_has_space(typ, pos, village)?;
+            self.try_spend(&typ.price(), village)?;
+            self.insert_building(&BuildingFactory::new(typ, pos, village, self.now()));
+            Ok(())
+        })?;
+        notify(Notification::ResourcesChanged(village));
+        Ok(())
     }
     /// Check for events to be executed upon inserting new buildings
     pub fn building_insertion_triggers(
@@ -23,13 +29,25 @@ impl DB {
         typ: BuildingType,
         player: PlayerKey,
         addr: actix_web::web::Data<crate::ActorAddresses>,
-    ) -> StringErr {
+    ) -> GameMasterResult<()> {
         // Improvement: Would be nice if this comes from a central specification
         match typ {
             BuildingType::Temple => {
-                // Inserting a temple means story progress
-                self.update_story_state(player, StoryState::TempleBuilt, addr)
-                    .map_err(|e| format!("Updating story state failed: {}", e))?;
+                // Inserting a temple means story progress, unless the player is already past that point
+                let p = self
+                    .player(player)
+                    .ok_or(GameMasterError::PlayerNotCreated)?;
+                match self
+                    .story_view(&p)
+                    .check_story_transition(StoryState::TempleBuilt)
+                {
+                    Ok(()) => self.update_story_state(player, StoryState::TempleBuilt, addr)?,
+                    Err(e) => println!(
+                        "No story progress for player {} after building a temple: {}",
+                        player.num(),
+                        e
+                    ),
+                }
             }
             _ => {}
         }
@@ -41,7 +59,7 @@ impl DB {
         typ: BuildingType,
         pos: (usize, usize),
         village: VillageKey,
-    ) -> StringErr {
+    ) -> GameMasterResult<()> {
         // Check conflict with existing building
         let (w, h) = typ.size();
         debug_assert_eq!(w, 1, "Not implemented yet");
@@ -55,14 +73,14 @@ impl DB {
             debug_assert_eq!(h, 1, "Not implemented yet");
             let (x, y) = (other.x as usize, other.y as usize);
             if x == x0 && y == y0 {
-                return Err("Space occupied".to_owned());
+                return Err(GameMasterError::InvalidPlacement("Space occupied"));
             }
         }
 
         // Check conflict with map
         // Note: Cleaner handling of map shape might be necessary in the future
         if y0 == 6 {
-            return Err("Cannot build here".to_owned());
+            return Err(GameMasterError::InvalidPlacement("Cannot build here"));
         }
 
         // Check conflict with stationary units
@@ -70,7 +88,7 @@ impl DB {
         let (x0, y0) = (pos.0 as i32, pos.1 as i32);
         for w in workers {
             if w.x == x0 && w.y == y0 {
-                return Err("Unit blocks space".to_owned());
+                return Err(GameMasterError::InvalidPlacement("Unit blocks space"));
             }
         }
         // Check conflict with walking units
@@ -80,7 +98,9 @@ impl DB {
             let mut worker_y = w.y;
             for task in self.worker_tasks(w.key()) {
                 if is_between(x0, worker_x, task.x) || is_between(y0, worker_y, task.y) {
-                    return Err("Walking unit blocks space".to_owned());
+                    return Err(GameMasterError::InvalidPlacement(
+                        "Walking unit blocks space",
+                    ));
                 }
                 worker_x = task.x;
                 worker_y = task.y;
@@ -1,34 +1,33 @@
-use crate::db::{DeferredDbStatement, DB};
-use crate::{ActorAddresses, StringErr};
+use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
+use crate::notifications::notify;
+use paddlers_shared_lib::api::notifications::Notification;
 use paddlers_shared_lib::{api::shop::*, game_mechanics::prophets::*, prelude::*};
 
 impl DB {
-    fn check_prophet_conditions(&self, p: &Player) -> Result<Price, String> {
+    fn check_prophet_conditions(&self, p: &Player) -> GameMasterResult<Price> {
         let karma = p.karma;
         let prophets_alive = self.player_prophets_count(p.uuid);
         let villlages_owned = self.player_village_count(p.key());
 
         let total_prophets = prophets_alive + villlages_owned - 1;
         if prophets_allowed(karma) <= total_prophets {
-            return Err("Not enough Karma".to_owned());
+            return Err(GameMasterError::NotEnoughKarma);
         }
         Ok(prophet_cost(total_prophets))
     }
 
-    pub fn try_buy_prophet(
-        &self,
-        village: VillageKey,
-        addrs: &ActorAddresses,
-        p: &Player,
-    ) -> StringErr {
-        self.check_prophet_conditions(p)
-            .and_then(|cost| self.try_spend(&cost, village))
-            .and_then(|()| {
-                addrs
-                    .db_actor
-                    .try_send(DeferredDbStatement::NewProphet(village))
-                    .map_err(|e| format!("{}", e))
-            })
+    pub fn try_buy_prophet(&self, village: VillageKey, player: PlayerKey) -> GameMasterResult<()> {
+        self.transaction(|| {
+            // Locking the player serializes prophet purchases, otherwise the number of prophets could exceed the limit
+            let p = self.lock_player(player)?;
+            let cost = self.check_prophet_conditions(&p)?;
+            self.try_spend(&cost, village)?;
+            self.add_prophet(village);
+            Ok(())
+        })?;
+        notify(Notification::ResourcesChanged(village));
+        Ok(())
     }
 
     pub fn add_prophet(&self, v: VillageKey) {
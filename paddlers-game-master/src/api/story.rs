@@ -3,48 +3,79 @@
 use crate::authentication::Authentication;
 use crate::db::NewHoboMessage;
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use crate::game_master::attack_funnel::PlannedAttack;
 use actix::prelude::*;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use paddlers_shared_lib::api::story::StoryStateTransition;
 use paddlers_shared_lib::prelude::*;
 use paddlers_shared_lib::story::story_state::StoryState;
+use paddlers_shared_lib::story::story_transitions::{IStoryProgress, StoryTransitionError};
 
 pub(crate) fn story_transition(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<StoryStateTransition>,
     mut auth: Authentication,
     addr: web::Data<crate::ActorAddresses>,
-) -> impl Responder {
+) -> GameMasterResult<HttpResponse> {
     let db: crate::db::DB = pool.get_ref().into();
-    if let Some(player) = auth.player_object(&db) {
-        db.try_execute_story_transition(player, body.0.before, body.0.after, addr)
-            .map_or_else(|e| HttpResponse::from(&e), |_| HttpResponse::Ok().into())
-    } else {
-        HttpResponse::BadRequest().body(format!(
-            "No such player in the database: {}",
-            auth.user.uuid
-        ))
+    let player = auth
+        .player_object(&db)
+        .ok_or(GameMasterError::PlayerNotCreated)?;
+    db.check_story_transition(player, body.0.before, body.0.after)?;
+    db.update_story_state(player.key(), body.0.after, addr)?;
+    Ok(HttpResponse::Ok().into())
+}
+
+/// A player's game state, as required to validate story transitions
+pub(crate) struct PlayerStoryView<'a> {
+    db: &'a DB,
+    player: &'a Player,
+    villages: Vec<VillageKey>,
+}
+
+impl IStoryProgress for PlayerStoryView<'_> {
+    fn story_state(&self) -> StoryState {
+        self.player.story_state
+    }
+    fn karma(&self) -> i64 {
+        self.player.karma
+    }
+    fn has_building(&self, building_type: BuildingType) -> bool {
+        self.villages.iter().any(|v| {
+            self.db
+                .buildings(*v)
+                .iter()
+                .any(|b| b.building_type == building_type)
+        })
+    }
+    fn has_worker_on_task(&self, task_type: TaskType) -> bool {
+        self.villages
+            .iter()
+            .any(|v| !self.db.workers_with_job(*v, &[task_type]).is_empty())
     }
 }
 
 impl DB {
-    fn try_execute_story_transition(
+    pub(crate) fn story_view<'a>(&'a self, player: &'a Player) -> PlayerStoryView<'a> {
+        PlayerStoryView {
+            db: self,
+            player,
+            villages: self
+                .player_villages(player.key())
+                .iter()
+                .map(|v| v.key())
+                .collect(),
+        }
+    }
+    fn check_story_transition(
         &self,
         player: &Player,
         before: StoryState,
         after: StoryState,
-        addr: web::Data<crate::ActorAddresses>,
-    ) -> Result<(), String> {
-        if before != player.story_state {
-            return Err(format!(
-                "Invalid story state: {:?}, database has: {:?}",
-                before, player.story_state
-            ));
-        }
-        // TODO: Check that transition is valid
-
-        self.update_story_state(player.key(), after, addr)
+    ) -> Result<(), StoryTransitionError> {
+        self.story_view(player)
+            .check_story_transition_from(before, after)
     }
 
     pub fn update_story_state(
@@ -52,9 +83,8 @@ impl DB {
         p: PlayerKey,
         new_story_state: StoryState,
         addr: web::Data<crate::ActorAddresses>,
-    ) -> Result<(), String> {
-        self.set_story_state(p, new_story_state)
-            .map_err(|e| e.to_string())?;
+    ) -> GameMasterResult<()> {
+        self.set_story_state(p, new_story_state)?;
         self.perform_story_actions(new_story_state, addr, p);
         Ok(())
     }
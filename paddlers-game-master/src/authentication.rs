@@ -3,6 +3,7 @@ use actix_web::error::{ErrorBadRequest, ErrorUnauthorized};
 use actix_web::{Error, FromRequest, HttpRequest};
 
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use paddlers_shared_lib::prelude::*;
 use paddlers_shared_lib::user_authentication::PadlUser;
 
@@ -36,6 +37,24 @@ impl FromRequest for Authentication {
     }
 }
 
+/// Authentication of a user with the admin role, requests by other users are rejected
+pub struct Admin(pub Authentication);
+
+impl FromRequest for Admin {
+    type Error = Error;
+    type Future = Result<Self, Error>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = Authentication::from_request(req, payload)?;
+        if auth.user.is_admin() {
+            Ok(Admin(auth))
+        } else {
+            Err(GameMasterError::AdminRequired.into())
+        }
+    }
+}
+
 impl Authentication {
     pub(crate) fn player_object(&mut self, db: &DB) -> Option<&Player> {
         if self.cached_player.is_none() {
@@ -43,9 +62,9 @@ impl Authentication {
         }
         self.cached_player.as_ref()
     }
-    pub(crate) fn player_key(&mut self, db: &DB) -> Result<PlayerKey, String> {
+    pub(crate) fn player_key(&mut self, db: &DB) -> GameMasterResult<PlayerKey> {
         self.player_object(&db)
-            .ok_or("No such player".to_owned())
+            .ok_or(GameMasterError::PlayerNotCreated)
             .map(|p| p.key())
     }
 }
@@ -3,8 +3,12 @@ use paddlers_shared_lib::{game_mechanics::attributes::*, models::*, prelude::Vil
 pub struct BuildingFactory;
 
 impl BuildingFactory {
-    pub fn new(typ: BuildingType, pos: (usize, usize), village: VillageKey) -> NewBuilding {
-        let now = chrono::Utc::now().naive_utc();
+    pub fn new(
+        typ: BuildingType,
+        pos: (usize, usize),
+        village: VillageKey,
+        now: chrono::NaiveDateTime,
+    ) -> NewBuilding {
         NewBuilding {
             x: pos.0 as i32,
             y: pos.1 as i32,
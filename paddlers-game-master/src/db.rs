@@ -1,27 +1,55 @@
 use diesel::r2d2::ConnectionManager;
-use diesel::PgConnection;
+use diesel::{Connection, PgConnection};
 use paddlers_shared_lib::prelude::*;
 pub mod diesel_queries;
 pub use diesel_queries::*;
 mod db_actor;
 pub use db_actor::*;
 type Manager = ConnectionManager<PgConnection>;
-pub type Pool = r2d2::Pool<Manager>;
-pub(crate) struct DB(r2d2::PooledConnection<Manager>);
+
+/// Connection pool, bundled with the clock that all DB helpers use
+#[derive(Clone)]
+pub struct Pool {
+    connections: r2d2::Pool<Manager>,
+    clock: Clock,
+}
+pub(crate) struct DB(r2d2::PooledConnection<Manager>, Clock);
 
 impl DB {
-    pub fn new_pool() -> Pool {
+    pub fn new_pool(clock: Clock) -> Pool {
         let url = paddlers_shared_lib::get_db_url();
         let manager = diesel::r2d2::ConnectionManager::<PgConnection>::new(url);
-        r2d2::Pool::builder()
+        let connections = r2d2::Pool::builder()
             .build(manager)
-            .expect("Failed to create pool.")
+            .expect("Failed to create pool.");
+        Pool { connections, clock }
+    }
+    pub fn clock(&self) -> &Clock {
+        &self.1
+    }
+    /// Runs `f` inside a transaction, all its changes are rolled back if it returns an error.
+    /// Nested calls are possible, inner transactions are implemented as savepoints.
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+        E: From<diesel::result::Error>,
+    {
+        self.dbconn().transaction(f)
+    }
+}
+
+impl Pool {
+    pub fn clock(&self) -> &Clock {
+        &self.clock
     }
 }
 
 impl From<&Pool> for DB {
     fn from(pool: &Pool) -> Self {
-        DB(pool.get().expect("Couldn't get DB connection"))
+        DB(
+            pool.connections.get().expect("Couldn't get DB connection"),
+            pool.clock.clone(),
+        )
     }
 }
 
@@ -29,4 +57,7 @@ impl GameDB for DB {
     fn dbconn(&self) -> &PgConnection {
         &self.0
     }
+    fn now(&self) -> chrono::NaiveDateTime {
+        self.1.naive_now()
+    }
 }
@@ -2,7 +2,9 @@ mod messages;
 pub use messages::*;
 
 use crate::db::*;
+use crate::notifications::notify;
 use actix::prelude::*;
+use paddlers_shared_lib::api::notifications::Notification;
 
 /// This actor executes DB requests which can be done concurrent to
 /// the request processing or game-master logic.
@@ -10,29 +12,6 @@ pub struct DbActor {
     dbpool: Pool,
 }
 
-impl Handler<DeferredDbStatement> for DbActor {
-    type Result = ();
-    fn handle(&mut self, msg: DeferredDbStatement, _ctx: &mut SyncContext<Self>) {
-        match msg {
-            DeferredDbStatement::NewProphet(village) => {
-                self.db().add_prophet(village);
-            }
-            DeferredDbStatement::NewAttack(planned_atk) => {
-                let attack = self.db().insert_attack(&planned_atk.attack);
-                for hobo in planned_atk.hobos.iter() {
-                    let atu = AttackToHobo {
-                        attack_id: attack.id,
-                        hobo_id: hobo.num(),
-                        satisfied: None,
-                        released: None,
-                    };
-                    self.db().insert_attack_to_hobo(&atu);
-                }
-            }
-        }
-    }
-}
-
 impl Handler<NewHoboMessage> for DbActor {
     type Result = NewHoboResponse;
     fn handle(&mut self, msg: NewHoboMessage, _ctx: &mut SyncContext<Self>) -> Self::Result {
@@ -62,6 +41,7 @@ impl Handler<CollectReportRewardsMessage> for DbActor {
             }
         }
         db.delete_visit_report(&report);
+        notify(Notification::ResourcesChanged(village));
     }
 }
 
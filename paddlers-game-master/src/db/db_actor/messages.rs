@@ -2,23 +2,6 @@ use actix::dev::{MessageResponse, ResponseChannel};
 use actix::prelude::*;
 use paddlers_shared_lib::prelude::*;
 
-#[derive(Debug)]
-/// Deferred DB requests should not be dependent on the state of the DB
-/// and instead be logically guaranteed to work. For example, the resource
-/// price should already be payed before-hand.
-pub enum DeferredDbStatement {
-    NewProphet(VillageKey),
-    NewAttack(ScheduledAttack),
-}
-impl Message for DeferredDbStatement {
-    type Result = ();
-}
-#[derive(Debug)]
-pub struct ScheduledAttack {
-    pub attack: NewAttack,
-    pub hobos: Vec<HoboKey>,
-}
-
 pub struct NewHoboMessage(pub NewHobo);
 pub struct NewHoboResponse(pub Hobo);
 impl Message for NewHoboMessage {
@@ -10,6 +10,13 @@ impl DB {
             .values(u)
             .get_result(self.dbconn())
     }
+    /// Loads a player and locks its row until the end of the current transaction
+    pub fn lock_player(&self, p: PlayerKey) -> QueryResult<Player> {
+        players::table
+            .find(p.num())
+            .for_update()
+            .get_result(self.dbconn())
+    }
     pub fn set_story_state(&self, p: PlayerKey, story_state: StoryState) -> QueryResult<Player> {
         let target = players::table.find(p.num());
         diesel::update(target)
@@ -27,6 +34,52 @@ impl DB {
         }
     }
 
+    /// Deletes the hobos of an attack which have been satisfied, the others swim back to their home village
+    pub fn return_attack_hobos_home(&self, atk: &Attack, returns_at: chrono::NaiveDateTime) {
+        for (hobo, info) in self.attack_hobos_with_attack_info(atk) {
+            if info.satisfied == Some(true) {
+                let result = diesel::delete(&hobo).execute(self.dbconn());
+                if result.is_err() {
+                    println!("Couldn't delete hobo {:?}", hobo);
+                }
+            } else {
+                // Effects from abilities only last for the visit
+                diesel::delete(effects::table.filter(effects::hobo_id.eq(hobo.id)))
+                    .execute(self.dbconn())
+                    .expect("Deleting effects");
+                diesel::update(&hobo)
+                    .set(hobos::returns_at.eq(Some(returns_at)))
+                    .execute(self.dbconn())
+                    .expect("Updating hobo");
+            }
+        }
+    }
+
+    /// Keeps a summary of the attack, must be called before its hobos are deleted
+    pub fn archive_attack(&self, atk: &Attack) {
+        let units = self.attack_hobos_with_attack_info(atk);
+        let satisfied = units
+            .iter()
+            .filter(|(_, info)| info.satisfied == Some(true))
+            .count();
+        let past = PastAttack {
+            id: atk.id,
+            departure: atk.departure,
+            arrival: atk.arrival,
+            finished: self.now(),
+            origin_village_id: atk.origin_village_id,
+            destination_village_id: atk.destination_village_id,
+            visitors: units.len() as i32,
+            satisfied_visitors: satisfied as i32,
+        };
+        let result = diesel::insert_into(past_attacks::table)
+            .values(&past)
+            .execute(self.dbconn());
+        if result.is_err() {
+            println!("Couldn't archive attack {:?}", atk);
+        }
+    }
+
     pub fn delete_attack(&self, atk: &Attack) {
         let result = diesel::delete(atk).execute(self.dbconn());
         if result.is_err() {
@@ -40,6 +93,12 @@ impl DB {
             .get_result(self.dbconn())
             .expect("Inserting hobo")
     }
+    pub fn update_hobo(&self, u: &Hobo) {
+        diesel::update(u)
+            .set(u)
+            .execute(self.dbconn())
+            .expect("Updating hobo");
+    }
     pub fn insert_worker(&self, u: &NewWorker) -> Worker {
         diesel::insert_into(workers::dsl::workers)
             .values(u)
@@ -59,17 +118,31 @@ impl DB {
             .execute(self.dbconn())
             .expect("Updating worker for mana");
     }
-    pub fn insert_attack(&self, new_attack: &NewAttack) -> Attack {
-        diesel::insert_into(attacks::dsl::attacks)
-            .values(new_attack)
-            .get_result(self.dbconn())
-            .expect("Inserting attack")
-    }
-    pub fn insert_attack_to_hobo(&self, atu: &AttackToHobo) {
-        diesel::insert_into(attacks_to_hobos::dsl::attacks_to_hobos)
-            .values(atu)
-            .execute(self.dbconn())
-            .expect("Inserting attack to hobo");
+    /// Inserts the attack and all its hobos in one transaction.
+    /// A unique constraint on the hobo ID makes this fail if a hobo is already part of another attack.
+    pub fn insert_attack_with_hobos(
+        &self,
+        new_attack: &NewAttack,
+        hobos: &[HoboKey],
+    ) -> QueryResult<Attack> {
+        self.dbconn().transaction(|| {
+            let attack: Attack = diesel::insert_into(attacks::dsl::attacks)
+                .values(new_attack)
+                .get_result(self.dbconn())?;
+            let atus: Vec<AttackToHobo> = hobos
+                .iter()
+                .map(|hobo| AttackToHobo {
+                    attack_id: attack.id,
+                    hobo_id: hobo.num(),
+                    satisfied: None,
+                    released: None,
+                })
+                .collect();
+            diesel::insert_into(attacks_to_hobos::dsl::attacks_to_hobos)
+                .values(&atus)
+                .execute(self.dbconn())?;
+            Ok(attack)
+        })
     }
     pub fn insert_resource(&self, res: &Resource) -> QueryResult<usize> {
         diesel::insert_into(dsl::resources)
@@ -87,6 +160,22 @@ impl DB {
             .set(resources::amount.eq(resources::amount + plus))
             .get_result(self.dbconn())
     }
+    /// Subtracts the amount only if enough resources are available, returns false otherwise.
+    /// The condition is part of the update, hence concurrent updates cannot drive resources below zero.
+    pub fn spend_resource(
+        &self,
+        rt: ResourceType,
+        vk: VillageKey,
+        amount: i64,
+    ) -> QueryResult<bool> {
+        let target = resources::table
+            .find((rt, vk.num()))
+            .filter(resources::amount.ge(amount));
+        diesel::update(target)
+            .set(resources::amount.eq(resources::amount - amount))
+            .execute(self.dbconn())
+            .map(|updated| updated == 1)
+    }
     pub fn add_karma(&self, p: PlayerKey, plus: i64) -> QueryResult<Player> {
         let target = players::table.find(p.num());
         diesel::update(target)
@@ -130,7 +219,7 @@ impl DB {
     }
     pub fn flush_task_queue(&self, worker_id: WorkerKey) {
         diesel::delete(tasks::table.filter(tasks::worker_id.eq(worker_id.num())))
-            .filter(tasks::start_time.gt(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.gt(self.now()))
             .execute(self.dbconn())
             .expect("Deleting task");
     }
@@ -161,7 +250,7 @@ impl DB {
     pub fn update_ability_used_timestamp(&self, worker: WorkerKey, at: AbilityType) {
         let target = abilities::table.find((at, worker.num()));
         diesel::update(target)
-            .set(abilities::last_used.eq(diesel::dsl::now.at_time_zone("UTC").nullable()))
+            .set(abilities::last_used.eq(Some(self.now())))
             .execute(self.dbconn())
             .expect("Updating ability timestamp");
     }
@@ -174,27 +263,21 @@ impl DB {
     pub fn update_worker_flag_timestamp_now(&self, w: WorkerKey, f: WorkerFlagType) {
         let target = worker_flags::table.find((w.num(), f));
         diesel::update(target)
-            .set(worker_flags::last_update.eq(diesel::dsl::now.at_time_zone("UTC")))
+            .set(worker_flags::last_update.eq(self.now()))
             .execute(self.dbconn())
             .expect("Updating flag timestamp to now");
     }
-    pub fn update_worker_flag_timestamp(
-        &self,
-        w: WorkerKey,
-        f: WorkerFlagType,
-        ts: chrono::NaiveDateTime,
-    ) {
-        let target = worker_flags::table.find((w.num(), f));
+    /// Moves the timestamp of a flag forward, unless it has been changed since it was loaded.
+    /// Returns false if someone else updated the flag in the meantime.
+    pub fn advance_worker_flag(&self, flag: &WorkerFlag, ts: chrono::NaiveDateTime) -> bool {
+        let target = worker_flags::table
+            .find((flag.worker_id, flag.flag_type))
+            .filter(worker_flags::last_update.eq(flag.last_update));
         diesel::update(target)
             .set(worker_flags::last_update.eq(ts))
             .execute(self.dbconn())
-            .expect("Updating flag timestamp");
-    }
-    pub fn worker_flags(&self, worker: WorkerKey) -> Vec<WorkerFlag> {
-        worker_flags::table
-            .filter(worker_flags::worker_id.eq(worker.num()))
-            .get_results(self.dbconn())
-            .expect("Error loading data")
+            .expect("Updating flag timestamp")
+            == 1
     }
     pub fn insert_visit_report(&self, vr: NewVisitReport) -> VisitReport {
         diesel::insert_into(visit_reports::dsl::visit_reports)
@@ -224,8 +307,66 @@ impl DB {
     pub fn release_resting_visitor(&self, hid: HoboKey, aid: AttackKey) {
         let target = attacks_to_hobos::table.find((aid.num(), hid.num()));
         diesel::update(target)
-            .set(attacks_to_hobos::released.eq(diesel::dsl::now.at_time_zone("UTC").nullable()))
+            .set(attacks_to_hobos::released.eq(Some(self.now())))
             .execute(self.dbconn())
             .expect("setting released");
     }
+    pub fn insert_scheduled_event(&self, e: &NewScheduledEvent) -> ScheduledEvent {
+        diesel::insert_into(scheduled_events::dsl::scheduled_events)
+            .values(e)
+            .get_result(self.dbconn())
+            .expect("Inserting scheduled event")
+    }
+    pub fn delete_scheduled_event(&self, id: i64) {
+        let result = diesel::delete(scheduled_events::table.find(id)).execute(self.dbconn());
+        if result.is_err() {
+            println!("Couldn't delete scheduled event {}", id);
+        }
+    }
+    /// Loads a world and locks its row until the end of the current transaction
+    pub fn lock_world(&self, w: WorldKey) -> QueryResult<World> {
+        worlds::table
+            .find(w.num())
+            .for_update()
+            .get_result(self.dbconn())
+    }
+    pub fn insert_world(&self, w: &NewWorld) -> QueryResult<World> {
+        diesel::insert_into(worlds::dsl::worlds)
+            .values(w)
+            .get_result(self.dbconn())
+    }
+    pub fn insert_season(&self, s: &NewSeason) -> QueryResult<Season> {
+        diesel::insert_into(seasons::dsl::seasons)
+            .values(s)
+            .get_result(self.dbconn())
+    }
+    pub fn end_season_now(&self, s: SeasonKey) -> QueryResult<Season> {
+        let target = seasons::table.find(s.num());
+        diesel::update(target)
+            .set(seasons::end_time.eq(Some(self.now())))
+            .get_result(self.dbconn())
+    }
+    pub fn insert_season_standings(&self, standings: &[SeasonStanding]) -> QueryResult<usize> {
+        diesel::insert_into(season_standings::dsl::season_standings)
+            .values(standings)
+            .execute(self.dbconn())
+    }
+    /// Deletes all streams and villages of all worlds, including everything that belongs to them.
+    /// Player accounts, worlds and archived seasons remain untouched.
+    pub fn wipe_worlds(&self) -> QueryResult<()> {
+        diesel::sql_query(
+            "TRUNCATE streams, villages, hobos, workers, attacks, scheduled_events CASCADE",
+        )
+        .execute(self.dbconn())
+        .map(|_| ())
+    }
+    /// Sets karma and story progress of all players back to the start
+    pub fn reset_players(&self) -> QueryResult<usize> {
+        diesel::update(players::table)
+            .set((
+                players::karma.eq(0),
+                players::story_state.eq(StoryState::Initialized),
+            ))
+            .execute(self.dbconn())
+    }
 }
@@ -0,0 +1,135 @@
+//! Errors of the game-master that are reported back to the client.
+//!
+//! Each error maps to a numeric code from `paddlers_shared_lib::api::error`,
+//! responses carry it in a JSON body so that the frontend can show its own message.
+
+use actix_web::error::BlockingError;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use paddlers_shared_lib::game_mechanics::town::TownError;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::story_transitions::StoryTransitionError;
+use std::fmt;
+
+pub(crate) type GameMasterResult<T> = Result<T, GameMasterError>;
+
+#[derive(Debug)]
+pub(crate) enum GameMasterError {
+    PlayerNotCreated,
+    /// The named object does not belong to the player that sent the request
+    NotOwned(&'static str),
+    NotEnoughResources(ResourceType),
+    NotEnoughKarma,
+    NotEnoughMana,
+    InvalidPlacement(&'static str),
+    BuildingLocked(BuildingType),
+    AbilityNotReady,
+    PathBlocked,
+    CannotDefendHere,
+    Town(TownError),
+    Story(StoryTransitionError),
+    HoboBusy,
+    TooManyRequests,
+    /// The named object referenced by the request does not exist
+    NotFound(&'static str),
+    InvalidTask(String),
+    InvalidRequest(String),
+    Internal(String),
+    AdminRequired,
+}
+
+impl GameMasterError {
+    pub fn code(&self) -> PadlApiError {
+        match self {
+            Self::PlayerNotCreated => PadlApiError::PlayerNotCreated,
+            Self::NotOwned(_) => PadlApiError::NotOwned,
+            Self::NotEnoughResources(_) => PadlApiError::NotEnoughResources,
+            Self::NotEnoughKarma => PadlApiError::NotEnoughKarma,
+            Self::NotEnoughMana => PadlApiError::NotEnoughMana,
+            Self::InvalidPlacement(_) => PadlApiError::InvalidPlacement,
+            Self::BuildingLocked(_) => PadlApiError::BuildingLocked,
+            Self::AbilityNotReady => PadlApiError::AbilityNotReady,
+            Self::PathBlocked => PadlApiError::PathBlocked,
+            Self::CannotDefendHere => PadlApiError::CannotDefendHere,
+            Self::Town(TownError::BuildingFull) => PadlApiError::BuildingFull,
+            Self::Town(TownError::NotEnoughSupply) => PadlApiError::NotEnoughSupply,
+            Self::Town(TownError::InvalidState(_)) => PadlApiError::InvalidTask,
+            Self::Story(_) => PadlApiError::StoryViolation,
+            Self::HoboBusy => PadlApiError::HoboBusy,
+            Self::TooManyRequests => PadlApiError::TooManyRequests,
+            Self::NotFound(_) => PadlApiError::NotFound,
+            Self::InvalidTask(_) => PadlApiError::InvalidTask,
+            Self::InvalidRequest(_) => PadlApiError::InvalidRequest,
+            Self::Internal(_) => PadlApiError::Internal,
+            Self::AdminRequired => PadlApiError::AdminRequired,
+        }
+    }
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::PlayerNotCreated | Self::NotOwned(_) | Self::AdminRequired => {
+                StatusCode::FORBIDDEN
+            }
+            Self::HoboBusy => StatusCode::CONFLICT,
+            Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl fmt::Display for GameMasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PlayerNotCreated => write!(f, "No such player"),
+            Self::NotOwned(what) => write!(f, "{} not owned by player", what),
+            Self::NotEnoughResources(res) => write!(f, "Not enough {}", res),
+            Self::NotEnoughKarma => write!(f, "Not enough Karma"),
+            Self::NotEnoughMana => write!(f, "Not enough mana"),
+            Self::InvalidPlacement(reason) => write!(f, "{}", reason),
+            Self::BuildingLocked(bt) => write!(f, "Player not allowed to build {}", bt),
+            Self::AbilityNotReady => write!(f, "Cooldown not ready"),
+            Self::PathBlocked => write!(f, "Cannot walk this way"),
+            Self::CannotDefendHere => write!(f, "Cannot defend at this position"),
+            Self::Town(e) => write!(f, "{}", e),
+            Self::Story(e) => write!(f, "{}", e),
+            Self::HoboBusy => write!(f, "A hobo is already part of another attack"),
+            Self::TooManyRequests => write!(f, "Too many requests"),
+            Self::NotFound(what) => write!(f, "{} does not exist", what),
+            Self::AdminRequired => write!(f, "Admin role required"),
+            Self::InvalidTask(msg) | Self::InvalidRequest(msg) | Self::Internal(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+impl std::error::Error for GameMasterError {}
+
+impl ResponseError for GameMasterError {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status()).json(self.code().body(self.to_string()))
+    }
+}
+
+impl From<TownError> for GameMasterError {
+    fn from(e: TownError) -> Self {
+        Self::Town(e)
+    }
+}
+impl From<StoryTransitionError> for GameMasterError {
+    fn from(e: StoryTransitionError) -> Self {
+        Self::Story(e)
+    }
+}
+impl From<diesel::result::Error> for GameMasterError {
+    fn from(e: diesel::result::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+impl From<BlockingError<GameMasterError>> for GameMasterError {
+    fn from(e: BlockingError<GameMasterError>) -> Self {
+        match e {
+            BlockingError::Error(e) => e,
+            BlockingError::Canceled => Self::Internal("Canceled".to_owned()),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Anarchist villages are the homes of the anarchists that visit players.
+//!
+//! Each anarchist village keeps a population of visitors and attacks are sent with those currently at home.
+//! Unsatisfied visitors return to their village after the visit, satisfied visitors do not.
+//! The game-master slowly replenishes the population of each anarchist village.
+
+use crate::db::DB;
+use paddlers_shared_lib::balancing::balancing;
+use paddlers_shared_lib::game_mechanics::map::map_distance;
+use paddlers_shared_lib::prelude::*;
+use rand::Rng;
+
+impl DB {
+    /// The anarchist village closest to the target which has visitors at home, together with these visitors
+    pub(crate) fn nearest_anarchists(&self, target: &Village) -> Option<(Village, Vec<Hobo>)> {
+        let mut villages = self.anarchist_villages(target.world());
+        let distance = |v: &Village| map_distance((v.x, v.y), (target.x, target.y));
+        villages.sort_by(|a, b| {
+            distance(a)
+                .partial_cmp(&distance(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        villages.into_iter().find_map(|v| {
+            let hobos = self.idle_hobos(v.key());
+            if hobos.is_empty() {
+                None
+            } else {
+                Some((v, hobos))
+            }
+        })
+    }
+    /// Adds one visitor to each anarchist village that is below its full population
+    pub(crate) fn replenish_anarchist_villages(&self) {
+        let population = balancing().anarchists.village_population;
+        let mut rng = rand::thread_rng();
+        for village in self.all_anarchist_villages() {
+            if self.village_hobos(village.key()).len() < population {
+                self.insert_hobo(&new_anarchist(village.key(), &mut rng));
+            }
+        }
+    }
+    /// Fills a newly created anarchist village with its full population
+    pub(crate) fn populate_anarchist_village(&self, village: VillageKey) {
+        let population = balancing().anarchists.village_population;
+        let mut rng = rand::thread_rng();
+        for _ in 0..population {
+            self.insert_hobo(&new_anarchist(village, &mut rng));
+        }
+    }
+}
+
+/// Strength and speed are only decided when the anarchist is sent to a player
+fn new_anarchist<R: Rng>(home: VillageKey, rng: &mut R) -> NewHobo {
+    NewHobo {
+        color: Some(gen_color(rng)),
+        hp: 1,
+        speed: 0.0625,
+        home: home.num(),
+        hurried: true,
+    }
+}
+
+fn gen_color<R>(rng: &mut R) -> UnitColor
+where
+    R: Rng,
+{
+    match rng.gen_range(0, 100) {
+        0..85 => UnitColor::Yellow,
+        85..99 => UnitColor::Camo,
+        99 => UnitColor::White,
+        _ => panic!("RNG bug?"),
+    }
+}
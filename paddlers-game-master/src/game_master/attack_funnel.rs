@@ -4,22 +4,27 @@
 //! This module should then guarantee some consistencies about attacks in a village.
 //! For now, the consistency rules are:
 //!     - No two attacks reach a town at the same time
-//!     - No hobo is involved in more than one attack at the time
+//!     - No hobo is involved in more than one attack at the time (also enforced by the DB)
 //!     - The maximum number of resting hobos is not surpassed
 
 use crate::db::*;
+use crate::error::{GameMasterError, GameMasterResult};
+use crate::game_master::attack_spawn::anarchist_attack;
 use crate::game_master::event::Event;
 use crate::game_master::town_defence::AttackingHobo;
 use crate::game_master::town_worker::{TownWorker, TownWorkerEventMsg};
+use crate::notifications::notify;
 use actix::prelude::*;
 use chrono::{offset::TimeZone, NaiveDateTime, Utc};
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
+use paddlers_shared_lib::api::notifications::Notification;
+use paddlers_shared_lib::game_mechanics::hobos::HoboLevel;
 use paddlers_shared_lib::game_mechanics::{map::map_distance, town::defence::IAttackingHobo};
 use paddlers_shared_lib::prelude::*;
 use std::ops::Add;
 
 pub struct AttackFunnel {
     dbpool: Pool,
-    db_actor: Addr<DbActor>,
     town_worker: Addr<TownWorker>,
 }
 
@@ -40,18 +45,43 @@ pub struct PlannedAttack {
     pub destination_village: Village,
     pub hobos: Vec<Hobo>,
 }
+/// Rejected attacks are answered with an error that can be forwarded to the player
 impl Message for PlannedAttack {
-    type Result = ();
+    type Result = GameMasterResult<()>;
+}
+
+/// Attack from the closest anarchist village, with visitors selected by the funnel
+pub struct AnarchistAttack {
+    pub village: VillageKey,
+    pub level: HoboLevel,
+}
+impl Message for AnarchistAttack {
+    type Result = GameMasterResult<()>;
+}
+
+impl Handler<AnarchistAttack> for AttackFunnel {
+    type Result = GameMasterResult<()>;
+
+    fn handle(&mut self, msg: AnarchistAttack, ctx: &mut Context<Self>) -> Self::Result {
+        // Selecting and inserting in the same message guarantees that idle hobos are not picked twice
+        match anarchist_attack(&self.db(), msg.village, msg.level) {
+            Some(planned) => <Self as Handler<PlannedAttack>>::handle(self, planned, ctx),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Handler<PlannedAttack> for AttackFunnel {
-    type Result = ();
+    type Result = GameMasterResult<()>;
 
     fn handle(&mut self, msg: PlannedAttack, _ctx: &mut Context<Self>) -> Self::Result {
         let db = self.db();
         let vid = msg.destination_village.key();
 
-        // TODO (Correctness): Somehow efficiently check that hobos are not attacking already
+        // Cheap check for a helpful error message, the DB constraint checked on insertion is the actual guarantee
+        if msg.hobos.iter().any(|h| db.hobo_is_attacking(h.key())) {
+            return Err(GameMasterError::HoboBusy);
+        }
         let unit_count = msg.hobos.len();
         let unhurried = msg
             .hobos
@@ -59,18 +89,10 @@ impl Handler<PlannedAttack> for AttackFunnel {
             .cloned()
             .filter(|h| !h.hurried)
             .collect::<Vec<_>>();
-        let hobos = msg.hobos.into_iter().map(|h| h.key()).collect();
-
-        let min_secs = 15;
-        let travel_time = if let Some(v0) = msg.origin_village {
-            let v1 = msg.destination_village;
-            let distance = map_distance((v0.x, v0.y), (v1.x, v1.y));
-            let seconds = 20.0 * distance;
-            chrono::Duration::seconds(min_secs + seconds as i64)
-        } else {
-            chrono::Duration::seconds(min_secs)
-        };
-        let now = chrono::Utc::now().naive_utc();
+        let hobos: Vec<HoboKey> = msg.hobos.into_iter().map(|h| h.key()).collect();
+
+        let travel_time = travel_time(msg.origin_village.as_ref(), &msg.destination_village);
+        let now = db.now();
         let earliest_arrival = now.add(travel_time);
         let arrival = Self::next_timeslot(&db, vid, unit_count, earliest_arrival);
         let attack = NewAttack {
@@ -81,11 +103,20 @@ impl Handler<PlannedAttack> for AttackFunnel {
         };
 
         // Put new attack in DB
-        let pa = ScheduledAttack { attack, hobos };
-        self.db_actor
-            .try_send(DeferredDbStatement::NewAttack(pa))
-            .expect("Sending attack failed");
+        let attack = db
+            .insert_attack_with_hobos(&attack, &hobos)
+            .map_err(|e| match e {
+                DatabaseError(DatabaseErrorKind::UniqueViolation, _) => GameMasterError::HoboBusy,
+                e => GameMasterError::Internal(format!("Attack could not be stored: {}", e)),
+            })?;
+        notify(Notification::NewAttack(vid));
 
+        // Evaluate the attack when the first visitor leaves the town again
+        if let Some((event, time)) = Event::load_next_attack_check(&db, attack.key()) {
+            self.town_worker
+                .try_send(TownWorkerEventMsg(event, time))
+                .expect("Sending event failed");
+        }
         // Validate the resting queue the attack arrives, unless there is no unhurried hobo
         if unhurried.len() > 0 {
             let delayed_event = Event::CheckRestingVisitors {
@@ -114,14 +145,27 @@ impl Handler<PlannedAttack> for AttackFunnel {
                 ))
                 .expect("Sending event failed");
         }
+        Ok(())
+    }
+}
+
+/// Time it takes hobos to swim from one village to another, or to appear in a village out of nowhere
+pub(crate) fn travel_time(origin: Option<&Village>, destination: &Village) -> chrono::Duration {
+    let min_secs = 15;
+    if let Some(v0) = origin {
+        let v1 = destination;
+        let distance = map_distance((v0.x, v0.y), (v1.x, v1.y));
+        let seconds = 20.0 * distance;
+        chrono::Duration::seconds(min_secs + seconds as i64)
+    } else {
+        chrono::Duration::seconds(min_secs)
     }
 }
 
 impl AttackFunnel {
-    pub fn new(dbpool: Pool, db_actor: Addr<DbActor>, town_worker: Addr<TownWorker>) -> Self {
+    pub fn new(dbpool: Pool, town_worker: Addr<TownWorker>) -> Self {
         AttackFunnel {
             dbpool,
-            db_actor,
             town_worker,
         }
     }
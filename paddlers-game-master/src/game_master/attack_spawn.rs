@@ -1,16 +1,13 @@
 //! Spawns random attacks on villages
 
 use crate::db::*;
-use crate::game_master::attack_funnel::{AttackFunnel, PlannedAttack};
+use crate::game_master::attack_funnel::{AnarchistAttack, AttackFunnel, PlannedAttack};
 use actix::prelude::*;
-use futures::future::join_all;
 use paddlers_shared_lib::game_mechanics::hobos::HoboLevel;
 use paddlers_shared_lib::prelude::*;
 use rand::Rng;
 
 pub struct AttackSpawner {
-    dbpool: Pool,
-    db_actor: Addr<DbActor>,
     attack_funnel_actor: Addr<AttackFunnel>,
 }
 
@@ -26,8 +23,8 @@ impl Actor for AttackSpawner {
     }
 }
 
-/// Attack from no specific origin
-pub(super) struct SendAnarchistAttack {
+/// Attack from the closest anarchist village
+pub(crate) struct SendAnarchistAttack {
     pub village: VillageKey,
     pub level: HoboLevel,
 }
@@ -38,86 +35,75 @@ impl Handler<SendAnarchistAttack> for AttackSpawner {
     type Result = ();
 
     fn handle(&mut self, msg: SendAnarchistAttack, _ctx: &mut Context<Self>) -> Self::Result {
-        self.spawn_anarchists(msg.village, msg.level);
+        let village = msg.village;
+        // Hobos are selected by the funnel, so that no other attack can reserve them in between
+        let future = self
+            .attack_funnel_actor
+            .send(AnarchistAttack {
+                village,
+                level: msg.level,
+            })
+            .map(move |result| {
+                if let Err(e) = result {
+                    eprintln!(
+                        "Anarchist attack on village {} failed: {}",
+                        village.num(),
+                        e
+                    );
+                }
+            })
+            .map_err(|e| eprintln!("Anarchist attack spawn failed: {:?}", e));
+        Arbiter::spawn(future);
     }
 }
 
 impl AttackSpawner {
-    pub fn new(
-        dbpool: Pool,
-        db_actor: Addr<DbActor>,
-        attack_funnel_actor: Addr<AttackFunnel>,
-    ) -> Self {
+    pub fn new(attack_funnel_actor: Addr<AttackFunnel>) -> Self {
         AttackSpawner {
-            dbpool,
-            db_actor,
             attack_funnel_actor,
         }
     }
-    fn db(&self) -> DB {
-        (&self.dbpool).into()
-    }
-
-    fn spawn_anarchists(&self, village: VillageKey, level: HoboLevel) {
-        // Send a random number of weak and hurried hobos + 1 stronger which is nor hurried
-        let mut rng = rand::thread_rng();
-        let n = rng.gen_range(2, 4);
-
-        // weak hobos
-        let (min_hp, max_hp) = level.hurried_anarchist_hp_range();
-        let mut futures: Vec<Request<DbActor, NewHoboMessage>> = (0..n)
-            .map(|_| {
-                let hobo = NewHobo {
-                    color: Some(Self::gen_color(&mut rng)),
-                    hp: rng.gen_range(min_hp, max_hp),
-                    speed: 0.0625,
-                    home: village.num(), // TODO: anarchists home
-                    hurried: true,
-                };
-                let msg = NewHoboMessage(hobo);
-                self.db_actor.send(msg)
-            })
-            .collect();
-        // strong unhurried hobo
-        futures.push({
-            let hobo = NewHobo {
-                color: Some(Self::gen_color(&mut rng)),
-                hp: level.unhurried_anarchist_hp(),
-                speed: 0.25,
-                home: village.num(), // TODO: anarchists home
-                hurried: false,
-            };
-            let msg = NewHoboMessage(hobo);
-            self.db_actor.send(msg)
-        });
-
-        let attack_funnel = self.attack_funnel_actor.clone();
-        let db = self.db();
-        Arbiter::spawn(
-            join_all(futures)
-                .map_err(|e| eprintln!("Attack spawn failed: {:?}", e))
-                .map(move |hobos| {
-                    let hobos = hobos.into_iter().map(|h| h.0).collect();
+}
 
-                    let pa = PlannedAttack {
-                        origin_village: None,
-                        destination_village: db.village(village).unwrap(),
-                        hobos: hobos,
-                    };
-                    attack_funnel.try_send(pa).expect("Spawning attack failed");
-                }),
-        );
-    }
+/// Picks anarchists that are at home and prepares them for a visit of the given village.
+/// Must only be called by the attack funnel, which reserves the hobos right after.
+pub(crate) fn anarchist_attack(
+    db: &DB,
+    village: VillageKey,
+    level: HoboLevel,
+) -> Option<PlannedAttack> {
+    let destination_village = db.village(village)?;
+    let (origin_village, idle_hobos) = match db.nearest_anarchists(&destination_village) {
+        Some(anarchists) => anarchists,
+        None => {
+            println!("No anarchists at home to visit village {}", village.num());
+            return None;
+        }
+    };
 
-    fn gen_color<R>(rng: &mut R) -> UnitColor
-    where
-        R: Rng,
-    {
-        match rng.gen_range(0, 100) {
-            0..85 => UnitColor::Yellow,
-            85..99 => UnitColor::Camo,
-            99 => UnitColor::White,
-            _ => panic!("RNG bug?"),
+    // Send a random number of weak and hurried hobos + 1 stronger which is nor hurried,
+    // as far as the anarchist village has enough hobos at home
+    let mut rng = rand::thread_rng();
+    let n = rng.gen_range(2, 4);
+    let mut hobos: Vec<Hobo> = idle_hobos.into_iter().take(n + 1).collect();
+    let (min_hp, max_hp) = level.hurried_anarchist_hp_range();
+    let strong = hobos.len() - 1;
+    for (i, hobo) in hobos.iter_mut().enumerate() {
+        if i == strong {
+            hobo.hp = level.unhurried_anarchist_hp();
+            hobo.speed = 0.25;
+            hobo.hurried = false;
+        } else {
+            hobo.hp = rng.gen_range(min_hp, max_hp);
+            hobo.speed = 0.0625;
+            hobo.hurried = true;
         }
+        db.update_hobo(hobo);
     }
+
+    Some(PlannedAttack {
+        origin_village: Some(origin_village),
+        destination_village,
+        hobos,
+    })
 }
@@ -1,9 +1,14 @@
 use crate::db::*;
+use crate::notifications::notify;
 use crate::worker_actions::finish_task;
 use chrono::prelude::*;
+use paddlers_shared_lib::api::notifications::Notification;
 use paddlers_shared_lib::game_mechanics::town::MAX_VISITOR_QUEUE;
 use paddlers_shared_lib::prelude::*;
 
+/// Attack checks are scheduled slightly after the computed departure, to be robust against rounding
+const ATTACK_CHECK_MARGIN_SECONDS: i64 = 1;
+
 #[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
 /// For actions that must be performed at a later point in time.
 /// These events can be queued up in the `EventQueue`
@@ -11,38 +16,106 @@ pub enum Event {
     WorkerTask { task_id: TaskKey },
     CheckRestingVisitors { village_id: VillageKey },
     CheckVisitorHp { hobo_id: HoboKey },
+    CheckAttack { attack_id: AttackKey },
 }
 
 impl Event {
-    pub(super) fn run(&self, db: &DB) -> Option<(Event, DateTime<Utc>)> {
+    /// Executes the event and returns follow-up events that should be scheduled
+    pub(super) fn run(&self, db: &DB) -> Vec<(Event, DateTime<Utc>)> {
         match self {
             Self::WorkerTask { task_id } => {
                 let res = finish_task(db, *task_id, None, None);
                 if let Err(e) = res {
                     println!("Task execution failed: {}", e);
-                    None
+                    vec![]
                 } else {
-                    res.unwrap()
+                    res.unwrap().into_iter().collect()
                 }
             }
             Self::CheckRestingVisitors { village_id } => {
                 // Release all visitors that are queued beyond the limit
                 let visitors = db.resting_visitors(*village_id);
+                let mut released_attacks = vec![];
                 if visitors.len() > MAX_VISITOR_QUEUE {
                     for (hobo, attack_id) in &visitors[0..visitors.len() - MAX_VISITOR_QUEUE] {
-                        db.release_resting_visitor(hobo.key(), *attack_id)
+                        db.release_resting_visitor(hobo.key(), *attack_id);
+                        released_attacks.push(*attack_id);
                     }
+                    notify(Notification::VisitorReleased(*village_id));
                 }
-                None
+                // Released visitors leave the town, the attacks need to be checked again when they are gone
+                released_attacks.sort();
+                released_attacks.dedup();
+                released_attacks
+                    .into_iter()
+                    .filter_map(|attack_id| Self::load_next_attack_check(db, attack_id))
+                    .collect()
             }
             Self::CheckVisitorHp { hobo_id } => {
-                let now = chrono::Utc::now().naive_utc();
+                let now = db.now();
+                let mut next = vec![];
                 for (atk, _info) in db.hobo_attack_info(*hobo_id) {
                     // Performance: Checking the entire attack is a bit of an overkill (was the easiest to implement without code duplication)
                     db.maybe_evaluate_attack(&atk, now);
+                    // A satisfied visitor may have been released from the resting place
+                    next.extend(Self::load_next_attack_check(db, atk.key()));
+                }
+                next
+            }
+            Self::CheckAttack { attack_id } => {
+                if let Some(atk) = db.attack(*attack_id) {
+                    db.maybe_evaluate_attack(&atk, db.now());
                 }
-                None
+                Self::load_next_attack_check(db, *attack_id)
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+    /// Representation of the event for storage in the database
+    pub(super) fn to_db(&self, due: DateTime<Utc>) -> NewScheduledEvent {
+        let mut db_event = NewScheduledEvent {
+            event_type: self.event_type(),
+            due: due.naive_utc(),
+            task_id: None,
+            village_id: None,
+            hobo_id: None,
+            attack_id: None,
+        };
+        match self {
+            Self::WorkerTask { task_id } => db_event.task_id = Some(task_id.num()),
+            Self::CheckRestingVisitors { village_id } => {
+                db_event.village_id = Some(village_id.num())
             }
+            Self::CheckVisitorHp { hobo_id } => db_event.hobo_id = Some(hobo_id.num()),
+            Self::CheckAttack { attack_id } => db_event.attack_id = Some(attack_id.num()),
+        }
+        db_event
+    }
+    /// Restores an event from the database, returns None if the row is inconsistent
+    pub(super) fn from_db(db_event: &ScheduledEvent) -> Option<(Self, DateTime<Utc>)> {
+        let event = match db_event.event_type {
+            ScheduledEventType::WorkerTask => Self::WorkerTask {
+                task_id: TaskKey(db_event.task_id?),
+            },
+            ScheduledEventType::CheckRestingVisitors => Self::CheckRestingVisitors {
+                village_id: VillageKey(db_event.village_id?),
+            },
+            ScheduledEventType::CheckVisitorHp => Self::CheckVisitorHp {
+                hobo_id: HoboKey(db_event.hobo_id?),
+            },
+            ScheduledEventType::CheckAttack => Self::CheckAttack {
+                attack_id: AttackKey(db_event.attack_id?),
+            },
+        };
+        Some((event, Utc.from_utc_datetime(&db_event.due)))
+    }
+    fn event_type(&self) -> ScheduledEventType {
+        match self {
+            Self::WorkerTask { .. } => ScheduledEventType::WorkerTask,
+            Self::CheckRestingVisitors { .. } => ScheduledEventType::CheckRestingVisitors,
+            Self::CheckVisitorHp { .. } => ScheduledEventType::CheckVisitorHp,
+            Self::CheckAttack { .. } => ScheduledEventType::CheckAttack,
         }
     }
     pub(crate) fn load_next_worker_task(
@@ -60,4 +133,19 @@ impl Event {
             )
         })
     }
+    /// Schedules the next evaluation of an attack for when its next visitor leaves the town.
+    /// Returns None if the attack is over or if all remaining visitors are resting.
+    pub(crate) fn load_next_attack_check(
+        db: &DB,
+        attack_id: AttackKey,
+    ) -> Option<(Self, DateTime<Utc>)> {
+        let atk = db.attack(attack_id)?;
+        let margin = chrono::Duration::seconds(ATTACK_CHECK_MARGIN_SECONDS);
+        let departure = db.next_departure(&atk)? + margin;
+        let due = departure.max(db.now() + margin);
+        Some((
+            Event::CheckAttack { attack_id },
+            Utc.from_utc_datetime(&due),
+        ))
+    }
 }
@@ -5,6 +5,9 @@ use std::collections::BinaryHeap;
 
 /// For delayed execution of events.
 /// This queue is being poled constantly by the `TownWorker`
+///
+/// Each event is mirrored in the database, the queue only keeps
+/// track of the row id. Inserting and deleting rows is up to the owner of the queue.
 pub struct EventQueue {
     queue: BinaryHeap<TimedEvent>,
 }
@@ -13,6 +16,7 @@ pub struct EventQueue {
 struct TimedEvent {
     time: DateTime<Utc>,
     event: Event,
+    db_id: i64,
 }
 
 impl EventQueue {
@@ -21,18 +25,19 @@ impl EventQueue {
             queue: BinaryHeap::new(),
         }
     }
-    pub fn add_event(&mut self, event: Event, time: DateTime<Utc>) {
+    pub fn add_event(&mut self, event: Event, time: DateTime<Utc>, db_id: i64) {
         self.queue.push(TimedEvent {
             time: time,
             event: event,
+            db_id: db_id,
         });
     }
-    /// Returns the next event in the queue if it is due
-    pub fn poll_event(&mut self) -> Option<Event> {
+    /// Returns the next event in the queue if it is due at the given time, together with its database id
+    pub fn poll_event(&mut self, now: DateTime<Utc>) -> Option<(Event, i64)> {
         let next = self.queue.peek();
         if let Some(evt) = next {
-            if evt.time <= chrono::Utc::now() {
-                return self.queue.pop().map(|te| te.event);
+            if evt.time <= now {
+                return self.queue.pop().map(|te| (te.event, te.db_id));
             }
         }
         None
@@ -41,6 +46,13 @@ impl EventQueue {
     pub fn time_of_next_event(&self) -> Option<&DateTime<Utc>> {
         self.queue.peek().map(|te| &te.time)
     }
+    /// All queued events in order of execution
+    pub fn events(&self) -> Vec<(Event, DateTime<Utc>)> {
+        // The heap is ordered in reverse
+        let mut events = self.queue.clone().into_sorted_vec();
+        events.reverse();
+        events.into_iter().map(|te| (te.event, te.time)).collect()
+    }
 }
 
 // Necessary traits for Binary Heap
@@ -48,11 +60,12 @@ impl EventQueue {
 impl Ord for TimedEvent {
     fn cmp(&self, other: &TimedEvent) -> Ordering {
         // Flipped order to make it a Min-Heap
-        // With event as tie-break
+        // With event and then insertion order as tie-break
         other
             .time
             .cmp(&self.time)
             .then_with(|| self.event.cmp(&other.event))
+            .then_with(|| other.db_id.cmp(&self.db_id))
     }
 }
 impl PartialOrd for TimedEvent {
@@ -1,6 +1,6 @@
+mod anarchist_villages;
 pub(super) mod attack_funnel;
 pub(super) mod attack_spawn;
-pub(super) mod economy_worker;
 pub(super) mod event;
 mod event_queue;
 mod town_defence;
@@ -8,29 +8,42 @@ pub(super) mod town_worker;
 
 use crate::db::*;
 use crate::game_master::attack_spawn::{AttackSpawner, SendAnarchistAttack};
+use crate::game_master::event::Event;
+use crate::game_master::town_worker::{TownWorker, TownWorkerEventMsg};
 use actix::prelude::*;
-use chrono::NaiveDateTime;
-use paddlers_shared_lib::game_mechanics::hobos::HoboLevel;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use paddlers_shared_lib::balancing::balancing;
+use paddlers_shared_lib::game_mechanics::hobos::{repetitive_attack_strength, should_send_attack};
 use paddlers_shared_lib::game_mechanics::town::TOWN_X;
-use paddlers_shared_lib::prelude::Player;
-use paddlers_shared_lib::prelude::VillageKey;
 use paddlers_shared_lib::sql::GameDB;
 use paddlers_shared_lib::sql_db::keys::SqlKey;
-use paddlers_shared_lib::story::story_state::StoryState;
 use rand::RngCore;
 use std::time::Duration;
 
+/// Attacks are evaluated by scheduled events, this is only a fallback for events that got lost
+const ATTACK_CHECK_FALLBACK_SECONDS: i64 = 60;
+
 pub struct GameMaster {
     last_attack: NaiveDateTime,
+    last_attack_check: NaiveDateTime,
+    last_replenish: NaiveDateTime,
     dbpool: Pool,
     attacker_addr: Addr<AttackSpawner>,
+    town_worker: Addr<TownWorker>,
 }
 impl GameMaster {
-    pub fn new(dbpool: Pool, attacker_addr: &Addr<AttackSpawner>) -> Self {
+    pub fn new(
+        dbpool: Pool,
+        attacker_addr: &Addr<AttackSpawner>,
+        town_worker: &Addr<TownWorker>,
+    ) -> Self {
         GameMaster {
             last_attack: NaiveDateTime::from_timestamp(0, 0),
+            last_attack_check: NaiveDateTime::from_timestamp(0, 0),
+            last_replenish: NaiveDateTime::from_timestamp(0, 0),
             dbpool: dbpool,
             attacker_addr: attacker_addr.clone(),
+            town_worker: town_worker.clone(),
         }
     }
 }
@@ -53,9 +66,20 @@ impl GameMaster {
         // println!("Cycle");
 
         let db: DB = (&self.dbpool).into();
-        check_attacks(&db);
+        let now = db.now();
+
+        if now - self.last_attack_check >= chrono::Duration::seconds(ATTACK_CHECK_FALLBACK_SECONDS)
+        {
+            self.last_attack_check = now;
+            check_attacks(&db, &self.town_worker);
+        }
+
+        let replenish_interval = balancing().anarchists.replenish_interval_seconds;
+        if now - self.last_replenish >= chrono::Duration::seconds(replenish_interval) {
+            self.last_replenish = now;
+            db.replenish_anarchist_villages();
+        }
 
-        let now = chrono::Utc::now().naive_utc();
         if now - self.last_attack >= chrono::Duration::seconds(40) {
             self.last_attack = now;
             let mut rng = rand::thread_rng();
@@ -64,15 +88,17 @@ impl GameMaster {
                 let vid = village.key();
                 let ongoing_attacks = db.attacks_count(vid, None);
 
-                if should_send_attack(ongoing_attacks, random_number, vid) {
+                if should_send_attack(ongoing_attacks, random_number, vid.num()) {
                     if let Some(player_info) = db.player_by_village(vid) {
-                        if let Some(anarchist_level) = repetitive_attack_strength(&player_info) {
-                            self.attacker_addr
-                                .try_send(SendAnarchistAttack {
-                                    village: vid,
-                                    level: anarchist_level,
-                                })
-                                .expect("send failed");
+                        if let Some(anarchist_level) =
+                            repetitive_attack_strength(player_info.story_state, player_info.karma)
+                        {
+                            if let Err(e) = self.attacker_addr.try_send(SendAnarchistAttack {
+                                village: vid,
+                                level: anarchist_level,
+                            }) {
+                                eprintln!("Attack on village {} not sent: {}", vid.num(), e);
+                            }
                         }
                     }
                 }
@@ -83,36 +109,23 @@ impl GameMaster {
     }
 }
 
-// TODO [0.1.5]: Define this in specification document and/or integrate with wiki
-fn should_send_attack(ongoing_attacks: usize, random_number: usize, vid: VillageKey) -> bool {
-    match ongoing_attacks {
-        0 => true,
-        n => (random_number + vid.0 as usize) % (n * n + 9) == 0,
-    }
-}
-// TODO [0.1.5]: Define this in specification document and/or integrate with wiki
-fn repetitive_attack_strength(player: &Player) -> Option<HoboLevel> {
-    match player.story_state {
-        StoryState::Initialized
-        | StoryState::ServantAccepted
-        | StoryState::TempleBuilt
-        | StoryState::VisitorArrived => None,
-        StoryState::FirstVisitorWelcomed | StoryState::FlowerPlanted => Some(HoboLevel::zero()),
-        StoryState::MoreHappyVisitors
-        | StoryState::TreePlanted
-        | StoryState::StickGatheringStationBuild
-        | StoryState::GatheringSticks => Some(HoboLevel::anarchist(player.karma)),
-    }
-}
-
-// TODO: Efficiently check only required attacks
-fn check_attacks(db: &DB) {
+/// Safety net that evaluates all attacks which should be over by now.
+/// Usually, attacks are finished by `Event::CheckAttack` already.
+/// The checks are run by the town worker, which evaluates all attacks one after another.
+fn check_attacks(db: &DB, town_worker: &Addr<TownWorker>) {
     for village in db.all_player_villages() {
         let attacks = db.attacks(village.key(), None);
-        let now = chrono::Utc::now().naive_utc();
+        let now = db.now();
         for atk in attacks.iter() {
             if atk.arrival + chrono::Duration::seconds(2 * TOWN_X as i64) < now {
-                db.maybe_evaluate_attack(atk, now);
+                let event = Event::CheckAttack {
+                    attack_id: atk.key(),
+                };
+                if let Err(e) =
+                    town_worker.try_send(TownWorkerEventMsg(event, Utc.from_utc_datetime(&now)))
+                {
+                    eprintln!("Attack check not sent: {}", e);
+                }
             }
         }
     }
@@ -2,6 +2,7 @@
 //!
 //! A fight report is generated as soon as all visitors have left or have been satisfied.
 //! Usually, the satisfaction of each visitor is only computed when time is up for an attack to be finished.
+//! For that, a `CheckAttack` event is scheduled for the moment the next visitor leaves the town.
 //! But there are two exceptions.
 //!     1) When a player has an open browser window, the frontend can detect that a visitor is satisfied and then notify the server
 //!     2) Units that wait in the town need to be checked regularly
@@ -14,9 +15,15 @@
 //! This can be marked in the db using the status on each HoboToAttack.
 
 use crate::db::DB;
+use crate::game_master::attack_funnel::travel_time;
+use crate::notifications::notify;
 use crate::town_view::TownView;
 use chrono::NaiveDateTime;
+use paddlers_shared_lib::api::notifications::Notification;
+use paddlers_shared_lib::game_mechanics::hobos::{reward_feathers, reward_logs, reward_sticks};
+use paddlers_shared_lib::game_mechanics::town::defence::{aura_active, Shooter};
 use paddlers_shared_lib::game_mechanics::town::*;
+use paddlers_shared_lib::game_mechanics::worker::DEFENDER_RANGE;
 use paddlers_shared_lib::prelude::*;
 
 pub(crate) struct AttackingHobo<'a> {
@@ -29,38 +36,115 @@ pub(crate) struct AttackingHobo<'a> {
 impl DB {
     /// Checks if all visitors have already left (or been satisfied).
     /// If so, the visit is evaluated and a report with rewards is generated.
-    pub fn maybe_evaluate_attack(&self, atk: &Attack, now: NaiveDateTime) {
+    ///
+    /// Returns the state of each visitor after the check.
+    /// Some(true) if satisfied, Some(false) if it left unsatisfied, None if it is still in town.
+    pub fn maybe_evaluate_attack(
+        &self,
+        atk: &Attack,
+        now: NaiveDateTime,
+    ) -> Vec<(HoboKey, Option<bool>)> {
         let now: Timestamp = now.into();
         let village = atk.destination();
-        let active_units = self.attack_hobos_active_with_attack_info(atk);
+        // Buildings share their shots among all visitors in town, hence all attacks on the village are considered.
+        // Satisfied visitors are required, too, to distribute shots exactly like the frontend does.
+        let other_attacks: Vec<Attack> = self
+            .attacks(village, None)
+            .into_iter()
+            .filter(|other| other.id != atk.id)
+            .collect();
+        let mut attack_keys: Vec<AttackKey> = other_attacks.iter().map(|a| a.key()).collect();
+        attack_keys.push(atk.key());
+        let all_units = self.attacks_hobos_with_attack_info(&attack_keys);
         let town = TownView::load_village(&self, village);
 
-        for (hobo, info) in &active_units {
-            let effects = self.effects_on_hobo(hobo.key());
-            let unit = AttackingHobo {
-                hobo: hobo,
-                attack_to_hobo: info,
-                effects: &effects,
-                attack: atk,
-            };
-            if town.hp_left(&unit, now) == 0 {
+        let effects: Vec<Vec<Effect>> = all_units
+            .iter()
+            .map(|(hobo, _)| self.effects_on_hobo(hobo.key()))
+            .collect();
+        let units: Vec<AttackingHobo> = all_units
+            .iter()
+            .zip(&effects)
+            .filter_map(|((hobo, info), effects)| {
+                let attack = if info.attack_id == atk.id {
+                    atk
+                } else {
+                    other_attacks.iter().find(|a| a.id == info.attack_id)?
+                };
+                Some(AttackingHobo {
+                    hobo,
+                    attack_to_hobo: info,
+                    effects,
+                    attack,
+                })
+            })
+            .collect();
+        let hp_left = town.hp_left_in_group(&units, now);
+
+        let mut outcome = vec![];
+        for (unit, hp) in units.iter().zip(hp_left) {
+            if unit.attack.id != atk.id {
+                continue;
+            }
+            let (hobo, info) = (unit.hobo, unit.attack_to_hobo);
+            if info.satisfied.is_some() {
+                outcome.push((hobo.key(), info.satisfied));
+                continue;
+            }
+            if hp == 0 {
                 self.set_satisfied(hobo.key(), atk.key(), true);
                 if !hobo.hurried && info.released.is_none() {
                     self.release_resting_visitor(hobo.key(), atk.key());
+                    notify(Notification::VisitorReleased(village));
                 }
-            } else if town.hobo_left_town(&unit, now) {
+                outcome.push((hobo.key(), Some(true)));
+            } else if town.hobo_left_town(unit, now) {
                 self.set_satisfied(hobo.key(), atk.key(), false);
+                outcome.push((hobo.key(), Some(false)));
+            } else {
+                outcome.push((hobo.key(), None));
             }
         }
 
         // Check if all are satisfied or have left otherwise, then finish visit
         if self.attack_done(atk) {
             self.generate_report(atk);
-            if atk.origin_village_id.is_none() {
-                self.delete_attack_hobos(atk.key());
+            self.archive_attack(atk);
+            match atk.origin().and_then(|v| self.village(v)) {
+                // Visitors without origin only exist for this attack
+                None => self.delete_attack_hobos(atk.key()),
+                // Satisfied anarchists stay away, the others swim back to their village
+                Some(origin) if origin.owner().is_none() => {
+                    let destination = self.village(village).expect("Attacked village exists");
+                    let returns_at = self.now() + travel_time(Some(&destination), &origin);
+                    self.return_attack_hobos_home(atk, returns_at);
+                }
+                Some(_) => {}
             }
             self.delete_attack(atk);
         }
+        outcome
+    }
+
+    /// The earliest point in time at which one of the visitors still in town leaves it.
+    /// Returns None if there is no such visitor or if all of them are resting without having been released.
+    pub fn next_departure(&self, atk: &Attack) -> Option<NaiveDateTime> {
+        let active_units = self.attack_hobos_active_with_attack_info(atk);
+        let town = TownView::load_village(&self, atk.destination());
+        active_units
+            .iter()
+            .filter_map(|(hobo, info)| {
+                let effects = self.effects_on_hobo(hobo.key());
+                let unit = AttackingHobo {
+                    hobo,
+                    attack_to_hobo: info,
+                    effects: &effects,
+                    attack: atk,
+                };
+                town.departure_time(&unit)
+            })
+            .min_by_key(Timestamp::micros)
+            .map(NaiveDateTime::from)
     }
 
     fn generate_report(&self, atk: &Attack) {
@@ -73,9 +157,18 @@ impl DB {
         report.karma = happy_hobos.len() as i64;
 
         use std::ops::Add;
-        let feathers = happy_hobos.iter().map(reward_feathers).fold(0, i64::add);
-        let sticks = happy_hobos.iter().map(reward_sticks).fold(0, i64::add);
-        let logs = happy_hobos.iter().map(reward_logs).fold(0, i64::add);
+        let feathers = happy_hobos
+            .iter()
+            .map(|h| reward_feathers(h.hp, h.speed, h.hurried))
+            .fold(0, i64::add);
+        let sticks = happy_hobos
+            .iter()
+            .map(|h| reward_sticks(h.id))
+            .fold(0, i64::add);
+        let logs = happy_hobos
+            .iter()
+            .map(|h| reward_logs(h.id))
+            .fold(0, i64::add);
 
         if report.karma + feathers + sticks + logs == 0 {
             return;
@@ -106,39 +199,14 @@ impl DB {
             });
         }
         self.insert_visit_report_rewards(rewards);
-    }
-}
-
-/// TODO [0.1.5]
-fn reward_feathers(unit: &Hobo) -> i64 {
-    let f = if unit.hurried {
-        (1.0 + unit.hp as f32 * unit.speed / 4.0).log2().floor()
-    } else {
-        (1.0 + unit.hp as f32 / 16.0).log2().ceil()
-    };
-    // println!("Unit {:?} gives {} feathers", unit, f);
-    f as i64
-}
-
-/// TODO [0.1.5]
-fn reward_sticks(unit: &Hobo) -> i64 {
-    if unit.id % 20 == 0 {
-        5
-    } else {
-        0
-    }
-}
-
-/// TODO [0.1.5]
-fn reward_logs(unit: &Hobo) -> i64 {
-    if unit.id % 60 == 11 {
-        5
-    } else {
-        0
+        notify(Notification::ReportCreated(atk.destination()));
     }
 }
 
 impl<'a> IAttackingHobo for AttackingHobo<'a> {
+    fn id(&self) -> i64 {
+        self.hobo.id
+    }
     fn max_hp(&self) -> u32 {
         self.hobo.hp as u32
     }
@@ -161,13 +229,27 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
             .filter(|e| e.strength.is_some())
             .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
     }
+    fn speed_effects(&self) -> Vec<(Timestamp, i32)> {
+        self.effects
+            .iter()
+            .filter(|e| e.attribute == HoboAttributeType::Speed)
+            .filter_map(|e| e.strength.map(|s| (e.start_time.into(), s)))
+            .collect()
+    }
 }
 
 impl ITownLayoutMarker for TownView {
     const LAYOUT: TownLayout = TownLayout::Basic;
 }
+/// Auras of buildings and of defending workers must be distinguishable, their IDs come from different tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AuraSource {
+    Building(i64),
+    Defender(i64),
+}
+
 impl IDefendingTown for TownView {
-    type AuraId = i64;
+    type AuraId = AuraSource;
     fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
         let mut auras = vec![];
         for b in &self.buildings_with_aura {
@@ -176,15 +258,50 @@ impl IDefendingTown for TownView {
             }
             if b.attacks_per_cycle.is_none() {
                 if let (Some(range), Some(ap)) = (b.building_range, b.attack_power) {
-                    let dx = (b.x - index.0 as i32).abs();
-                    let dy = (b.y - index.1 as i32).abs();
-                    let in_range = (dx * dx + dy * dy) as f32 <= range * range;
-                    if in_range {
-                        auras.push((b.id, ap));
+                    if in_range((b.x, b.y), index, range) {
+                        auras.push((AuraSource::Building(b.id), ap));
                     }
                 }
             }
         }
+        for d in &self.defenders {
+            if !aura_active(d.since.into(), d.until.map(Timestamp::from), time) {
+                continue;
+            }
+            if in_range((d.x, d.y), index, DEFENDER_RANGE) {
+                auras.push((AuraSource::Defender(d.worker_id), d.strength));
+            }
+        }
         auras
     }
+    fn shooters_in_range(
+        &self,
+        index: &Self::Index,
+        time: Timestamp,
+    ) -> Vec<Shooter<Self::AuraId>> {
+        let mut shooters = vec![];
+        for b in &self.shooting_buildings {
+            if time < b.creation.into() {
+                continue;
+            }
+            if let (Some(range), Some(ap), Some(shots)) =
+                (b.building_range, b.attack_power, b.attacks_per_cycle)
+            {
+                if in_range((b.x, b.y), index, range) {
+                    shooters.push(Shooter {
+                        id: AuraSource::Building(b.id),
+                        power: ap,
+                        shots_per_cycle: shots as usize,
+                    });
+                }
+            }
+        }
+        shooters
+    }
+}
+
+fn in_range(center: (i32, i32), index: &TileIndex, range: f32) -> bool {
+    let dx = (center.0 - index.0 as i32).abs();
+    let dy = (center.1 - index.1 as i32).abs();
+    (dx * dx + dy * dy) as f32 <= range * range
 }
@@ -23,21 +23,31 @@ impl TownWorker {
         (&self.dbpool).into()
     }
     fn work(&mut self, ctx: &mut Context<Self>) {
-        while let Some(event) = self.event_queue.poll_event() {
-            let res = event.run(&self.db());
-            if let Some((next_event, time)) = res {
-                self.event_queue.add_event(next_event, time);
+        while let Some((event, db_id)) = self.event_queue.poll_event(self.dbpool.clock().now()) {
+            let db = self.db();
+            let follow_ups = event.run(&db);
+            db.delete_scheduled_event(db_id);
+            for (next_event, time) in follow_ups {
+                self.add_event(&db, next_event, time);
             }
         }
         ctx.run_later(std::time::Duration::from_millis(100), Self::work);
     }
+    /// Stores the event in the database and then queues it up for execution
+    fn add_event(&mut self, db: &DB, event: Event, time: DateTime<Utc>) {
+        let stored = db.insert_scheduled_event(&event.to_db(time));
+        self.event_queue.add_event(event, time, stored.id);
+    }
+    /// Loads all events that have been stored before the last shutdown.
+    /// Events that became due during the downtime are executed in order of their due time.
     fn with_filled_event_queue(mut self) -> Self {
         let db = self.db();
-        for village in db.all_player_villages() {
-            for unit in db.workers(village.key()) {
-                if let Some((event, time)) = Event::load_next_worker_task(&db, unit.key()) {
-                    self.event_queue.add_event(event, time);
-                }
+        for stored in db.scheduled_events() {
+            if let Some((event, time)) = Event::from_db(&stored) {
+                self.event_queue.add_event(event, time, stored.id);
+            } else {
+                println!("Dropping invalid scheduled event: {:?}", stored);
+                db.delete_scheduled_event(stored.id);
             }
         }
         self
@@ -66,6 +76,45 @@ impl Message for TownWorkerEventMsg {
 impl Handler<TownWorkerEventMsg> for TownWorker {
     type Result = ();
     fn handle(&mut self, msg: TownWorkerEventMsg, _ctx: &mut Context<Self>) {
-        self.event_queue.add_event(msg.0, msg.1);
+        let db = self.db();
+        self.add_event(&db, msg.0, msg.1);
+    }
+}
+
+/// Requests a copy of all events that are currently queued
+pub struct ListScheduledEvents;
+
+impl Message for ListScheduledEvents {
+    type Result = Vec<(Event, DateTime<Utc>)>;
+}
+impl Handler<ListScheduledEvents> for TownWorker {
+    type Result = MessageResult<ListScheduledEvents>;
+    fn handle(&mut self, _msg: ListScheduledEvents, _ctx: &mut Context<Self>) -> Self::Result {
+        MessageResult(self.event_queue.events())
+    }
+}
+
+/// Evaluates an attack immediately and answers with the outcome for one of its visitors.
+/// Going through the town worker keeps the evaluation from overlapping with scheduled attack checks.
+pub struct EvaluateAttackMsg {
+    pub attack: AttackKey,
+    pub hobo: HoboKey,
+}
+
+impl Message for EvaluateAttackMsg {
+    type Result = Option<bool>;
+}
+impl Handler<EvaluateAttackMsg> for TownWorker {
+    type Result = MessageResult<EvaluateAttackMsg>;
+    fn handle(&mut self, msg: EvaluateAttackMsg, _ctx: &mut Context<Self>) -> Self::Result {
+        let db = self.db();
+        // The attack may have been finished while the message was queued
+        let outcome = db.attack(msg.attack).and_then(|atk| {
+            db.maybe_evaluate_attack(&atk, db.now())
+                .into_iter()
+                .find(|(key, _)| *key == msg.hobo)
+                .and_then(|(_, satisfied)| satisfied)
+        });
+        MessageResult(outcome)
     }
 }
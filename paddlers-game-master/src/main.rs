@@ -6,7 +6,9 @@ mod api;
 mod authentication;
 mod buildings;
 mod db;
+mod error;
 mod game_master;
+mod notifications;
 mod resource_system;
 mod setup;
 mod statistics;
@@ -18,14 +20,16 @@ use actix_cors::Cors;
 use actix_web::{http::header, web, App, HttpServer};
 use db::*;
 use game_master::{
-    attack_funnel::AttackFunnel, attack_spawn::AttackSpawner, economy_worker::EconomyWorker,
-    town_worker::TownWorker, GameMaster,
+    attack_funnel::AttackFunnel, attack_spawn::AttackSpawner, town_worker::TownWorker, GameMaster,
 };
 use paddlers_shared_lib::api::reports::ReportCollect;
 use paddlers_shared_lib::api::story::StoryStateTransition;
-use paddlers_shared_lib::prelude::HoboKey;
+use paddlers_shared_lib::balancing::validate_balancing;
+use paddlers_shared_lib::clock::{Clock, RealClock};
+use paddlers_shared_lib::prelude::{HoboKey, PlayerKey};
 use paddlers_shared_lib::{
     api::{
+        admin::{AnarchistAttackOrder, KarmaGrant, ResourceGrant, StoryStateReset},
         attacks::AttackDescriptor,
         shop::{BuildingDeletion, BuildingPurchase, ProphetPurchase},
         statistics::FrontendRuntimeStatistics,
@@ -33,14 +37,12 @@ use paddlers_shared_lib::{
     },
     config::Config,
 };
-
-type StringErr = Result<(), String>;
+use std::sync::Arc;
 
 struct ActorAddresses {
     _game_master: Addr<GameMaster>,
     town_worker: Addr<TownWorker>,
-    _econ_worker: Addr<EconomyWorker>,
-    _attack_worker: Addr<AttackSpawner>,
+    attack_worker: Addr<AttackSpawner>,
     db_actor: Addr<DbActor>,
     attack_funnel: Addr<AttackFunnel>,
 }
@@ -49,11 +51,28 @@ fn main() {
     std::env::set_var("RUST_LOG", "actix_web=info");
     env_logger::init();
 
-    let dbpool: Pool = DB::new_pool();
+    validate_balancing().expect("Invalid balancing specification.");
+
+    let clock: Clock = Arc::new(RealClock);
+    let dbpool: Pool = DB::new_pool(clock);
     let conn: DB = (&dbpool.clone()).into();
     conn.db_scripts_by_env().expect("DB initialization failed.");
     println!("DB successfully migrated");
 
+    // Commands for operating the game, they run instead of the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("end-season") => {
+            setup::end_season_command(&conn, &args[1..]).expect("Ending the season failed.");
+            return;
+        }
+        Some("new-world") => {
+            setup::new_world_command(&conn, &args[1..]).expect("Creating the world failed.");
+            return;
+        }
+        _ => {}
+    }
+
     let config = Config::from_env().unwrap_or(Config::default());
     let origin = config.frontend_origin.clone();
     let base_url = config.game_master_service_name.clone();
@@ -67,12 +86,14 @@ fn main() {
 
     // Spawn all "normal" actors onto the actix system
     let town_worker_actor = TownWorker::new(dbpool.clone()).start();
-    let attack_funnel =
-        AttackFunnel::new(dbpool.clone(), db_actor.clone(), town_worker_actor.clone()).start();
-    let attack_worker =
-        AttackSpawner::new(dbpool.clone(), db_actor.clone(), attack_funnel.clone()).start();
-    let gm_actor = GameMaster::new(dbpool.clone(), &attack_worker).start();
-    let econ_worker = EconomyWorker::new(dbpool.clone()).start();
+    let attack_funnel = AttackFunnel::new(dbpool.clone(), town_worker_actor.clone()).start();
+    let attack_worker = AttackSpawner::new(attack_funnel.clone()).start();
+    let gm_actor = GameMaster::new(dbpool.clone(), &attack_worker, &town_worker_actor).start();
+
+    notifications::start_hub();
+
+    // Shared between all HTTP workers
+    let notification_limiter = web::Data::new(api::VisitorNotificationLimiter::default());
 
     // Also spawn the HTTP server on the same runtime
     HttpServer::new(move || {
@@ -89,14 +110,15 @@ fn main() {
             .data(ActorAddresses {
                 _game_master: gm_actor.clone(),
                 town_worker: town_worker_actor.clone(),
-                _econ_worker: econ_worker.clone(),
-                _attack_worker: attack_worker.clone(),
+                attack_worker: attack_worker.clone(),
                 db_actor: db_actor.clone(),
                 attack_funnel: attack_funnel.clone(),
             })
             .data(config.clone())
             .data(dbpool.clone())
+            .register_data(notification_limiter.clone())
             .route("/", web::get().to(api::index))
+            .service(web::resource("/notifications").route(web::get().to(notifications::connect)))
             .service(web::resource("/player/create").route(web::post().to(api::new_player)))
             .service(
                 web::resource("/shop/building")
@@ -126,7 +148,7 @@ fn main() {
             .service(
                 web::resource("/attacks/notifications/visitor_satisfied")
                     .data(web::Json::<HoboKey>)
-                    .route(web::post().to(api::visitor_satisfied_notification)),
+                    .route(web::post().to_async(api::visitor_satisfied_notification)),
             )
             .service(
                 web::resource("/report/collect")
@@ -143,6 +165,38 @@ fn main() {
                     .data(web::Json::<FrontendRuntimeStatistics>)
                     .route(web::post().to(statistics::new_frontend_info)),
             )
+            .service(
+                web::scope("/admin")
+                    .service(
+                        web::resource("/player")
+                            .data(web::Json::<PlayerKey>)
+                            .route(web::post().to(api::admin::inspect_player)),
+                    )
+                    .service(
+                        web::resource("/resources")
+                            .data(web::Json::<ResourceGrant>)
+                            .route(web::post().to(api::admin::grant_resources)),
+                    )
+                    .service(
+                        web::resource("/karma")
+                            .data(web::Json::<KarmaGrant>)
+                            .route(web::post().to(api::admin::grant_karma)),
+                    )
+                    .service(
+                        web::resource("/story")
+                            .data(web::Json::<StoryStateReset>)
+                            .route(web::post().to(api::admin::reset_story_state)),
+                    )
+                    .service(
+                        web::resource("/attack")
+                            .data(web::Json::<AnarchistAttackOrder>)
+                            .route(web::post().to(api::admin::send_anarchist_attack)),
+                    )
+                    .service(
+                        web::resource("/events")
+                            .route(web::post().to_async(api::admin::list_scheduled_events)),
+                    ),
+            )
     })
     .disable_signals()
     .bind(&base_url)
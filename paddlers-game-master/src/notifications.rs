@@ -0,0 +1,230 @@
+//! Pushes change notifications to connected frontends over WebSockets.
+//!
+//! Each WebSocket session subscribes to the villages of its player at the `NotificationHub`.
+//! Game logic calls `notify` whenever something changes that the frontend would otherwise have to poll for.
+//! Notifications are best-effort, frontends reload everything after reconnecting.
+
+use crate::db::{Pool, DB};
+use crate::error::GameMasterError;
+use actix::prelude::*;
+use actix_web::error::ErrorUnauthorized;
+use actix_web::http::header::SEC_WEBSOCKET_PROTOCOL;
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use once_cell::sync::OnceCell;
+use paddlers_shared_lib::api::notifications::{Notification, NOTIFICATION_PROTOCOL};
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::user_authentication::PadlUser;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+/// Sessions that have not answered a ping for this long are closed
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+static HUB: OnceCell<Addr<NotificationHub>> = OnceCell::new();
+static NEXT_SESSION_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts the hub on the current actix system, must be called before the HTTP server is started
+pub(crate) fn start_hub() {
+    if HUB.set(NotificationHub::default().start()).is_err() {
+        panic!("Notification hub started twice");
+    }
+}
+
+/// Sends the notification to all players owning the village.
+/// Does nothing when no hub is running, e.g. in command-line mode.
+pub(crate) fn notify(notification: Notification) {
+    if let Some(hub) = HUB.get() {
+        hub.do_send(PushNotification(notification));
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct PushNotification(Notification);
+impl Message for PushNotification {
+    type Result = ();
+}
+
+struct Subscribe {
+    session: usize,
+    villages: Vec<VillageKey>,
+    recipient: Recipient<PushNotification>,
+}
+impl Message for Subscribe {
+    type Result = ();
+}
+
+struct Unsubscribe {
+    session: usize,
+}
+impl Message for Unsubscribe {
+    type Result = ();
+}
+
+#[derive(Default)]
+pub(crate) struct NotificationHub {
+    subscribers: BTreeMap<VillageKey, Vec<(usize, Recipient<PushNotification>)>>,
+}
+
+impl Actor for NotificationHub {
+    type Context = Context<Self>;
+}
+
+impl Handler<Subscribe> for NotificationHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Subscribe, _ctx: &mut Context<Self>) {
+        for village in msg.villages {
+            self.subscribers
+                .entry(village)
+                .or_default()
+                .push((msg.session, msg.recipient.clone()));
+        }
+    }
+}
+
+impl Handler<Unsubscribe> for NotificationHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Unsubscribe, _ctx: &mut Context<Self>) {
+        let mut unused = vec![];
+        for (village, sessions) in self.subscribers.iter_mut() {
+            sessions.retain(|(id, _)| *id != msg.session);
+            if sessions.is_empty() {
+                unused.push(*village);
+            }
+        }
+        for village in unused {
+            self.subscribers.remove(&village);
+        }
+    }
+}
+
+impl Handler<PushNotification> for NotificationHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: PushNotification, _ctx: &mut Context<Self>) {
+        if let Some(sessions) = self.subscribers.get(&msg.0.village()) {
+            for (_, recipient) in sessions {
+                // Closed sessions unsubscribe themselves
+                let _ = recipient.do_send(msg.clone());
+            }
+        }
+    }
+}
+
+/// WebSocket connection of one frontend
+struct NotificationSession {
+    id: usize,
+    villages: Vec<VillageKey>,
+    last_heartbeat: Instant,
+}
+
+impl Actor for NotificationSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.heartbeat(ctx);
+        if let Some(hub) = HUB.get() {
+            hub.do_send(Subscribe {
+                session: self.id,
+                villages: self.villages.clone(),
+                recipient: ctx.address().recipient(),
+            });
+        }
+    }
+
+    fn stopping(&mut self, _ctx: &mut Self::Context) -> Running {
+        if let Some(hub) = HUB.get() {
+            hub.do_send(Unsubscribe { session: self.id });
+        }
+        Running::Stop
+    }
+}
+
+impl StreamHandler<ws::Message, ws::ProtocolError> for NotificationSession {
+    fn handle(&mut self, msg: ws::Message, ctx: &mut Self::Context) {
+        match msg {
+            ws::Message::Ping(msg) => {
+                self.last_heartbeat = Instant::now();
+                ctx.pong(&msg);
+            }
+            ws::Message::Pong(_) => {
+                self.last_heartbeat = Instant::now();
+            }
+            ws::Message::Close(_) => ctx.stop(),
+            // The channel only pushes to the frontend, anything else is ignored
+            _ => {}
+        }
+    }
+}
+
+impl Handler<PushNotification> for NotificationSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: PushNotification, ctx: &mut Self::Context) {
+        match serde_json::to_string(&msg.0) {
+            Ok(text) => ctx.text(text),
+            Err(e) => println!("Serializing notification failed: {}", e),
+        }
+    }
+}
+
+impl NotificationSession {
+    fn new(villages: Vec<VillageKey>) -> Self {
+        NotificationSession {
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            villages,
+            last_heartbeat: Instant::now(),
+        }
+    }
+    fn heartbeat(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |session, ctx| {
+            if Instant::now().duration_since(session.last_heartbeat) > CLIENT_TIMEOUT {
+                ctx.stop();
+                return;
+            }
+            ctx.ping("");
+        });
+    }
+}
+
+/// Reads the JWT that the frontend offers as sub-protocol next to `NOTIFICATION_PROTOCOL`.
+/// Unlike a query parameter, the header does not end up in access logs.
+fn protocol_token(req: &HttpRequest) -> Option<&str> {
+    let protocols = req.headers().get(SEC_WEBSOCKET_PROTOCOL)?.to_str().ok()?;
+    let mut protocols = protocols.split(',').map(str::trim);
+    if !protocols.clone().any(|p| p == NOTIFICATION_PROTOCOL) {
+        return None;
+    }
+    protocols.find(|p| *p != NOTIFICATION_PROTOCOL)
+}
+
+/// Opens the WebSocket for the player's villages.
+pub(crate) fn connect(
+    req: HttpRequest,
+    stream: web::Payload,
+    pool: web::Data<Pool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let token = protocol_token(&req).ok_or(ErrorUnauthorized("No token"))?;
+    let user = PadlUser::from_token(token, &config).map_err(ErrorUnauthorized)?;
+    let db: DB = pool.get_ref().into();
+    let player = db
+        .player_by_uuid(user.uuid)
+        .ok_or(GameMasterError::PlayerNotCreated)?;
+    let villages = db
+        .player_villages(player.key())
+        .iter()
+        .map(Village::key)
+        .collect();
+    // Only the fixed protocol is echoed back, the token stays out of the response
+    ws::start_with_protocols(
+        NotificationSession::new(villages),
+        &[NOTIFICATION_PROTOCOL],
+        &req,
+        stream,
+    )
+}
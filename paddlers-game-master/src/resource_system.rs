@@ -1,4 +1,7 @@
-use crate::{db::DB, StringErr};
+use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
+use paddlers_shared_lib::game_mechanics::worker::hero_max_mana;
+use paddlers_shared_lib::sql::Income;
 use paddlers_shared_lib::{api::shop::*, prelude::*};
 
 impl DB {
@@ -19,24 +22,50 @@ impl DB {
         }
     }
 
-    pub fn try_spend(&self, p: &Price, village: VillageKey) -> StringErr {
-        self.can_afford(p, village)?;
-        self.spend(p, village);
-        Ok(())
-    }
-
-    pub fn spend(&self, p: &Price, village: VillageKey) {
-        for (res, n) in p.0.iter() {
-            self.add_resource((*res).into(), village, -*n)
-                .expect("Unchecked spending resources");
+    /// Stores all income that workers of the village have gathered since it has last been stored.
+    /// Must be called before resources are spent, the stored amounts alone are outdated.
+    pub fn collect_income(&self, village: VillageKey) {
+        for mut worker in self.workers(village) {
+            self.collect_worker_income(&mut worker);
         }
     }
-    pub fn can_afford(&self, p: &Price, village: VillageKey) -> StringErr {
-        for (res, n) in p.0.iter() {
-            if self.resource((*res).into(), village) < *n {
-                return Err(format!("Not enough {}", res));
+    /// Stores resources and mana a single worker has gathered, the mana of `worker` is updated accordingly.
+    /// Must be called before its mana is used or before its work flag is reset.
+    pub fn collect_worker_income(&self, worker: &mut Worker) {
+        for pending in self.pending_income(worker.key()) {
+            // When two threads collect at the same time, only one of them adds the income
+            if !self.advance_worker_flag(&pending.flag, pending.until) {
+                continue;
+            }
+            match pending.income {
+                Income::Mana(n) => {
+                    self.add_worker_mana(worker.key(), n, hero_max_mana());
+                    worker.mana = worker.mana.map(|mana| hero_max_mana().min(mana + n));
+                }
+                Income::Resource(res, n) => {
+                    self.add_resource(res, worker.home(), n)
+                        .expect("Adding resources");
+                }
             }
         }
-        Ok(())
+    }
+
+    /// Pays the price with resources of the village, or nothing at all if the village cannot afford it.
+    /// Safe to call concurrently, also as part of a larger transaction.
+    /// The caller notifies the frontend once the surrounding transaction has been committed.
+    pub fn try_spend(&self, p: &Price, village: VillageKey) -> GameMasterResult<()> {
+        self.collect_income(village);
+        self.transaction(|| {
+            for (res, n) in p.0.iter() {
+                let paid = self.spend_resource((*res).into(), village, *n)?;
+                if !paid {
+                    return Err(GameMasterError::NotEnoughResources(*res));
+                }
+            }
+            Ok(())
+        })
     }
 }
+
+#[cfg(test)]
+mod resource_system_test;
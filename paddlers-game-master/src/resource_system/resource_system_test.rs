@@ -0,0 +1,80 @@
+//! These tests need a local Postgres database at DATABASE_URL.
+//! Run them with `cargo test -- --ignored`.
+
+use crate::db::{Pool, DB};
+use paddlers_shared_lib::api::shop::Price;
+use paddlers_shared_lib::clock::RealClock;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::sql_db::run_db_migrations;
+use std::sync::Arc;
+
+const PARALLEL_PURCHASES: usize = 16;
+
+#[test]
+#[ignore]
+fn parallel_purchases_do_not_overspend() {
+    let pool = DB::new_pool(Arc::new(RealClock));
+    let village = test_village(&pool);
+    let db: DB = (&pool).into();
+
+    // Enough for exactly 5 purchases
+    let sticks = db.resource(ResourceType::Sticks, village);
+    db.add_resource(ResourceType::Sticks, village, 50 - sticks)
+        .unwrap();
+    let price = Price(vec![(ResourceType::Sticks, 10)]);
+
+    let threads: Vec<_> = (0..PARALLEL_PURCHASES)
+        .map(|_| {
+            let pool = pool.clone();
+            let price = price.clone();
+            std::thread::spawn(move || {
+                let db: DB = (&pool).into();
+                db.try_spend(&price, village).is_ok()
+            })
+        })
+        .collect();
+    let purchases = threads
+        .into_iter()
+        .filter(|t| t.join().expect("Purchase panicked"))
+        .count();
+
+    assert_eq!(purchases, 5);
+    assert_eq!(db.resource(ResourceType::Sticks, village), 0);
+}
+
+#[test]
+#[ignore]
+fn failed_purchase_spends_nothing() {
+    let pool = DB::new_pool(Arc::new(RealClock));
+    let village = test_village(&pool);
+    let db: DB = (&pool).into();
+
+    let sticks = db.resource(ResourceType::Sticks, village);
+    let feathers = db.resource(ResourceType::Feathers, village);
+    let price = Price(vec![
+        (ResourceType::Sticks, 1),
+        (ResourceType::Feathers, feathers + 1),
+    ]);
+    assert!(db.try_spend(&price, village).is_err());
+    assert_eq!(db.resource(ResourceType::Sticks, village), sticks);
+    assert_eq!(db.resource(ResourceType::Feathers, village), feathers);
+}
+
+/// Creates a new player with a fresh village, the database is initialized if necessary
+fn test_village(pool: &Pool) -> VillageKey {
+    let db: DB = pool.into();
+    run_db_migrations(db.dbconn()).expect("Migrations failed");
+    if db.streams(WorldKey(1), 0.0, f32::MAX).is_empty() {
+        db.init_map(WorldKey(1), 1);
+    }
+    let unique = chrono::Utc::now().timestamp_nanos() as u128;
+    let player = db
+        .new_player(
+            format!("Purchase Test {}", unique),
+            uuid::Uuid::from_u128(unique),
+            WorldKey(1),
+            None,
+        )
+        .expect("Creating test player failed");
+    db.player_villages(player.key())[0].key()
+}
@@ -2,15 +2,20 @@
 //!  - Server initialization
 //!  - Map generation
 //!  - Player creation
+//!  - World and season management
 
 mod map_generation;
 mod new_player;
+mod season;
 mod village_creation;
+mod village_placement;
 
 use crate::buildings::BuildingFactory;
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use diesel::result::{DatabaseErrorKind, Error};
 use dotenv::dotenv;
+use map_generation::world_map_seed;
 use paddlers_shared_lib::test_data::*;
 use paddlers_shared_lib::{
     api::PlayerInitData, prelude::*, sql_db::run_db_migrations, story::story_state::StoryState,
@@ -21,30 +26,95 @@ pub(crate) fn initialize_new_player_account(
     db: &DB,
     uuid: uuid::Uuid,
     info: &PlayerInitData,
-) -> Result<(), String> {
-    let result = db.new_player(info.display_name.clone(), uuid);
+) -> GameMasterResult<()> {
+    let friend = match &info.invited_by {
+        Some(code) => Some(
+            db.player_by_invite_code(code)
+                .ok_or(GameMasterError::NotFound("Inviting player"))?,
+        ),
+        None => None,
+    };
+    // Invited players join their friend's world, unless they explicitly want another one
+    let world = match (info.world, &friend) {
+        (None, Some(friend)) => friend.world(),
+        (requested, _) => pick_world(db, requested)?,
+    };
+    let result = db.new_player(
+        info.display_name.clone(),
+        uuid,
+        world,
+        friend.map(|f| f.key()),
+    );
     if let Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _info)) = result {
         println!("Warning: Tried to create player account that already exists");
         Ok(())
     } else {
         result
-            .map_err(|_e| "Player creation failed".to_owned())
+            .map_err(|e| GameMasterError::Internal(format!("Player creation failed: {}", e)))
             .map(|_p| ())
     }
 }
 
+/// Uses the world requested by the player, or the one with the fewest players if none was requested
+fn pick_world(db: &DB, requested: Option<WorldKey>) -> GameMasterResult<WorldKey> {
+    if let Some(world) = requested {
+        db.world(world).ok_or(GameMasterError::NotFound("World"))?;
+        Ok(world)
+    } else {
+        db.worlds()
+            .iter()
+            .map(|w| w.key())
+            .min_by_key(|w| db.world_player_count(*w))
+            .ok_or(GameMasterError::Internal("No world exists".to_owned()))
+    }
+}
+
+/// Handles `new-world NAME` from the command line
+pub(crate) fn new_world_command(db: &DB, args: &[String]) -> Result<(), String> {
+    let name = match args {
+        [name] => name.clone(),
+        _ => return Err("Usage: new-world NAME".to_owned()),
+    };
+    let world = db.new_world(name).map_err(|e| e.to_string())?;
+    println!("World {} created: {}", world.id, world.display_name);
+    Ok(())
+}
+
+/// Handles `end-season [--seed N]` from the command line.
+/// Without a seed, the map of the next season is generated with the previous seed plus one.
+pub(crate) fn end_season_command(db: &DB, args: &[String]) -> Result<(), String> {
+    let seed = match args {
+        [] => db
+            .current_season()
+            .map(|s| s.map_seed as u64 + 1)
+            .ok_or("No season is running")?,
+        [flag, n] if flag == "--seed" => n.parse().map_err(|e| format!("Invalid seed: {}", e))?,
+        _ => return Err("Usage: end-season [--seed N]".to_owned()),
+    };
+    let season = db.end_season(seed).map_err(|e| e.to_string())?;
+    println!(
+        "Season {} started with map seed {}",
+        season.id, season.map_seed
+    );
+    Ok(())
+}
+
 impl DB {
     pub fn db_scripts_by_env(&self) -> Result<(), Box<dyn std::error::Error>> {
         dotenv().ok();
         if env::var("DATABASE_INIT").is_ok() {
-            let server = 1;
             run_db_migrations(self.dbconn())?;
-            self.init_map(server);
+            let season = self.current_season().ok_or("No season is running")?;
+            for world in self.worlds() {
+                self.init_map(world.key(), world_map_seed(season.map_seed, world.key()));
+            }
         }
         if env::var("INSERT_TEST_DATA").is_ok() {
             if let Ok(player) = self.new_player(
                 TEST_PLAYER_NAME.to_owned(),
                 uuid::Uuid::parse_str(TEST_PLAYER_UUID).unwrap(),
+                WorldKey(1),
+                None,
             ) {
                 let village = self.player_villages(player.key())[0];
                 self.add_prophet(village.key());
@@ -56,7 +126,7 @@ impl DB {
         Ok(())
     }
     fn insert_temple(&self, village: VillageKey) {
-        let building = BuildingFactory::new(BuildingType::Temple, (4, 2), village);
+        let building = BuildingFactory::new(BuildingType::Temple, (4, 2), village, self.now());
         self.insert_building(&building);
     }
 }
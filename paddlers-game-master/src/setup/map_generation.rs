@@ -1,36 +1,55 @@
 //! Uses a LCG to generate a pseudo-random sequence for the streams on the map
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use paddlers_shared_lib::game_mechanics::map::*;
 use paddlers_shared_lib::prelude::*;
 mod lcg;
 use lcg::Lcg;
 
-const STREAMS: usize = 100;
+/// Streams are generated in pairs, one flowing to each side of the river
+const STREAM_PAIRS: usize = 100;
+/// Horizontal space taken by one pair of streams
+const STREAM_PAIR_W: usize = 4;
 const ANARCHISTS: usize = 100;
+/// Number of stream pairs appended to a world that has no space left for new villages
+const EXPANSION_STREAM_PAIRS: usize = 10;
 
-struct NewMap {
-    streams: Vec<NewStream>,
+/// Each world has its own map, the seed of it is derived from the season's seed.
+/// The first world uses the season's seed unchanged.
+pub(crate) fn world_map_seed(season_seed: i64, world: WorldKey) -> u64 {
+    season_seed as u64 ^ ((world.num() as u64 - 1) << 32)
+}
+
+pub(super) struct NewMap {
+    pub streams: Vec<NewStream>,
 }
 
 impl NewMap {
-    /// Generates the map for a specific server (Seed = server id)
-    fn generate(seed: u64) -> NewMap {
+    /// Generates streams for a specific world, see `world_map_seed`.
+    /// The streams are placed to the right of `start_x`, the same seed and start always give the same streams.
+    pub(super) fn generate(world: WorldKey, seed: u64, start_x: usize, pairs: usize) -> NewMap {
         let mut streams = vec![];
-        let mut lcg = Lcg::new(seed);
+        let mut lcg = Lcg::new(seed.wrapping_add(start_x as u64));
 
         let start_y = 5.5;
         let dx = MAP_STREAM_AREA_W;
-        for i in 0..STREAMS {
-            let b = (4 * i) as f32;
-            streams.push(new_stream((b + 1.0, start_y), dx, 20.0, &mut lcg));
-            streams.push(new_stream((b + 3.0, start_y), dx, -10.0, &mut lcg));
+        for i in 0..pairs {
+            let b = (start_x + STREAM_PAIR_W * i) as f32;
+            streams.push(new_stream((b + 1.0, start_y), dx, 20.0, world, &mut lcg));
+            streams.push(new_stream((b + 3.0, start_y), dx, -10.0, world, &mut lcg));
         }
 
         NewMap { streams }
     }
 }
 
-fn new_stream(start: (f32, f32), max_dx: f32, max_y: f32, lcg: &mut Lcg) -> NewStream {
+fn new_stream(
+    start: (f32, f32),
+    max_dx: f32,
+    max_y: f32,
+    world: WorldKey,
+    lcg: &mut Lcg,
+) -> NewStream {
     let mut control_points = vec![];
 
     let half_max_dx = max_dx / 2.0;
@@ -54,19 +73,56 @@ fn new_stream(start: (f32, f32), max_dx: f32, max_y: f32, lcg: &mut Lcg) -> NewS
     NewStream {
         start_x: start.0,
         control_points,
+        world_id: world.num(),
     }
 }
 
 impl DB {
-    pub fn init_map(&self, seed: u64) {
-        let map = NewMap::generate(seed);
+    pub fn init_map(&self, world: WorldKey, seed: u64) {
+        let map = NewMap::generate(world, seed, 0, STREAM_PAIRS);
 
-        self.insert_streams(&map.streams);
+        let streams = self.insert_streams(&map.streams);
 
-        if let Err(e) = self.generate_anarchists(ANARCHISTS) {
+        if let Err(e) = self.generate_anarchists(&streams, ANARCHISTS) {
             eprintln!("Failure on anarchists spawning: {}", e);
         }
         // #[cfg(debug_assertions)]
         // self.test_add_all_villages();
     }
+    /// Appends new streams to the right end of the world, unless another expansion happened in the meantime
+    pub(crate) fn expand_map(&self, world: WorldKey, known_width: i32) -> GameMasterResult<()> {
+        let season = self
+            .current_season()
+            .ok_or(GameMasterError::NotFound("Season"))?;
+        self.transaction(|| {
+            // Concurrent expansions would otherwise generate overlapping streams
+            self.lock_world(world)?;
+            let width = self.map_width(world);
+            if width == known_width {
+                let seed = world_map_seed(season.map_seed, world);
+                let map = NewMap::generate(world, seed, width as usize, EXPANSION_STREAM_PAIRS);
+                let streams = self.insert_streams(&map.streams);
+                // Same share of anarchists as on the initial map
+                let anarchists = streams.len() * ANARCHISTS / (2 * STREAM_PAIRS);
+                if let Err(e) = self.generate_anarchists(&streams, anarchists) {
+                    eprintln!("Failure on anarchists spawning: {}", e);
+                }
+            }
+            Ok(())
+        })
+    }
+    /// Adds a world next to the existing ones, with a map for the current season
+    pub(crate) fn new_world(&self, display_name: String) -> GameMasterResult<World> {
+        let season = self
+            .current_season()
+            .ok_or(GameMasterError::NotFound("Season"))?;
+        self.transaction(|| {
+            let world = self.insert_world(&NewWorld { display_name })?;
+            self.init_map(world.key(), world_map_seed(season.map_seed, world.key()));
+            Ok(world)
+        })
+    }
 }
+
+#[cfg(test)]
+mod map_generation_test;
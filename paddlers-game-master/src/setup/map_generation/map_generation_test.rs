@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn appended_streams_are_right_of_the_map() {
+    let world = WorldKey(1);
+    let initial = NewMap::generate(world, 1, 0, STREAM_PAIRS);
+    let width = initial
+        .streams
+        .iter()
+        .map(|s| s.start_x.ceil() as usize + 1)
+        .max()
+        .unwrap();
+    assert_eq!(width, STREAM_PAIRS * STREAM_PAIR_W);
+
+    let expansion = NewMap::generate(world, 1, width, EXPANSION_STREAM_PAIRS);
+    assert_eq!(expansion.streams.len(), 2 * EXPANSION_STREAM_PAIRS);
+    assert!(expansion.streams.iter().all(|s| s.start_x > width as f32));
+
+    let again = NewMap::generate(world, 1, width, EXPANSION_STREAM_PAIRS);
+    for (a, b) in expansion.streams.iter().zip(&again.streams) {
+        assert_eq!(a.control_points, b.control_points);
+    }
+}
@@ -1,19 +1,46 @@
+use super::village_placement::{default_strategy, NearFriend, PlacementStrategy};
 use crate::db::DB;
 use diesel::QueryResult;
 use paddlers_shared_lib::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+
+/// Length of the random invite codes, collisions are rejected by the DB
+const INVITE_CODE_LEN: usize = 16;
 
 impl DB {
-    pub(super) fn new_player(&self, display_name: String, uuid: uuid::Uuid) -> QueryResult<Player> {
+    /// Creates a player with a first village, which is placed close to the inviting friend if there is one
+    pub(crate) fn new_player(
+        &self,
+        display_name: String,
+        uuid: uuid::Uuid,
+        world: WorldKey,
+        invited_by: Option<PlayerKey>,
+    ) -> QueryResult<Player> {
         let player = NewPlayer {
             display_name: display_name,
             karma: 0,
             uuid,
+            world_id: world.num(),
+            invite_code: new_invite_code(),
         };
         let player = self.insert_player(&player)?;
-        let village = self.new_village(player.key());
+        let strategy: Box<dyn PlacementStrategy> = match invited_by {
+            Some(friend) => Box::new(NearFriend(friend)),
+            None => default_strategy(),
+        };
+        self.new_player_village(&player, &*strategy);
+        Ok(player)
+    }
 
+    /// Gives a player a freshly settled village with a hero in it
+    pub(crate) fn new_player_village(
+        &self,
+        player: &Player,
+        strategy: &dyn PlacementStrategy,
+    ) -> Village {
+        let village = self.new_village(player, strategy);
         self.insert_hero(village.key());
-        Ok(player)
+        village
     }
 
     fn insert_hero(&self, vid: VillageKey) -> Worker {
@@ -35,7 +62,7 @@ impl DB {
             task_type: TaskType::Idle,
             x: x,
             y: y,
-            start_time: None,
+            start_time: Some(self.now()),
             target_hobo_id: None,
         };
         self.insert_task(&task);
@@ -52,18 +79,20 @@ impl DB {
         self.insert_worker_flag(WorkerFlag {
             worker_id: worker.id,
             flag_type: WorkerFlagType::ManaRegeneration,
-            last_update: chrono::Utc::now().naive_utc(),
+            last_update: self.now(),
         });
         self.insert_worker_flag(WorkerFlag {
             worker_id: worker.id,
             flag_type: WorkerFlagType::Work,
-            last_update: chrono::Utc::now().naive_utc(),
+            last_update: self.now(),
         });
         worker
     }
 
-    fn new_village(&self, pid: PlayerKey) -> Village {
-        let village = self.add_village(pid).expect("Village insertion failed");
+    fn new_village(&self, player: &Player, strategy: &dyn PlacementStrategy) -> Village {
+        let village = self
+            .add_village(player, strategy)
+            .expect("Village insertion failed");
         self.insert_initial_resources(village.key());
         village
     }
@@ -88,3 +117,10 @@ impl DB {
             .expect("Adding dev resources");
     }
 }
+
+fn new_invite_code() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(INVITE_CODE_LEN)
+        .collect()
+}
@@ -0,0 +1,61 @@
+//! Ending a season archives the standings and starts over with a new map.
+//!
+//! This must only run while the game-master is stopped, events queued in memory would refer to deleted objects.
+
+use super::map_generation::world_map_seed;
+use super::village_placement::default_strategy;
+use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
+use paddlers_shared_lib::prelude::*;
+
+impl DB {
+    /// Archives the standings of the current season, then resets all worlds with maps generated from `seed`.
+    ///
+    /// Player accounts are kept, each player starts the new season with a single new village in the same world.
+    /// Returns the newly started season.
+    pub(crate) fn end_season(&self, seed: u64) -> GameMasterResult<Season> {
+        let season = self
+            .current_season()
+            .ok_or(GameMasterError::NotFound("Season"))?;
+        let worlds = self.worlds();
+        self.transaction(|| {
+            let mut players = vec![];
+            for world in &worlds {
+                let world_players =
+                    self.players_sorted_by_karma(world.key(), None, i64::max_value());
+                let standings: Vec<SeasonStanding> = world_players
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| SeasonStanding {
+                        season_id: season.id,
+                        player_id: p.id,
+                        rank: i as i64 + 1,
+                        karma: p.karma,
+                        story_state: p.story_state,
+                        village_count: self.player_village_count(p.key()),
+                        world_id: world.id,
+                    })
+                    .collect();
+                self.insert_season_standings(&standings)?;
+                players.extend(world_players);
+            }
+            self.end_season_now(season.key())?;
+
+            self.wipe_worlds()?;
+            self.reset_players()?;
+            for world in &worlds {
+                self.init_map(world.key(), world_map_seed(seed as i64, world.key()));
+            }
+            let strategy = default_strategy();
+            for p in &players {
+                self.new_player_village(p, &*strategy);
+            }
+
+            let new_season = self.insert_season(&NewSeason {
+                map_seed: seed as i64,
+                start_time: self.now(),
+            })?;
+            Ok(new_season)
+        })
+    }
+}
@@ -1,30 +1,65 @@
+use super::village_placement::{Candidate, PlacementStrategy};
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use paddlers_shared_lib::game_mechanics::map::*;
 use paddlers_shared_lib::prelude::*;
+use std::collections::HashSet;
 
 impl DB {
-    pub fn add_village(&self, pid: PlayerKey) -> Result<Village, &'static str> {
-        // Find unsaturated stream
-        let streams = self.streams_to_add_village();
-        for s in &streams {
-            // Pick a position on it
-            match self.insert_village_on_stream(s, Some(pid)) {
-                Err(_) => {}
-                Ok(v) => return Ok(v),
-            }
+    /// Settles a new village for the player where the strategy wants it.
+    /// The world is expanded if it has no space left.
+    pub(crate) fn add_village(
+        &self,
+        player: &Player,
+        strategy: &dyn PlacementStrategy,
+    ) -> GameMasterResult<Village> {
+        let world = player.world();
+        let width = self.map_width(world);
+        if let Some(v) = self.place_village(world, player.key(), strategy)? {
+            return Ok(v);
         }
-        Err("World full: No space for another village")
+        self.expand_map(world, width)?;
+        self.place_village(world, player.key(), strategy)?
+            .ok_or(GameMasterError::Internal(
+                "No space for another village after expanding the world".to_owned(),
+            ))
+    }
+    /// Returns None if the strategy finds no free position
+    fn place_village(
+        &self,
+        world: WorldKey,
+        pid: PlayerKey,
+        strategy: &dyn PlacementStrategy,
+    ) -> GameMasterResult<Option<Village>> {
+        self.transaction(|| {
+            // Concurrent registrations would otherwise pick the same free position
+            self.lock_world(world)?;
+            let streams = self.streams(world, 0.0, std::f32::MAX);
+            let villages = self.villages(world, std::f32::MIN, std::f32::MAX);
+            let candidates = free_positions(&streams, &villages);
+            let c = match strategy.choose(&candidates, &villages) {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+            let v = NewVillage {
+                stream_id: c.stream_id,
+                x: c.x,
+                y: c.y,
+                player_id: Some(pid.num()),
+                faith: None, // Start with default value
+                world_id: world.num(),
+            };
+            Ok(Some(self.insert_villages(&[v])[0]))
+        })
     }
-    pub fn generate_anarchists(&self, n: usize) -> Result<(), &'static str> {
-        for i in 1..n + 1 {
-            self.add_anarchists_village(StreamKey(i as i64))?;
+    /// Places anarchist villages with their full population on the first `n` streams
+    pub fn generate_anarchists(&self, streams: &[Stream], n: usize) -> Result<(), &'static str> {
+        for s in streams.iter().take(n) {
+            let village = self.insert_village_on_stream(s, None)?;
+            self.populate_anarchist_village(village.key());
         }
         Ok(())
     }
-    fn add_anarchists_village(&self, stream_id: StreamKey) -> Result<Village, &'static str> {
-        let s = self.stream(stream_id);
-        self.insert_village_on_stream(&s, None)
-    }
     fn insert_village_on_stream(
         &self,
         s: &Stream,
@@ -32,13 +67,14 @@ impl DB {
     ) -> Result<Village, &'static str> {
         let vp = village_positions(&s.control_points);
         for (x, y) in vp {
-            if self.map_position_empty(x, y) {
+            if self.map_position_empty(s.world(), x, y) {
                 let v = NewVillage {
                     stream_id: s.id,
                     x,
                     y,
                     player_id: player.as_ref().map(PlayerKey::num),
                     faith: None, // Start with default value
+                    world_id: s.world_id,
                 };
                 return Ok(self.insert_villages(&[v])[0]);
             }
@@ -46,19 +82,14 @@ impl DB {
         Err("Stream full: No space for another village")
     }
 
-    fn streams_to_add_village(&self) -> Vec<Stream> {
-        // Good enough for now
-        self.streams(0.0, MAP_MAX_X as f32)
-    }
-
-    fn map_position_empty(&self, x: f32, y: f32) -> bool {
-        self.village_at(x, y).is_none()
+    fn map_position_empty(&self, world: WorldKey, x: f32, y: f32) -> bool {
+        self.village_at(world, x, y).is_none()
     }
 
     #[cfg(debug_assertions)]
     #[allow(dead_code)]
-    fn test_add_all_villages(&self) {
-        let streams = self.streams(-1.0, 21.0);
+    fn test_add_all_villages(&self, world: WorldKey) {
+        let streams = self.streams(world, -1.0, 21.0);
         for s in streams {
             let vp = village_positions(&s.control_points);
             for (x, y) in vp {
@@ -68,6 +99,7 @@ impl DB {
                     y,
                     player_id: None,
                     faith: None, // Start with default value
+                    world_id: world.num(),
                 };
                 self.insert_villages(&[v]);
             }
@@ -75,6 +107,31 @@ impl DB {
     }
 }
 
+/// Positions on the streams that are not taken by a village, sorted from left to right
+pub(super) fn free_positions(streams: &[Stream], villages: &[Village]) -> Vec<Candidate> {
+    let mut taken: HashSet<(i32, i32)> =
+        villages.iter().map(|v| (v.x as i32, v.y as i32)).collect();
+    let mut candidates = vec![];
+    for s in streams {
+        for (x, y) in village_positions(&s.control_points) {
+            // Streams can cross, each position is only offered once
+            if taken.insert((x as i32, y as i32)) {
+                candidates.push(Candidate {
+                    stream_id: s.id,
+                    x,
+                    y,
+                });
+            }
+        }
+    }
+    candidates.sort_by(|a, b| {
+        (a.x, a.y)
+            .partial_cmp(&(b.x, b.y))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    candidates
+}
+
 fn village_positions(stream_points: &[f32]) -> Vec<(f32, f32)> {
     let mut v: std::collections::HashSet<(i32, i32)> = std::collections::HashSet::new();
     let points: Vec<(f32, f32)> = stream_points
@@ -0,0 +1,109 @@
+//! Strategies to decide where on the map a new player village is settled.
+//!
+//! Strategies only pick among free positions, they never touch the database.
+//! The default strategy can be selected with the environment variable `VILLAGE_PLACEMENT`.
+
+use paddlers_shared_lib::game_mechanics::map::map_distance;
+use paddlers_shared_lib::prelude::*;
+use std::cmp::Reverse;
+use std::env;
+
+/// Villages within this distance are considered neighbours
+const NEIGHBOURHOOD_RADIUS: f32 = 5.0;
+
+/// Free position on a stream where a village could be settled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Candidate {
+    pub stream_id: i64,
+    pub x: f32,
+    pub y: f32,
+}
+
+pub(crate) trait PlacementStrategy {
+    /// Picks one of the candidates, which are sorted from left to right.
+    /// `villages` are all villages that already exist in the same world.
+    fn choose(&self, candidates: &[Candidate], villages: &[Village]) -> Option<Candidate>;
+}
+
+/// Takes the leftmost free position, new players settle right next to those registered before them
+pub(crate) struct FirstFree;
+/// Prefers positions with few player villages around, such that players do not end up clustered
+pub(crate) struct SpreadByDensity;
+/// Prefers positions with many anarchist villages around, which are easy targets for beginners
+pub(crate) struct NearAnarchists;
+/// Settles as close as possible to the villages of a friend who invited the player
+pub(crate) struct NearFriend(pub PlayerKey);
+
+impl PlacementStrategy for FirstFree {
+    fn choose(&self, candidates: &[Candidate], _villages: &[Village]) -> Option<Candidate> {
+        candidates.first().copied()
+    }
+}
+
+impl PlacementStrategy for SpreadByDensity {
+    fn choose(&self, candidates: &[Candidate], villages: &[Village]) -> Option<Candidate> {
+        candidates
+            .iter()
+            .min_by_key(|c| neighbours(c, villages, |v| v.player_id.is_some()))
+            .copied()
+    }
+}
+
+impl PlacementStrategy for NearAnarchists {
+    fn choose(&self, candidates: &[Candidate], villages: &[Village]) -> Option<Candidate> {
+        candidates
+            .iter()
+            .min_by_key(|c| Reverse(neighbours(c, villages, |v| v.player_id.is_none())))
+            .copied()
+    }
+}
+
+impl PlacementStrategy for NearFriend {
+    fn choose(&self, candidates: &[Candidate], villages: &[Village]) -> Option<Candidate> {
+        let friend_villages: Vec<&Village> = villages
+            .iter()
+            .filter(|v| v.owner() == Some(self.0))
+            .collect();
+        if friend_villages.is_empty() {
+            return FirstFree.choose(candidates, villages);
+        }
+        let distance_to_friend = |c: &Candidate| {
+            friend_villages
+                .iter()
+                .map(|v| map_distance((c.x, c.y), (v.x, v.y)))
+                .fold(std::f32::MAX, f32::min)
+        };
+        candidates
+            .iter()
+            .min_by(|a, b| {
+                distance_to_friend(a)
+                    .partial_cmp(&distance_to_friend(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .copied()
+    }
+}
+
+fn neighbours(c: &Candidate, villages: &[Village], filter: impl Fn(&Village) -> bool) -> usize {
+    villages
+        .iter()
+        .filter(|v| filter(v))
+        .filter(|v| map_distance((c.x, c.y), (v.x, v.y)) <= NEIGHBOURHOOD_RADIUS)
+        .count()
+}
+
+/// Strategy used for players without invitation, read from `VILLAGE_PLACEMENT`
+pub(crate) fn default_strategy() -> Box<dyn PlacementStrategy> {
+    match env::var("VILLAGE_PLACEMENT").as_ref().map(String::as_str) {
+        Ok("first") => Box::new(FirstFree),
+        Ok("anarchists") => Box::new(NearAnarchists),
+        Ok("spread") | Err(_) => Box::new(SpreadByDensity),
+        Ok(other) => {
+            eprintln!("Unknown village placement '{}', spreading villages", other);
+            Box::new(SpreadByDensity)
+        }
+    }
+}
+
+#[cfg(test)]
+mod village_placement_test;
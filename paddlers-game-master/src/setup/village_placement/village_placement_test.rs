@@ -0,0 +1,141 @@
+use super::*;
+use crate::setup::map_generation::NewMap;
+use crate::setup::village_creation::free_positions;
+
+const STREAM_PAIRS: usize = 20;
+const ANARCHISTS: usize = 20;
+const PLAYERS: usize = 15;
+
+/// Generates a small map with anarchist villages on the leftmost streams, like a fresh world
+fn generated_world() -> (Vec<Stream>, Vec<Village>) {
+    let map = NewMap::generate(WorldKey(1), 1, 0, STREAM_PAIRS);
+    let streams: Vec<Stream> = map
+        .streams
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| Stream {
+            id: i as i64 + 1,
+            start_x: s.start_x,
+            control_points: s.control_points,
+            world_id: 1,
+        })
+        .collect();
+    let mut villages = vec![];
+    for s in streams.iter().take(ANARCHISTS) {
+        if let Some(c) = free_positions(std::slice::from_ref(s), &villages).first() {
+            villages.push(village(*c, None, villages.len()));
+        }
+    }
+    (streams, villages)
+}
+
+fn village(c: Candidate, owner: Option<PlayerKey>, index: usize) -> Village {
+    Village {
+        id: index as i64 + 1,
+        x: c.x,
+        y: c.y,
+        stream_id: c.stream_id,
+        player_id: owner.map(|p| p.num()),
+        faith: 100,
+        world_id: 1,
+    }
+}
+
+/// Settles players one after another, returns all villages in the world
+fn settle(
+    strategy: &dyn PlacementStrategy,
+    streams: &[Stream],
+    mut villages: Vec<Village>,
+) -> Vec<Village> {
+    for p in 0..PLAYERS {
+        let candidates = free_positions(streams, &villages);
+        let c = strategy
+            .choose(&candidates, &villages)
+            .expect("No space on map");
+        let player = PlayerKey(p as i64 + 1);
+        villages.push(village(c, Some(player), villages.len()));
+    }
+    villages
+}
+
+/// For each player village, how many villages of the given kind are in its neighbourhood
+fn neighbourhood(villages: &[Village], filter: impl Fn(&Village) -> bool + Copy) -> Vec<usize> {
+    villages
+        .iter()
+        .filter(|v| v.player_id.is_some())
+        .map(|v| {
+            let c = Candidate {
+                stream_id: v.stream_id,
+                x: v.x,
+                y: v.y,
+            };
+            let others: Vec<Village> = villages.iter().filter(|o| o.id != v.id).copied().collect();
+            neighbours(&c, &others, filter)
+        })
+        .collect()
+}
+
+fn player_neighbours(villages: &[Village]) -> Vec<usize> {
+    neighbourhood(villages, |v| v.player_id.is_some())
+}
+fn anarchist_neighbours(villages: &[Village]) -> Vec<usize> {
+    neighbourhood(villages, |v| v.player_id.is_none())
+}
+
+#[test]
+fn spread_players_have_fewer_neighbours() {
+    let (streams, anarchists) = generated_world();
+    let first = player_neighbours(&settle(&FirstFree, &streams, anarchists.clone()));
+    let spread = player_neighbours(&settle(&SpreadByDensity, &streams, anarchists));
+    assert_eq!(first.len(), PLAYERS);
+    assert_eq!(spread.len(), PLAYERS);
+    assert!(
+        spread.iter().max() < first.iter().max(),
+        "Spread {:?}, first free {:?}",
+        spread,
+        first
+    );
+    assert!(spread.iter().sum::<usize>() < first.iter().sum::<usize>());
+}
+
+#[test]
+fn beginners_settle_near_anarchists() {
+    let (streams, anarchists) = generated_world();
+    let near = anarchist_neighbours(&settle(&NearAnarchists, &streams, anarchists.clone()));
+    let spread = anarchist_neighbours(&settle(&SpreadByDensity, &streams, anarchists));
+    assert_eq!(near.len(), PLAYERS);
+    assert_eq!(spread.len(), PLAYERS);
+    assert!(near.iter().all(|n| *n > 0), "Near anarchists {:?}", near);
+    assert!(
+        near.iter().sum::<usize>() > spread.iter().sum::<usize>(),
+        "Near anarchists {:?}, spread {:?}",
+        near,
+        spread
+    );
+}
+
+#[test]
+fn invited_players_settle_near_friend() {
+    let (streams, anarchists) = generated_world();
+    let villages = settle(&SpreadByDensity, &streams, anarchists);
+    for friend in &[PlayerKey(1), PlayerKey(PLAYERS as i64)] {
+        let friend_village = villages
+            .iter()
+            .find(|v| v.owner() == Some(*friend))
+            .unwrap();
+        let candidates = free_positions(&streams, &villages);
+        let c = NearFriend(*friend).choose(&candidates, &villages).unwrap();
+        let distance = map_distance((c.x, c.y), (friend_village.x, friend_village.y));
+        assert!(distance <= NEIGHBOURHOOD_RADIUS, "Distance {}", distance);
+    }
+}
+
+#[test]
+fn unknown_friend_falls_back_to_first_free() {
+    let (streams, anarchists) = generated_world();
+    let candidates = free_positions(&streams, &anarchists);
+    assert_eq!(
+        NearFriend(PlayerKey(99)).choose(&candidates, &anarchists),
+        candidates.first().copied()
+    );
+}
@@ -1,6 +1,8 @@
 use crate::db::DB;
+use chrono::NaiveDateTime;
 use paddlers_shared_lib::game_mechanics::forestry::tree_size;
 use paddlers_shared_lib::game_mechanics::town::*;
+use paddlers_shared_lib::game_mechanics::worker::defender_aura_strength;
 use paddlers_shared_lib::prelude::*;
 
 /// Game master state of a town.
@@ -12,16 +14,31 @@ pub struct TownView {
     pub map: TownMap,
     pub state: TownState<i64>,
     pub buildings_with_aura: Vec<Building>,
+    /// Buildings that fire a limited number of shots per cycle
+    pub shooting_buildings: Vec<Building>,
+    pub defenders: Vec<Defender>,
+}
+
+/// A worker standing in a defend position, acting like a small aura building
+pub struct Defender {
+    pub worker_id: i64,
+    pub x: i32,
+    pub y: i32,
+    pub strength: i32,
+    pub since: NaiveDateTime,
+    /// Start of the next task of the worker, if one has been scheduled already
+    pub until: Option<NaiveDateTime>,
 }
 
 impl TownView {
     pub(crate) fn load_village(db: &DB, village: VillageKey) -> Self {
         let mut map = TownMap::new(TownLayout::Basic);
         let mut state = TownState::new();
-        let now = chrono::Utc::now().naive_utc();
+        let now = db.now();
 
         let buildings = db.buildings(village);
         let mut buildings_with_aura = vec![];
+        let mut shooting_buildings = vec![];
         for b in buildings {
             let idx = (b.x as usize, b.y as usize);
             map[idx] = TownTileType::BUILDING(b.building_type);
@@ -38,20 +55,34 @@ impl TownView {
                 _ => 0,
             };
             state.forest_size += forest_supply;
-            if b.attacks_per_cycle.is_none()
-                && b.building_range.is_some()
-                && b.attack_power.is_some()
-            {
-                buildings_with_aura.push(b);
+            if b.building_range.is_some() && b.attack_power.is_some() {
+                if b.attacks_per_cycle.is_none() {
+                    buildings_with_aura.push(b);
+                } else {
+                    shooting_buildings.push(b);
+                }
             }
         }
 
         let workers = db.workers(village);
+        let mut defenders = vec![];
         for worker in workers {
             if let Some(task) = db.current_task(worker.key()) {
                 state
                     .register_task_begin(task.task_type)
                     .expect("Current DB state invalid");
+                if task.task_type == TaskType::Defend {
+                    defenders.push(Defender {
+                        worker_id: worker.id,
+                        x: task.x,
+                        y: task.y,
+                        strength: defender_aura_strength(worker.level),
+                        since: task.start_time,
+                        until: db
+                            .earliest_future_task(worker.key())
+                            .map(|next| next.start_time),
+                    });
+                }
             } else {
                 println!("Warning: worker without task: {:?}", worker);
             }
@@ -61,6 +92,8 @@ impl TownView {
             map,
             state,
             buildings_with_aura,
+            shooting_buildings,
+            defenders,
         }
     }
 
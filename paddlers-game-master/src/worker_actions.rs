@@ -7,6 +7,7 @@ mod worker_abilities;
 mod worker_updates;
 
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use crate::game_master::event::*;
 use crate::game_master::town_worker::*;
 use crate::town_view::*;
@@ -30,14 +31,14 @@ pub struct ValidatedTaskList {
     pub update_tasks: Vec<Task>,
     pub village_id: VillageKey,
 }
-pub(crate) fn validate_task_list(
-    db: &DB,
-    tl: &TaskList,
-) -> Result<ValidatedTaskList, Box<dyn std::error::Error>> {
+pub(crate) fn validate_task_list(db: &DB, tl: &TaskList) -> GameMasterResult<ValidatedTaskList> {
     let worker_id = tl.worker_id;
 
     // Load relevant data into memory
-    let mut worker = db.worker_priv(worker_id).ok_or("Worker does not exist")?;
+    let mut worker = db
+        .worker_priv(worker_id)
+        .ok_or(GameMasterError::NotFound("Worker"))?;
+    db.collect_worker_income(&mut worker);
     let village_id = VillageKey(worker.home);
     let mut town = TownView::load_village(db, village_id);
 
@@ -45,8 +46,8 @@ pub(crate) fn validate_task_list(
     let mut current_task = db
         .current_task(worker.key())
         .expect("Must have a current task");
-    let mut timestamp =
-        interrupt_task(&mut current_task, &worker).ok_or("Cannot interrupt current task.")?;
+    let mut timestamp = interrupt_task(&mut current_task, &worker, db.now())
+        .ok_or_else(|| GameMasterError::InvalidTask("Cannot interrupt current task.".to_owned()))?;
     worker.x = current_task.x;
     worker.y = current_task.y;
 
@@ -56,7 +57,8 @@ pub(crate) fn validate_task_list(
     for task in tl.tasks.iter() {
         // Validate target hobo exists if there is one
         if let Some(target_id) = task.target {
-            db.hobo(HoboKey(target_id)).ok_or("No such hobo id")?;
+            db.hobo(HoboKey(target_id))
+                .ok_or(GameMasterError::NotFound("Hobo"))?;
         }
 
         validate_ability(db, task.task_type, worker_id, timestamp)?;
@@ -105,19 +107,20 @@ pub(crate) fn replace_worker_tasks(
     }
 }
 
-fn interrupt_task(current_task: &mut Task, worker: &Worker) -> Option<NaiveDateTime> {
+fn interrupt_task(
+    current_task: &mut Task,
+    worker: &Worker,
+    now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
     match current_task.task_type {
         TaskType::Idle
         | TaskType::ChopTree
         | TaskType::Defend
         | TaskType::GatherSticks
-        | TaskType::CollectReward => {
-            let now = chrono::Utc::now().naive_utc();
-            Some(now)
-        }
+        | TaskType::CollectReward => Some(now),
         TaskType::Walk => {
             let speed = unit_speed_to_worker_tiles_per_second(worker.speed) as f64;
-            let time_so_far: Duration = Utc::now().naive_utc() - current_task.start_time;
+            let time_so_far: Duration = now - current_task.start_time;
             let steps = (speed * time_so_far.num_microseconds().unwrap() as f64 / 1_000_000.0)
                 .ceil() as i32;
             let total_time = steps as f64 / speed;
@@ -169,12 +172,14 @@ pub(crate) fn finish_task(
     task_id: TaskKey,
     task: Option<Task>,
     town: Option<&mut TownView>,
-) -> Result<Option<(Event, DateTime<Utc>)>, Box<dyn std::error::Error>> {
+) -> GameMasterResult<Option<(Event, DateTime<Utc>)>> {
     let task = task.or_else(|| db.task(task_id));
     if let Some(task) = task {
-        let mut worker = db
-            .worker_priv(task.worker())
-            .ok_or("Task references non-existing worker")?;
+        let mut worker = db.worker_priv(task.worker()).ok_or_else(|| {
+            GameMasterError::Internal("Task references non-existing worker".to_owned())
+        })?;
+        // Income of the finished task must be stored before the work flag is reset
+        db.collect_worker_income(&mut worker);
         if let Some(town) = town {
             crate::worker_actions::simulate_finish_task(&task, town, &mut worker)?;
             apply_task_to_db(db, &task, &mut worker)?;
@@ -195,18 +200,23 @@ pub(crate) fn finish_task(
     }
 }
 
-fn apply_task_to_db(db: &DB, task: &Task, worker: &mut Worker) -> Result<(), String> {
+fn apply_task_to_db(db: &DB, task: &Task, worker: &mut Worker) -> GameMasterResult<()> {
     match task.task_type {
         TaskType::WelcomeAbility => {
             let a = AbilityType::Welcome;
-            let (attribute, strength) = a.apply();
-            let ne = NewEffect {
-                hobo_id: task.target().ok_or("Ability must have a target")?.num(),
-                attribute,
-                strength: Some(strength),
-                start_time: None, // default = now
-            };
-            db.insert_effect(&ne);
+            let target = task.target().ok_or_else(|| {
+                GameMasterError::InvalidTask("Ability must have a target".to_owned())
+            })?;
+            for (attribute, strength) in a.apply() {
+                let ne = NewEffect {
+                    hobo_id: target.num(),
+                    attribute: *attribute,
+                    strength: Some(*strength),
+                    // Effects start when the ability is cast, which matters for speed effects
+                    start_time: Some(task.start_time),
+                };
+                db.insert_effect(&ne);
+            }
             db.update_ability_used_timestamp(WorkerKey(worker.id), a);
             *worker.mana.as_mut().unwrap() -= AbilityType::Welcome.mana_cost();
         }
@@ -218,14 +228,17 @@ fn apply_task_to_db(db: &DB, task: &Task, worker: &mut Worker) -> Result<(), Str
                         db.delete_building(&building);
                     }
                     None => {
-                        return Err(format!(
+                        return Err(GameMasterError::InvalidTask(format!(
                             "Tried to collect {} as reward",
                             building.building_type
-                        ));
+                        )));
                     }
                 }
             } else {
-                return Err(format!("No reward to collect at {},{}", task.x, task.y));
+                return Err(GameMasterError::InvalidTask(format!(
+                    "No reward to collect at {},{}",
+                    task.x, task.y
+                )));
             }
         }
         _ => { /* NOP */ }
@@ -240,7 +253,7 @@ fn simulate_finish_task<T: WorkerAction>(
     task: &T,
     town: &mut TownView,
     worker: &mut Worker,
-) -> Result<Duration, String> {
+) -> GameMasterResult<Duration> {
     match task.task_type() {
         TaskType::Idle => Ok(Duration::milliseconds(0)),
         TaskType::Walk => Ok(worker_walk(
@@ -249,9 +262,7 @@ fn simulate_finish_task<T: WorkerAction>(
             (task.x() as usize, task.y() as usize),
         )?),
         TaskType::GatherSticks | TaskType::ChopTree => {
-            town.state
-                .register_task_end(*task.task_type())
-                .map_err(|e| e.to_string())?;
+            town.state.register_task_end(*task.task_type())?;
             worker_out_of_building(town, worker, (task.x() as usize, task.y() as usize))
         }
         TaskType::WelcomeAbility => {
@@ -266,7 +277,7 @@ fn simulate_finish_task<T: WorkerAction>(
             town.state.remove(&index);
             Ok(Duration::milliseconds(0))
         }
-        TaskType::Defend => Err("Task not implemented".to_owned()),
+        TaskType::Defend => Ok(Duration::milliseconds(0)),
     }
 }
 /// (Try to) apply changes to village state that happen when a worker starts a given task.
@@ -275,13 +286,11 @@ fn simulate_begin_task<T: WorkerAction>(
     task: &T,
     town: &mut TownView,
     worker: &mut Worker,
-) -> Result<(), String> {
+) -> GameMasterResult<()> {
     match task.task_type() {
         TaskType::Idle | TaskType::Walk | TaskType::CollectReward => Ok(()),
         TaskType::GatherSticks | TaskType::ChopTree => {
-            town.state
-                .register_task_begin(*task.task_type())
-                .map_err(|e| e.to_string())?;
+            town.state.register_task_begin(*task.task_type())?;
             worker_into_building(town, worker, (task.x() as usize, task.y() as usize))
         }
         TaskType::WelcomeAbility => {
@@ -291,13 +300,24 @@ fn simulate_begin_task<T: WorkerAction>(
                     *mana = *mana - cost;
                     Ok(())
                 } else {
-                    Err("Not enough mana".to_owned())
+                    Err(GameMasterError::NotEnoughMana)
                 }
             } else {
-                Err("Worker has no mana but tries to use welcome ability".to_owned())
+                Err(GameMasterError::InvalidTask(
+                    "Worker has no mana but tries to use welcome ability".to_owned(),
+                ))
+            }
+        }
+        TaskType::Defend => {
+            if town
+                .map
+                .can_defend_at((task.x() as usize, task.y() as usize))
+            {
+                Ok(())
+            } else {
+                Err(GameMasterError::CannotDefendHere)
             }
         }
-        TaskType::Defend => Err("Task not implemented".to_owned()),
     }
 }
 
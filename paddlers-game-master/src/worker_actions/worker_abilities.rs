@@ -1,4 +1,5 @@
 use crate::db::DB;
+use crate::error::{GameMasterError, GameMasterResult};
 use crate::town_view::*;
 use chrono::Duration;
 use paddlers_shared_lib::game_mechanics::{town::*, worker::*};
@@ -8,10 +9,10 @@ pub fn worker_walk(
     town: &TownView,
     worker: &mut Worker,
     to: TileIndex,
-) -> Result<Duration, String> {
+) -> GameMasterResult<Duration> {
     let from = (worker.x as usize, worker.y as usize);
     if !town.path_walkable(from, to) {
-        return Err(format!("Cannot walk this way. {:?} -> {:?}", from, to));
+        return Err(GameMasterError::PathBlocked);
     }
     let speed = unit_speed_to_worker_tiles_per_second(worker.speed);
     let distance = distance2(from, to).sqrt();
@@ -25,18 +26,24 @@ pub fn worker_out_of_building(
     town: &mut TownView,
     _worker: &mut Worker,
     to: TileIndex,
-) -> Result<Duration, String> {
-    let tile_state = town.state.get_mut(&to).ok_or("No building found")?;
-    tile_state.try_remove_entity().map_err(|e| e.to_string())?;
+) -> GameMasterResult<Duration> {
+    let tile_state = town
+        .state
+        .get_mut(&to)
+        .ok_or(GameMasterError::NotFound("Building"))?;
+    tile_state.try_remove_entity()?;
     Ok(Duration::milliseconds(0))
 }
 pub fn worker_into_building(
     town: &mut TownView,
     _worker: &mut Worker,
     to: TileIndex,
-) -> Result<(), String> {
-    let tile_state = town.state.get_mut(&to).ok_or("No building found")?;
-    tile_state.try_add_entity().map_err(|e| e.to_string())?;
+) -> GameMasterResult<()> {
+    let tile_state = town
+        .state
+        .get_mut(&to)
+        .ok_or(GameMasterError::NotFound("Building"))?;
+    tile_state.try_add_entity()?;
     Ok(())
 }
 pub(super) fn validate_ability(
@@ -44,7 +51,7 @@ pub(super) fn validate_ability(
     task_type: TaskType,
     worker_id: WorkerKey,
     now: chrono::NaiveDateTime,
-) -> Result<(), String> {
+) -> GameMasterResult<()> {
     if let Some(ability_type) = AbilityType::from_task(&task_type) {
         // TODO: Range checks
         // let range = ability_type.range();
@@ -54,11 +61,13 @@ pub(super) fn validate_ability(
             if let Some(last_used) = a.last_used {
                 let free_to_use = last_used + ability_type.cooldown();
                 if free_to_use > now {
-                    return Err("Cooldown not ready".to_owned());
+                    return Err(GameMasterError::AbilityNotReady);
                 }
             }
         } else {
-            return Err("Worker does not have this ability".to_owned());
+            return Err(GameMasterError::InvalidTask(
+                "Worker does not have this ability".to_owned(),
+            ));
         }
     }
     Ok(())
@@ -0,0 +1,104 @@
+//! Blocking client for the game-master REST routes and the db-interface GraphQL endpoint.
+//!
+//! Every request is timed and reported as a `Sample`, no matter if it succeeded.
+
+use hyper::header::{Authorization, ContentType};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT_SECONDS: u64 = 30;
+
+/// Measurement of a single request
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub endpoint: &'static str,
+    pub latency: Duration,
+    pub ok: bool,
+}
+
+pub struct Client {
+    http: hyper::Client,
+    game_master_url: String,
+    graphql_url: String,
+    token: String,
+    samples: Sender<Sample>,
+}
+
+impl Client {
+    pub fn new(
+        game_master_url: &str,
+        graphql_url: &str,
+        token: String,
+        samples: Sender<Sample>,
+    ) -> Self {
+        let mut http = hyper::Client::new();
+        http.set_read_timeout(Some(Duration::from_secs(REQUEST_TIMEOUT_SECONDS)));
+        http.set_write_timeout(Some(Duration::from_secs(REQUEST_TIMEOUT_SECONDS)));
+        Client {
+            http,
+            game_master_url: game_master_url.to_owned(),
+            graphql_url: graphql_url.to_owned(),
+            token,
+            samples,
+        }
+    }
+    /// Posts JSON to a game-master route, e.g. `player/create`. Returns true on success.
+    pub fn rest<T: Serialize>(&self, route: &'static str, body: &T) -> bool {
+        let url = format!("{}{}", self.game_master_url, route);
+        let body = serde_json::to_string(body).expect("Serializing request");
+        let start = Instant::now();
+        let ok = self.post(&url, &body).is_ok();
+        self.record(route, start, ok);
+        ok
+    }
+    /// Sends a GraphQL query and returns the `data` of the response.
+    /// Responses with errors count as failed, like they do in the frontend.
+    pub fn graphql(&self, operation: &'static str, query: &str, variables: Value) -> Option<Value> {
+        let body = json!({
+            "query": query,
+            "variables": variables,
+            "operationName": operation,
+        });
+        let start = Instant::now();
+        let data = self
+            .post(&self.graphql_url, &body.to_string())
+            .ok()
+            .and_then(|response| serde_json::from_str::<Value>(&response).ok())
+            .filter(|response| response.get("errors").is_none())
+            .and_then(|mut response| response.get_mut("data").map(Value::take))
+            .filter(|data| !data.is_null());
+        self.record(operation, start, data.is_some());
+        data
+    }
+    fn post(&self, url: &str, body: &str) -> Result<String, String> {
+        let mut response = self
+            .http
+            .post(url)
+            .header(ContentType::json())
+            .header(Authorization(self.token.clone()))
+            .body(body)
+            .send()
+            .map_err(|e| e.to_string())?;
+        let mut text = String::new();
+        response
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+        if response.status.is_success() {
+            Ok(text)
+        } else {
+            Err(format!("{}: {}", response.status, text))
+        }
+    }
+    fn record(&self, endpoint: &'static str, start: Instant, ok: bool) {
+        let sample = Sample {
+            endpoint,
+            latency: start.elapsed(),
+            ok,
+        };
+        // The receiver only hangs up after all sessions are done
+        self.samples.send(sample).expect("Reporting sample");
+    }
+}
@@ -0,0 +1,156 @@
+//! Headless load-testing client
+//!
+//! Signs up synthetic players and plays scripted sessions against a running deployment,
+//! using the game-master REST routes and the db-interface GraphQL endpoint like the frontend does.
+//! At the end, latencies and error rates are reported per endpoint.
+//!
+//! Usage: `paddlers-load-test --key <private_key.der> [--players N] [--minutes M] [--ramp-up-seconds S]
+//!     [--game-master URL] [--graphql URL] [--issuer URL] [--world ID] [--seed S]`
+//!
+//! Tokens are minted locally and signed with the given RSA private key (DER).
+//! The services only accept them if they have been started with the matching public key,
+//! which means this can only run against test deployments.
+//! Only plain HTTP is supported.
+
+mod client;
+mod report;
+mod session;
+mod token;
+
+use client::{Client, Sample};
+use paddlers_shared_lib::api::keys::WorldKey;
+use paddlers_shared_lib::config::Config;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use report::Report;
+use session::Session;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct Options {
+    key: String,
+    players: usize,
+    minutes: u64,
+    ramp_up_seconds: u64,
+    game_master_url: String,
+    graphql_url: String,
+    issuer: String,
+    world: Option<WorldKey>,
+    seed: u64,
+}
+
+fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            eprintln!("Usage: paddlers-load-test --key <private_key.der> [--players N] [--minutes M] [--ramp-up-seconds S] [--game-master URL] [--graphql URL] [--issuer URL] [--world ID] [--seed S]");
+            std::process::exit(1);
+        }
+    };
+    let private_key = std::fs::read(&options.key).unwrap_or_else(|e| {
+        eprintln!("Cannot read key {}: {}", options.key, e);
+        std::process::exit(1);
+    });
+
+    // Players are generated upfront, such that the same seed leads to the same players
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let (sender, receiver) = channel::<Sample>();
+    let mut sessions = vec![];
+    for i in 0..options.players {
+        let uuid = uuid::Uuid::from_u128(rng.gen());
+        let name = format!("loadtest-{}", i);
+        let token =
+            token::mint_token(uuid, &name, &options.issuer, &private_key).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+        let client = Client::new(
+            &options.game_master_url,
+            &options.graphql_url,
+            token,
+            sender.clone(),
+        );
+        let delay = Duration::from_millis(
+            options.ramp_up_seconds * 1000 * i as u64 / options.players as u64,
+        );
+        let play_time = Duration::from_secs(options.minutes * 60);
+        let world = options.world;
+        sessions.push(thread::spawn(move || {
+            thread::sleep(delay);
+            let until = Instant::now() + play_time;
+            match Session::start(client, name, world) {
+                Some(session) => {
+                    session.play(until);
+                    true
+                }
+                None => false,
+            }
+        }));
+    }
+    // All senders are owned by clients, receiving ends when the last session is over
+    drop(sender);
+
+    let mut report = Report::default();
+    for sample in receiver {
+        report.add(sample);
+    }
+    let failed = sessions
+        .into_iter()
+        .map(|s| s.join().unwrap_or(false))
+        .filter(|started| !started)
+        .count();
+
+    print!("{}", report.to_table());
+    println!(
+        "{} of {} sessions could not be started",
+        failed, options.players
+    );
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut args = std::env::args().skip(1);
+        let defaults = Config::default();
+        let mut options = Options {
+            key: String::new(),
+            players: 10,
+            minutes: 5,
+            ramp_up_seconds: 30,
+            game_master_url: "http://localhost/api/".to_owned(),
+            graphql_url: "http://localhost/graphql/".to_owned(),
+            issuer: defaults.keycloak_issuer,
+            world: None,
+            seed: 0,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--key" => options.key = value()?,
+                "--players" => options.players = parse(&value()?)?,
+                "--minutes" => options.minutes = parse(&value()?)?,
+                "--ramp-up-seconds" => options.ramp_up_seconds = parse(&value()?)?,
+                "--game-master" => options.game_master_url = value()?,
+                "--graphql" => options.graphql_url = value()?,
+                "--issuer" => options.issuer = value()?,
+                "--world" => options.world = Some(WorldKey(parse(&value()?)?)),
+                "--seed" => options.seed = parse(&value()?)?,
+                other => return Err(format!("Unknown option {}", other)),
+            }
+        }
+        if options.key.is_empty() {
+            return Err("No key given".to_owned());
+        }
+        if options.players == 0 {
+            return Err("At least one player is required".to_owned());
+        }
+        Ok(options)
+    }
+}
+
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Invalid number {}", s))
+}
@@ -0,0 +1,62 @@
+//! Aggregates samples per endpoint into latency percentiles and error rates.
+
+use crate::client::Sample;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct Report {
+    endpoints: BTreeMap<&'static str, EndpointStats>,
+}
+
+#[derive(Default)]
+struct EndpointStats {
+    latencies: Vec<Duration>,
+    errors: usize,
+}
+
+impl Report {
+    pub fn add(&mut self, sample: Sample) {
+        let stats = self.endpoints.entry(sample.endpoint).or_default();
+        stats.latencies.push(sample.latency);
+        if !sample.ok {
+            stats.errors += 1;
+        }
+    }
+    /// One row per endpoint, latencies in milliseconds
+    pub fn to_table(&mut self) -> String {
+        let mut table = format!(
+            "{:<28} {:>8} {:>8} {:>7} {:>8} {:>8} {:>8} {:>8}\n",
+            "endpoint", "requests", "errors", "error%", "p50", "p90", "p99", "max"
+        );
+        for (endpoint, stats) in &mut self.endpoints {
+            stats.latencies.sort();
+            let n = stats.latencies.len();
+            let ms = |p: f64| percentile(&stats.latencies, p).as_millis();
+            table += &format!(
+                "{:<28} {:>8} {:>8} {:>7.2} {:>8} {:>8} {:>8} {:>8}\n",
+                endpoint,
+                n,
+                stats.errors,
+                100.0 * stats.errors as f64 / n as f64,
+                ms(50.0),
+                ms(90.0),
+                ms(99.0),
+                ms(100.0),
+            );
+        }
+        table
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::default();
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.max(1).min(sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod report_test;
@@ -0,0 +1,35 @@
+use super::*;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn nearest_rank_percentiles() {
+    let sorted: Vec<Duration> = (1..=10).map(ms).collect();
+    assert_eq!(percentile(&sorted, 50.0), ms(5));
+    assert_eq!(percentile(&sorted, 90.0), ms(9));
+    assert_eq!(percentile(&sorted, 99.0), ms(10));
+    assert_eq!(percentile(&sorted, 100.0), ms(10));
+    assert_eq!(percentile(&sorted, 0.0), ms(1));
+    assert_eq!(percentile(&[], 50.0), ms(0));
+}
+
+#[test]
+fn errors_are_counted_per_endpoint() {
+    let mut report = Report::default();
+    for (endpoint, ok) in &[
+        ("PlayerQuery", true),
+        ("PlayerQuery", false),
+        ("stats", true),
+    ] {
+        report.add(Sample {
+            endpoint,
+            latency: ms(1),
+            ok: *ok,
+        });
+    }
+    assert_eq!(report.endpoints["PlayerQuery"].errors, 1);
+    assert_eq!(report.endpoints["PlayerQuery"].latencies.len(), 2);
+    assert_eq!(report.endpoints["stats"].errors, 0);
+}
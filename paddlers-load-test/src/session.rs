@@ -0,0 +1,180 @@
+//! Scripted session of one synthetic player.
+//!
+//! The requests mimic the frontend: A complete load after login, then polling every 5 seconds like `NetState::work`.
+//! The queries are the same documents the frontend sends.
+
+use crate::client::Client;
+use paddlers_shared_lib::api::keys::WorldKey;
+use paddlers_shared_lib::api::statistics::{BrowserInfo, FrontendRuntimeStatistics};
+use paddlers_shared_lib::api::PlayerInitData;
+use serde_json::{json, Value};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const ATTACKS_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/attacks_query.graphql");
+const BUILDINGS_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/buildings_query.graphql");
+const HOBOS_QUERY: &str = include_str!("../../paddlers-frontend/api/queries/hobos_query.graphql");
+const LEADERBOARD_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/leaderboard_query.graphql");
+const MAP_QUERY: &str = include_str!("../../paddlers-frontend/api/queries/map_query.graphql");
+const PLAYER_QUERY: &str = include_str!("../../paddlers-frontend/api/queries/player_query.graphql");
+const PLAYER_VILLAGES_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/player_villages_query.graphql");
+const REPORTS_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/reports_query.graphql");
+const VILLAGE_UNITS_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/village_units_query.graphql");
+const VOLATILE_VILLAGE_INFO_QUERY: &str =
+    include_str!("../../paddlers-frontend/api/queries/volatile_village_info_query.graphql");
+
+/// Same as `interval_ms` of the frontend's network state
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Same as the interval of the frontend's statistician
+const STATISTICS_INTERVAL: Duration = Duration::from_secs(10);
+/// Columns of the map loaded at once, like the frontend's `LOAD_STEP`
+const MAP_LOAD_STEP: i64 = 10;
+
+pub struct Session {
+    client: Client,
+    village_id: i64,
+    attacks_cursor: Option<String>,
+    reports_cursor: Option<String>,
+    start: Instant,
+}
+
+impl Session {
+    /// Signs up a new player and loads everything the frontend loads after login.
+    /// Returns None if the player has no village to play with.
+    pub fn start(client: Client, display_name: String, world: Option<WorldKey>) -> Option<Self> {
+        let start = Instant::now();
+        let init = PlayerInitData {
+            display_name,
+            world,
+            invited_by: None,
+        };
+        client.rest("player/create", &init);
+        let villages = client.graphql("PlayerVillagesQuery", PLAYER_VILLAGES_QUERY, json!({}))?;
+        let village_id = villages["player"]["villages"][0]["id"].as_i64()?;
+
+        let mut session = Session {
+            client,
+            village_id,
+            attacks_cursor: None,
+            reports_cursor: None,
+            start,
+        };
+        session.complete_reload();
+        Some(session)
+    }
+    /// Keeps polling until the deadline is reached
+    pub fn play(mut self, until: Instant) {
+        let mut last_statistics = Instant::now();
+        while Instant::now() + POLL_INTERVAL < until {
+            sleep(POLL_INTERVAL);
+            self.poll();
+            if last_statistics.elapsed() >= STATISTICS_INTERVAL {
+                last_statistics = Instant::now();
+                self.send_statistics();
+            }
+        }
+    }
+    /// Same requests as `request_client_state` and an initial map read
+    fn complete_reload(&mut self) {
+        let village = json!({ "village_id": self.village_id });
+        self.client
+            .graphql("BuildingsQuery", BUILDINGS_QUERY, village.clone());
+        self.client
+            .graphql("VillageUnitsQuery", VILLAGE_UNITS_QUERY, village.clone());
+        self.client.graphql("HobosQuery", HOBOS_QUERY, village);
+        self.client
+            .graphql("LeaderboardQuery", LEADERBOARD_QUERY, json!({}));
+        self.attacks();
+        self.resources();
+        self.player();
+        self.client.graphql(
+            "MapQuery",
+            MAP_QUERY,
+            json!({ "lowX": 0, "highX": MAP_LOAD_STEP }),
+        );
+    }
+    /// Same requests as `NetState::work`
+    fn poll(&mut self) {
+        self.attacks();
+        self.reports();
+        self.resources();
+        self.player();
+    }
+    /// Loads pages until the last one, like the frontend does
+    fn attacks(&mut self) {
+        loop {
+            let vars = json!({ "village_id": self.village_id, "after": self.attacks_cursor });
+            let data = match self.client.graphql("AttacksQuery", ATTACKS_QUERY, vars) {
+                Some(data) => data,
+                None => return,
+            };
+            let connection = &data["village"]["attacks"];
+            if let Some(cursor) = end_cursor(connection) {
+                self.attacks_cursor = Some(cursor);
+            }
+            if !has_next_page(connection) {
+                return;
+            }
+        }
+    }
+    /// Loads pages until the last one, like the frontend does
+    fn reports(&mut self) {
+        loop {
+            let vars = json!({ "village_id": self.village_id, "after": self.reports_cursor });
+            let data = match self.client.graphql("ReportsQuery", REPORTS_QUERY, vars) {
+                Some(data) => data,
+                None => return,
+            };
+            let connection = &data["village"]["reports"];
+            if let Some(cursor) = end_cursor(connection) {
+                self.reports_cursor = Some(cursor);
+            }
+            if !has_next_page(connection) {
+                return;
+            }
+        }
+    }
+    fn resources(&self) {
+        let vars = json!({ "village_id": self.village_id });
+        self.client.graphql(
+            "VolatileVillageInfoQuery",
+            VOLATILE_VILLAGE_INFO_QUERY,
+            vars,
+        );
+    }
+    fn player(&self) {
+        self.client.graphql("PlayerQuery", PLAYER_QUERY, json!({}));
+    }
+    fn send_statistics(&self) {
+        let msg = FrontendRuntimeStatistics {
+            browser: BrowserInfo {
+                user_agent: "paddlers-load-test".to_owned(),
+                inner_width: 0,
+                inner_height: 0,
+                outer_width: 0,
+                outer_height: 0,
+            },
+            session_duration_s: self.start.elapsed().as_secs() as i64,
+            fps: 0.0,
+        };
+        self.client.rest("stats", &msg);
+    }
+}
+
+/// Cursor of the last node of a connection, None if the page is empty
+fn end_cursor(connection: &Value) -> Option<String> {
+    connection["pageInfo"]["endCursor"]
+        .as_str()
+        .map(str::to_owned)
+}
+fn has_next_page(connection: &Value) -> bool {
+    connection["pageInfo"]["hasNextPage"]
+        .as_bool()
+        .unwrap_or(false)
+}
@@ -0,0 +1,42 @@
+//! Locally minted tokens, signed like Keycloak signs them.
+//!
+//! The services only accept them if they verify tokens against the public key of the same key pair,
+//! so this is only possible on test deployments.
+
+use jsonwebtoken::{encode, Algorithm, Header};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Minted tokens outlive any load test
+const TOKEN_VALIDITY_SECONDS: u64 = 24 * 3600;
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    sub: String,
+    iss: &'a str,
+    aud: &'a str,
+    exp: u64,
+    preferred_username: &'a str,
+}
+
+/// Signs a token for a synthetic user with a DER-encoded RSA private key
+pub fn mint_token(
+    uuid: uuid::Uuid,
+    name: &str,
+    issuer: &str,
+    private_key: &[u8],
+) -> Result<String, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time before 1970")
+        .as_secs();
+    let claims = Claims {
+        sub: uuid.to_string(),
+        iss: issuer,
+        aud: "account",
+        exp: now + TOKEN_VALIDITY_SECONDS,
+        preferred_username: name,
+    };
+    encode(&Header::new(Algorithm::RS256), &claims, private_key)
+        .map_err(|e| format!("Minting token failed: {}", e))
+}
@@ -1,15 +1,22 @@
+pub mod admin;
 pub mod attacks;
 pub mod error;
 pub mod keys;
+pub mod notifications;
 pub mod reports;
 pub mod shop;
 pub mod statistics;
 pub mod story;
 pub mod tasks;
 
+use keys::WorldKey;
 use serde::*;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PlayerInitData {
     pub display_name: String,
+    /// The world to play in, a world with few players is picked if none is specified
+    pub world: Option<WorldKey>,
+    /// Invite code of a friend, the new village is placed close to the friend's villages.
+    pub invited_by: Option<String>,
 }
@@ -0,0 +1,63 @@
+//! Shared data for network transmission of the admin API.
+//!
+//! Only users with the admin role can use it, see `user_authentication::ADMIN_ROLE`.
+
+use crate::prelude::*;
+use crate::story::story_state::StoryState;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Answer to the inspection of a player
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerOverview {
+    pub player: PlayerKey,
+    pub uuid: String,
+    pub display_name: String,
+    pub karma: i64,
+    pub story_state: StoryState,
+    pub villages: Vec<VillageOverview>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VillageOverview {
+    pub village: VillageKey,
+    pub x: f32,
+    pub y: f32,
+    /// Current amounts, including income that has not been collected yet
+    pub resources: Vec<(ResourceType, i64)>,
+    pub workers: Vec<WorkerKey>,
+}
+
+/// Adds resources to a village, negative amounts are subtracted
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResourceGrant {
+    pub village: VillageKey,
+    pub resources: Vec<(ResourceType, i64)>,
+}
+
+/// Adds karma to a player, negative amounts are subtracted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct KarmaGrant {
+    pub player: PlayerKey,
+    pub karma: i64,
+}
+
+/// Moves a player to any story state, no matter the story transitions
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct StoryStateReset {
+    pub player: PlayerKey,
+    pub story_state: StoryState,
+}
+
+/// Sends anarchists to a village right away, with a strength according to the player's karma
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct AnarchistAttackOrder {
+    pub village: VillageKey,
+}
+
+/// An event waiting in the queue of the game-master
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledEventInfo {
+    pub event: String,
+    pub time: NaiveDateTime,
+}
@@ -7,3 +7,14 @@ pub struct AttackDescriptor {
     pub to: (i32, i32),
     pub units: Vec<HoboKey>,
 }
+
+/// Answer of the game-master when a client reports a satisfied visitor
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitorSatisfaction {
+    /// The server agrees, the visitor is satisfied
+    Satisfied,
+    /// The visitor is still in town and not satisfied according to the server
+    NotSatisfied,
+    /// The visitor has already left the town without being satisfied
+    LeftUnsatisfied,
+}
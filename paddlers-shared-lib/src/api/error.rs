@@ -1,8 +1,39 @@
+use serde::{Deserialize, Serialize};
+
 /// Defines API error codes to be sent over the network
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum PadlApiError {
     PlayerNotCreated = 1,
+    NotOwned = 2,
+    NotEnoughResources = 3,
+    NotEnoughKarma = 4,
+    NotEnoughMana = 5,
+    NotEnoughSupply = 6,
+    BuildingFull = 7,
+    InvalidPlacement = 8,
+    BuildingLocked = 9,
+    AbilityNotReady = 10,
+    PathBlocked = 11,
+    CannotDefendHere = 12,
+    StoryViolation = 13,
+    HoboBusy = 14,
+    TooManyRequests = 15,
+    NotFound = 16,
+    InvalidTask = 17,
+    InvalidRequest = 18,
+    Internal = 19,
+    AdminRequired = 20,
+}
+
+/// Body of a rejected request to the game-master, serialized as JSON.
+///
+/// The `padlcode` is the same number that is sent in GraphQL error extensions,
+/// the message is only meant for developers and should not be shown to players.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PadlApiErrorBody {
+    pub padlcode: u8,
+    pub message: String,
 }
 
 impl std::error::Error for PadlApiError {}
@@ -10,6 +41,25 @@ impl std::fmt::Display for PadlApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             PadlApiError::PlayerNotCreated => write!(f, "The player is not in the database."),
+            PadlApiError::NotOwned => write!(f, "The object is not owned by the player."),
+            PadlApiError::NotEnoughResources => write!(f, "Not enough resources."),
+            PadlApiError::NotEnoughKarma => write!(f, "Not enough karma."),
+            PadlApiError::NotEnoughMana => write!(f, "Not enough mana."),
+            PadlApiError::NotEnoughSupply => write!(f, "Not enough forest supply."),
+            PadlApiError::BuildingFull => write!(f, "The building is full."),
+            PadlApiError::InvalidPlacement => write!(f, "Cannot place it there."),
+            PadlApiError::BuildingLocked => write!(f, "The building has not been unlocked."),
+            PadlApiError::AbilityNotReady => write!(f, "The ability is not ready."),
+            PadlApiError::PathBlocked => write!(f, "The path is blocked."),
+            PadlApiError::CannotDefendHere => write!(f, "Cannot defend at this position."),
+            PadlApiError::StoryViolation => write!(f, "Not allowed by the story."),
+            PadlApiError::HoboBusy => write!(f, "The hobo is busy."),
+            PadlApiError::TooManyRequests => write!(f, "Too many requests."),
+            PadlApiError::NotFound => write!(f, "The object does not exist."),
+            PadlApiError::InvalidTask => write!(f, "The task cannot be performed."),
+            PadlApiError::InvalidRequest => write!(f, "The request is invalid."),
+            PadlApiError::Internal => write!(f, "Internal server error."),
+            PadlApiError::AdminRequired => write!(f, "Only admins are allowed to do this."),
         }
     }
 }
@@ -18,7 +68,35 @@ impl PadlApiError {
     pub fn try_from_num(i: u8) -> Option<Self> {
         match i {
             1 => Some(PadlApiError::PlayerNotCreated),
+            2 => Some(PadlApiError::NotOwned),
+            3 => Some(PadlApiError::NotEnoughResources),
+            4 => Some(PadlApiError::NotEnoughKarma),
+            5 => Some(PadlApiError::NotEnoughMana),
+            6 => Some(PadlApiError::NotEnoughSupply),
+            7 => Some(PadlApiError::BuildingFull),
+            8 => Some(PadlApiError::InvalidPlacement),
+            9 => Some(PadlApiError::BuildingLocked),
+            10 => Some(PadlApiError::AbilityNotReady),
+            11 => Some(PadlApiError::PathBlocked),
+            12 => Some(PadlApiError::CannotDefendHere),
+            13 => Some(PadlApiError::StoryViolation),
+            14 => Some(PadlApiError::HoboBusy),
+            15 => Some(PadlApiError::TooManyRequests),
+            16 => Some(PadlApiError::NotFound),
+            17 => Some(PadlApiError::InvalidTask),
+            18 => Some(PadlApiError::InvalidRequest),
+            19 => Some(PadlApiError::Internal),
+            20 => Some(PadlApiError::AdminRequired),
             _ => None,
         }
     }
+    pub fn body(self, message: String) -> PadlApiErrorBody {
+        PadlApiErrorBody {
+            padlcode: self as u8,
+            message,
+        }
+    }
 }
+
+#[cfg(test)]
+mod error_test;
@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn codes_can_be_read_back() {
+    let mut n = 0;
+    for i in 0..=u8::max_value() {
+        if let Some(err) = PadlApiError::try_from_num(i) {
+            assert_eq!(err as u8, i);
+            n += 1;
+        }
+    }
+    assert_eq!(n, PadlApiError::AdminRequired as u8);
+}
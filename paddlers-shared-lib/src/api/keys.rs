@@ -54,7 +54,9 @@ macro_rules! object_key {
 object_key!(Attack, AttackKey);
 object_key!(Hobo, HoboKey);
 object_key!(Player, PlayerKey);
+object_key!(Season, SeasonKey);
 object_key!(Stream, StreamKey);
 object_key!(Task, TaskKey);
 object_key!(VisitReport, VisitReportKey);
 object_key!(Worker, WorkerKey);
+object_key!(World, WorldKey);
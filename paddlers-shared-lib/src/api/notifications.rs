@@ -0,0 +1,28 @@
+use crate::api::keys::VillageKey;
+use serde::*;
+
+/// WebSocket sub-protocol of the notification channel.
+/// The frontend offers the JWT as a second sub-protocol, as browsers cannot set other headers on WebSocket requests.
+pub const NOTIFICATION_PROTOCOL: &str = "padl-notifications";
+
+/// Change notification that the game-master pushes to the frontend over a WebSocket.
+/// It only tells the frontend what to reload, the data itself is still read from GraphQL.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Notification {
+    NewAttack(VillageKey),
+    VisitorReleased(VillageKey),
+    ResourcesChanged(VillageKey),
+    ReportCreated(VillageKey),
+}
+
+impl Notification {
+    /// Only players owning this village receive the notification
+    pub fn village(&self) -> VillageKey {
+        match self {
+            Self::NewAttack(v)
+            | Self::VisitorReleased(v)
+            | Self::ResourcesChanged(v)
+            | Self::ReportCreated(v) => *v,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::api::keys::VillageKey;
+use crate::balancing::balancing;
 use crate::models::*;
 use serde::{Deserialize, Serialize};
 
@@ -13,18 +14,7 @@ pub trait Cost {
 
 impl Cost for BuildingType {
     fn cost(&self) -> Vec<(ResourceType, i64)> {
-        match self {
-            BuildingType::BlueFlowers => vec![(ResourceType::Feathers, 20)],
-            BuildingType::RedFlowers => {
-                vec![(ResourceType::Feathers, 100), (ResourceType::Sticks, 20)]
-            }
-            BuildingType::Tree => vec![(ResourceType::Sticks, 10)],
-            BuildingType::BundlingStation => vec![(ResourceType::Feathers, 20)],
-            BuildingType::SawMill => vec![(ResourceType::Feathers, 20), (ResourceType::Sticks, 20)],
-            BuildingType::PresentA => vec![(ResourceType::Feathers, 100)],
-            BuildingType::PresentB => vec![(ResourceType::Sticks, 50), (ResourceType::Logs, 50)],
-            BuildingType::Temple => vec![],
-        }
+        balancing().building(*self).cost.to_vec()
     }
 }
 
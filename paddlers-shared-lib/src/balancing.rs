@@ -0,0 +1,212 @@
+//! Balancing numbers of the game, loaded from a specification file.
+//!
+//! The specification lives in `specification/balancing.toml` and is embedded at compile time,
+//! which keeps game-master and frontend in sync without shipping an additional file.
+//! Services should call [`validate_balancing`](fn.validate_balancing.html) once at startup,
+//! after that the values can be accessed through [`balancing`](fn.balancing.html).
+
+use crate::models::*;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+const ALL_BUILDING_TYPES: [BuildingType; 8] = [
+    BuildingType::BlueFlowers,
+    BuildingType::RedFlowers,
+    BuildingType::Tree,
+    BuildingType::BundlingStation,
+    BuildingType::SawMill,
+    BuildingType::PresentA,
+    BuildingType::PresentB,
+    BuildingType::Temple,
+];
+
+const SPECIFICATION: &str = include_str!("../specification/balancing.toml");
+
+static BALANCING: Lazy<Result<Balancing, BalancingError>> =
+    Lazy::new(|| Balancing::parse(SPECIFICATION));
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Balancing {
+    building: Vec<BuildingSpec>,
+    pub prophets: ProphetSpec,
+    pub anarchists: AnarchistSpec,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildingSpec {
+    #[serde(rename = "type")]
+    pub building_type: BuildingType,
+    pub cost: ResourceAmounts,
+    pub range: Option<f32>,
+    pub attack_power: Option<i64>,
+    pub attacks_per_cycle: Option<i64>,
+    /// Buildings without karma requirement are not unlocked by karma but by other means (e.g. the story)
+    pub karma_required: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProphetSpec {
+    pub karma_thresholds: Vec<i64>,
+    pub karma_per_additional_prophet: i64,
+    pub base_cost: ResourceAmounts,
+    pub cost_offset: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnarchistSpec {
+    pub level_karma_thresholds: Vec<i64>,
+    /// Right-exclusive ranges, indexed by level
+    pub hurried_hp_ranges: Vec<(i64, i64)>,
+    pub attack_chance_offset: usize,
+    pub village_population: usize,
+    pub replenish_interval_seconds: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceAmounts {
+    #[serde(default)]
+    pub feathers: i64,
+    #[serde(default)]
+    pub sticks: i64,
+    #[serde(default)]
+    pub logs: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalancingError {
+    Parse(String),
+    Invalid(String),
+}
+
+/// Checks that the embedded balancing specification can be loaded
+pub fn validate_balancing() -> Result<(), BalancingError> {
+    BALANCING.as_ref().map(|_| ()).map_err(Clone::clone)
+}
+
+/// Access to the embedded balancing specification.
+///
+/// Panics if the specification is invalid, which is checked in the unit tests and at startup.
+pub fn balancing() -> &'static Balancing {
+    match BALANCING.as_ref() {
+        Ok(b) => b,
+        Err(e) => panic!("Invalid balancing specification: {}", e),
+    }
+}
+
+impl Balancing {
+    pub fn parse(input: &str) -> Result<Self, BalancingError> {
+        let balancing: Balancing =
+            toml::from_str(input).map_err(|e| BalancingError::Parse(e.to_string()))?;
+        balancing.validate()?;
+        Ok(balancing)
+    }
+    pub fn building(&self, bt: BuildingType) -> &BuildingSpec {
+        self.building
+            .iter()
+            .find(|spec| spec.building_type == bt)
+            .expect("Validated balancing has all building types")
+    }
+    fn validate(&self) -> Result<(), BalancingError> {
+        for bt in &ALL_BUILDING_TYPES {
+            let n = self
+                .building
+                .iter()
+                .filter(|spec| spec.building_type == *bt)
+                .count();
+            if n != 1 {
+                return invalid(format!("{:?} is specified {} times", bt, n));
+            }
+        }
+        for spec in &self.building {
+            spec.cost
+                .validate(&format!("cost of {:?}", spec.building_type))?;
+            if spec.range.map(|r| r < 0.0).unwrap_or(false) {
+                return invalid(format!("{:?} has a negative range", spec.building_type));
+            }
+        }
+
+        let prophets = &self.prophets;
+        ascending(&prophets.karma_thresholds, "prophets.karma_thresholds")?;
+        if prophets.karma_per_additional_prophet <= 0 {
+            return invalid("prophets.karma_per_additional_prophet must be positive".to_owned());
+        }
+        if prophets.cost_offset < 0 {
+            return invalid("prophets.cost_offset must not be negative".to_owned());
+        }
+        prophets.base_cost.validate("prophets.base_cost")?;
+
+        let anarchists = &self.anarchists;
+        ascending(
+            &anarchists.level_karma_thresholds,
+            "anarchists.level_karma_thresholds",
+        )?;
+        if anarchists.hurried_hp_ranges.is_empty() {
+            return invalid("anarchists.hurried_hp_ranges must not be empty".to_owned());
+        }
+        for (min, max) in &anarchists.hurried_hp_ranges {
+            if *min < 1 || min >= max {
+                return invalid(format!(
+                    "anarchists.hurried_hp_ranges contains invalid range [{}, {}]",
+                    min, max
+                ));
+            }
+        }
+        if anarchists.village_population == 0 {
+            return invalid("anarchists.village_population must be positive".to_owned());
+        }
+        if anarchists.replenish_interval_seconds <= 0 {
+            return invalid("anarchists.replenish_interval_seconds must be positive".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl ResourceAmounts {
+    /// Non-zero amounts in a list, as used for prices
+    pub fn to_vec(&self) -> Vec<(ResourceType, i64)> {
+        [
+            (ResourceType::Feathers, self.feathers),
+            (ResourceType::Sticks, self.sticks),
+            (ResourceType::Logs, self.logs),
+        ]
+        .iter()
+        .copied()
+        .filter(|(_, n)| *n != 0)
+        .collect()
+    }
+    fn validate(&self, what: &str) -> Result<(), BalancingError> {
+        if self.feathers < 0 || self.sticks < 0 || self.logs < 0 {
+            return invalid(format!("{} contains negative amounts", what));
+        }
+        Ok(())
+    }
+}
+
+fn ascending(values: &[i64], what: &str) -> Result<(), BalancingError> {
+    if values.windows(2).any(|w| w[0] >= w[1]) {
+        return invalid(format!("{} must be strictly ascending", what));
+    }
+    Ok(())
+}
+
+fn invalid<T>(msg: String) -> Result<T, BalancingError> {
+    Err(BalancingError::Invalid(msg))
+}
+
+impl std::fmt::Display for BalancingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BalancingError::Parse(msg) => write!(f, "Failed to parse balancing: {}", msg),
+            BalancingError::Invalid(msg) => write!(f, "Invalid balancing: {}", msg),
+        }
+    }
+}
+impl std::error::Error for BalancingError {}
+
+#[cfg(test)]
+mod balancing_test;
@@ -0,0 +1,29 @@
+use super::*;
+
+#[test]
+fn embedded_specification_is_valid() {
+    assert_eq!(validate_balancing(), Ok(()));
+}
+
+#[test]
+fn building_costs() {
+    let b = balancing();
+    assert_eq!(
+        b.building(BuildingType::RedFlowers).cost.to_vec(),
+        vec![(ResourceType::Feathers, 100), (ResourceType::Sticks, 20)]
+    );
+    assert!(b.building(BuildingType::Temple).cost.to_vec().is_empty());
+    assert_eq!(b.building(BuildingType::Temple).karma_required, None);
+}
+
+#[test]
+fn duplicate_building_is_rejected() {
+    let duplicated = format!(
+        "{}\n[[building]]\ntype = \"Tree\"\ncost = {{}}\n",
+        SPECIFICATION
+    );
+    match Balancing::parse(&duplicated) {
+        Err(BalancingError::Invalid(_)) => {}
+        other => panic!("Expected invalid balancing, got {:?}", other),
+    }
+}
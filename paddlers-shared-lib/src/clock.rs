@@ -0,0 +1,62 @@
+//! Source of the current game time.
+//!
+//! Game logic should never read the system time directly but ask a `GameClock` instead.
+//! In production, the `RealClock` is used. Tests can use a `SimulatedClock` and
+//! fast-forward through hours of game time in a couple of milliseconds.
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+pub trait GameClock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+    fn naive_now(&self) -> NaiveDateTime {
+        self.now().naive_utc()
+    }
+}
+
+/// Shared handle to the clock that is used all over a service
+pub type Clock = Arc<dyn GameClock>;
+
+/// Wall-clock time of the system
+#[derive(Default, Debug, Clone, Copy)]
+pub struct RealClock;
+
+impl GameClock for RealClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves forward when told so
+#[derive(Debug)]
+pub struct SimulatedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        SimulatedClock {
+            now: Mutex::new(start),
+        }
+    }
+    /// Starts the simulated time at the current system time
+    pub fn starting_now() -> Self {
+        Self::new(Utc::now())
+    }
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().expect("Clock poisoned");
+        *now = *now + duration;
+    }
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.now.lock().expect("Clock poisoned") = time;
+    }
+}
+
+impl GameClock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("Clock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod clock_test;
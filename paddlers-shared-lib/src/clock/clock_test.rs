@@ -0,0 +1,27 @@
+use super::*;
+#[cfg(feature = "game_mechanics")]
+use crate::game_mechanics::forestry::tree_size;
+use chrono::TimeZone;
+
+#[test]
+fn simulated_clock_only_moves_when_told() {
+    let start = Utc.ymd(2020, 5, 1).and_hms(12, 0, 0);
+    let clock = SimulatedClock::new(start);
+    assert_eq!(clock.now(), start);
+    clock.advance(Duration::minutes(90));
+    assert_eq!(clock.now(), start + Duration::minutes(90));
+    assert_eq!(
+        clock.naive_now(),
+        (start + Duration::minutes(90)).naive_utc()
+    );
+}
+
+#[test]
+#[cfg(feature = "game_mechanics")]
+fn fast_forward_tree_growth() {
+    let clock = SimulatedClock::starting_now();
+    let planted = clock.naive_now();
+    assert_eq!(tree_size(clock.naive_now() - planted), 1);
+    clock.advance(Duration::hours(2));
+    assert_eq!(tree_size(clock.naive_now() - planted), 2);
+}
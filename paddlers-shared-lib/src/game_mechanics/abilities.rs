@@ -2,11 +2,16 @@ use crate::models::*;
 use chrono::Duration;
 
 impl AbilityType {
-    /// Returns affected attribute and strength of ability
-    pub fn apply(&self) -> (HoboAttributeType, i32) {
+    /// Returns affected attributes and strengths of ability.
+    /// Speed strengths are in percent of the base speed.
+    pub fn apply(&self) -> &'static [(HoboAttributeType, i32)] {
         match self {
             AbilityType::Work => panic!("Cannot apply work to hobo"),
-            AbilityType::Welcome => (HoboAttributeType::Health, 1),
+            // Welcomed visitors are happier and slow down to enjoy the town
+            AbilityType::Welcome => &[
+                (HoboAttributeType::Health, 1),
+                (HoboAttributeType::Speed, -30),
+            ],
         }
     }
 
@@ -1,3 +1,4 @@
+use crate::balancing::balancing;
 use crate::models::*;
 
 pub trait Attributes {
@@ -9,30 +10,13 @@ pub trait Attributes {
 
 impl Attributes for BuildingType {
     fn range(&self) -> Option<f32> {
-        match self {
-            BuildingType::BlueFlowers => Some(2.0),
-            BuildingType::RedFlowers => Some(1.0),
-            _ => None,
-        }
+        balancing().building(*self).range
     }
     fn attack_power(&self) -> Option<i64> {
-        match self {
-            BuildingType::BlueFlowers => Some(1),
-            BuildingType::RedFlowers => Some(3),
-            _ => None,
-        }
+        balancing().building(*self).attack_power
     }
     fn attacks_per_cycle(&self) -> Option<i64> {
-        match self {
-            BuildingType::BlueFlowers => None,
-            BuildingType::RedFlowers => None,
-            BuildingType::Tree => None,
-            BuildingType::BundlingStation => None,
-            BuildingType::SawMill => None,
-            BuildingType::PresentA => None,
-            BuildingType::PresentB => None,
-            BuildingType::Temple => None,
-        }
+        balancing().building(*self).attacks_per_cycle
     }
     fn size(&self) -> (usize, usize) {
         (1, 1)
@@ -1,3 +1,4 @@
+use crate::balancing::balancing;
 use crate::models::*;
 use crate::story::story_state::StoryState;
 
@@ -43,14 +44,12 @@ impl BuildingType {
     /// Definition of which buildings are available to a player
     pub fn player_can_build(&self, karma: i64, story_state: StoryState) -> bool {
         match self {
-            BuildingType::BlueFlowers => karma >= 1,
-            BuildingType::BundlingStation => karma >= 1,
-            BuildingType::PresentA => karma >= 200,
-            BuildingType::PresentB => karma >= 2000,
-            BuildingType::RedFlowers => karma >= 1000,
-            BuildingType::SawMill => karma >= 100,
             BuildingType::Temple => story_state == StoryState::ServantAccepted,
-            BuildingType::Tree => karma >= 1,
+            _ => balancing()
+                .building(*self)
+                .karma_required
+                .map(|required| karma >= required)
+                .unwrap_or(false),
         }
     }
     /// Buildings that may be available at the default shop, regardless of player restrictions
@@ -1,4 +1,5 @@
-// TODO [0.1.5] Move to better place, maybe a separate specification / balancing crate.
+use crate::balancing::balancing;
+use crate::story::story_state::StoryState;
 
 pub struct HoboLevel(usize);
 
@@ -7,31 +8,72 @@ impl HoboLevel {
         HoboLevel(0)
     }
     pub fn anarchist(player_karma: i64) -> Self {
-        match player_karma {
-            0..=19 => HoboLevel(0),
-            20..=99 => HoboLevel(1),
-            100..=899 => HoboLevel(1 + player_karma as usize / 100),
-            _ => HoboLevel(10),
-        }
+        let level = balancing()
+            .anarchists
+            .level_karma_thresholds
+            .iter()
+            .take_while(|threshold| **threshold <= player_karma)
+            .count();
+        HoboLevel(level)
     }
     /// Right-exclusive range
     pub fn hurried_anarchist_hp_range(&self) -> (i64, i64) {
-        // TODO: Balancing
-        match self.0 {
-            0 => (1, 2),
-            1 => (1, 3),
-            2 => (1, 4),
-            3 => (2, 5),
-            4 => (3, 8),
-            5 => (4, 9),
-            6 => (6, 13),
-            7 => (10, 20),
-            8 => (20, 40),
-            9 => (30, 60),
-            _ => (50, 100),
-        }
+        let ranges = &balancing().anarchists.hurried_hp_ranges;
+        ranges[self.0.min(ranges.len() - 1)]
     }
     pub fn unhurried_anarchist_hp(&self) -> i64 {
         self.hurried_anarchist_hp_range().1
     }
 }
+
+/// Decides if another group of anarchists is sent to a village that has `ongoing_attacks` unfinished visits
+pub fn should_send_attack(ongoing_attacks: usize, random_number: usize, village_id: i64) -> bool {
+    let offset = balancing().anarchists.attack_chance_offset;
+    match ongoing_attacks {
+        0 => true,
+        n => (random_number + village_id as usize) % (n * n + offset) == 0,
+    }
+}
+// TODO [0.1.5]: Define this in specification document and/or integrate with wiki
+/// Level of anarchists that are repeatedly sent to a player, None if the story has not progressed far enough
+pub fn repetitive_attack_strength(story_state: StoryState, karma: i64) -> Option<HoboLevel> {
+    match story_state {
+        StoryState::Initialized
+        | StoryState::ServantAccepted
+        | StoryState::TempleBuilt
+        | StoryState::VisitorArrived => None,
+        StoryState::FirstVisitorWelcomed | StoryState::FlowerPlanted => Some(HoboLevel::zero()),
+        StoryState::MoreHappyVisitors
+        | StoryState::TreePlanted
+        | StoryState::StickGatheringStationBuild
+        | StoryState::GatheringSticks => Some(HoboLevel::anarchist(karma)),
+    }
+}
+
+/// TODO [0.1.5]
+pub fn reward_feathers(hp: i64, speed: f32, hurried: bool) -> i64 {
+    let f = if hurried {
+        (1.0 + hp as f32 * speed / 4.0).log2().floor()
+    } else {
+        (1.0 + hp as f32 / 16.0).log2().ceil()
+    };
+    f as i64
+}
+
+/// TODO [0.1.5]
+pub fn reward_sticks(hobo_id: i64) -> i64 {
+    if hobo_id % 20 == 0 {
+        5
+    } else {
+        0
+    }
+}
+
+/// TODO [0.1.5]
+pub fn reward_logs(hobo_id: i64) -> i64 {
+    if hobo_id % 60 == 11 {
+        5
+    } else {
+        0
+    }
+}
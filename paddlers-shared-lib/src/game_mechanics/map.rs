@@ -1,5 +1,4 @@
 pub const MAP_H: u32 = 11;
-pub const MAP_MAX_X: u32 = 20;
 pub const MAP_STREAM_AREA_W: f32 = 5.0;
 
 pub fn map_distance(a: (f32, f32), b: (f32, f32)) -> f32 {
@@ -1,25 +1,28 @@
 use crate::api::shop::Price;
-use crate::models::*;
+use crate::balancing::balancing;
 
 pub fn prophets_allowed(karma: i64) -> i64 {
-    match karma {
-        0..=999 => 0,
-        1000..=1999 => 1,
-        2000..=2999 => 2,
-        3000..=4999 => 3,
-        5000..=7499 => 4,
-        7500..=9999 => 5,
-        10000..=12499 => 6,
-        12500..=15999 => 7,
-        16000..=19999 => 8,
-        k => 9 + (k - 20000) / 10000,
+    let spec = &balancing().prophets;
+    let reached = spec
+        .karma_thresholds
+        .iter()
+        .take_while(|threshold| **threshold <= karma)
+        .count() as i64;
+    match spec.karma_thresholds.last() {
+        Some(last) if karma >= *last => {
+            reached + (karma - last) / spec.karma_per_additional_prophet
+        }
+        _ => reached,
     }
 }
 pub fn prophet_cost(existing: i64) -> Price {
-    let factor = existing + 2;
-    Price(vec![
-        (ResourceType::Feathers, 500 * factor),
-        (ResourceType::Sticks, 350 * factor),
-        (ResourceType::Logs, 150 * factor),
-    ])
+    let spec = &balancing().prophets;
+    let factor = existing + spec.cost_offset;
+    Price(
+        spec.base_cost
+            .to_vec()
+            .into_iter()
+            .map(|(rt, n)| (rt, n * factor))
+            .collect(),
+    )
 }
@@ -75,6 +75,11 @@ impl TownMap {
         d.max(0.0)
     }
 
+    /// Workers can only take a defend position on an empty tile right next to the lane
+    pub fn can_defend_at(&self, i: TileIndex) -> bool {
+        self.tile_type(i) == Some(&TownTileType::EMPTY) && self.distance_to_lane(i) == 0.0
+    }
+
     pub fn tile_type(&self, index: TileIndex) -> Option<&TownTileType> {
         self.0.get(index.0).and_then(|m| m.get(index.1))
     }
@@ -7,20 +7,98 @@ use super::town_layout::ITownLayout;
 use super::{TOWN_RESTING_X, TOWN_X};
 use crate::shared_types::*;
 
+/// Length of a cycle after which discrete-shot buildings have their full number of cheer-ups available again
+pub const SHOT_CYCLE_SECONDS: i64 = 4;
+
+/// Speed effects cannot slow a visitor down below this fraction of its base speed
+pub const MIN_SPEED_FACTOR: f32 = 0.1;
+
+/// Converts the sum of all speed effects on a visitor, given in percent, to a factor on the base speed
+pub fn speed_factor(effects_percent: i32) -> f32 {
+    (1.0 + effects_percent as f32 / 100.0).max(MIN_SPEED_FACTOR)
+}
+
+/// Whether an aura that is present from `since` until `until` (exclusive) affects visitors passing by at `time`.
+/// Auras of buildings have no end, those of defending workers end when the worker leaves its position.
+pub fn aura_active(since: Timestamp, until: Option<Timestamp>, time: Timestamp) -> bool {
+    let ended = until.map(|until| until <= time).unwrap_or(false);
+    since <= time && !ended
+}
+
+/// A building that cheers up a limited number of visitors per cycle, as opposed to an aura which affects all passing visitors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shooter<I> {
+    pub id: I,
+    pub power: i32,
+    pub shots_per_cycle: usize,
+}
+
 /// Provides information about a hobo currently attacking
 pub trait IAttackingHobo {
     // TO IMPLEMENT
+    /// Unique ID, used to break ties deterministically
+    fn id(&self) -> i64;
     fn max_hp(&self) -> u32;
     fn speed(&self) -> f32;
     fn hurried(&self) -> bool;
     fn arrival(&self) -> Timestamp;
     fn released(&self) -> Option<Timestamp>;
     fn effects_strength(&self) -> i32;
+    /// Speed effects as (start time, strength in percent) pairs
+    fn speed_effects(&self) -> Vec<(Timestamp, i32)>;
 
     // PROVIDED
     /// Returns the duration it takes the hobo to reach the resting place, after having reached the town.
     fn time_until_resting(&self) -> Timestamp {
-        Self::s_time_until_resting(self.speed())
+        let distance_until_resting = TOWN_X - TOWN_RESTING_X;
+        self.time_after_distance(self.arrival(), distance_until_resting as f32) - self.arrival()
+    }
+    /// Speed in tiles per second at the given time, considering all speed effects applied until then
+    fn speed_at(&self, t: Timestamp) -> f32 {
+        let percent = self
+            .speed_effects()
+            .iter()
+            .filter(|(start, _)| *start <= t)
+            .fold(0, |acc, (_, strength)| acc + strength);
+        self.speed() * speed_factor(percent)
+    }
+    /// Number of tiles swum between two points in time
+    fn distance_walked(&self, from: Timestamp, to: Timestamp) -> f32 {
+        let mut distance = 0.0;
+        let mut t = from;
+        for change in self.speed_changes_after(from) {
+            if change >= to {
+                break;
+            }
+            distance += (change - t).seconds_float() * self.speed_at(t);
+            t = change;
+        }
+        distance + (to - t).seconds_float() * self.speed_at(t)
+    }
+    /// The point in time when the given number of tiles has been swum, starting at `from`
+    fn time_after_distance(&self, from: Timestamp, distance: f32) -> Timestamp {
+        let mut left = distance;
+        let mut t = from;
+        for change in self.speed_changes_after(from) {
+            let segment = (change - t).seconds_float() * self.speed_at(t);
+            if segment >= left {
+                break;
+            }
+            left -= segment;
+            t = change;
+        }
+        t + Timestamp::from_float_seconds(left / self.speed_at(t))
+    }
+    /// Ordered points in time after `t` at which the speed changes
+    fn speed_changes_after(&self, t: Timestamp) -> Vec<Timestamp> {
+        let mut changes: Vec<Timestamp> = self
+            .speed_effects()
+            .into_iter()
+            .map(|(start, _)| start)
+            .filter(|start| *start > t)
+            .collect();
+        changes.sort_by_key(Timestamp::micros);
+        changes
     }
     fn s_time_until_resting(speed: f32) -> Timestamp {
         let distance_until_resting = TOWN_X - TOWN_RESTING_X;
@@ -28,13 +106,56 @@ pub trait IAttackingHobo {
     }
 }
 
+/// Allows computations on groups of visitors borrowed from different places
+impl<T: IAttackingHobo> IAttackingHobo for &T {
+    fn id(&self) -> i64 {
+        (*self).id()
+    }
+    fn max_hp(&self) -> u32 {
+        (*self).max_hp()
+    }
+    fn speed(&self) -> f32 {
+        (*self).speed()
+    }
+    fn hurried(&self) -> bool {
+        (*self).hurried()
+    }
+    fn arrival(&self) -> Timestamp {
+        (*self).arrival()
+    }
+    fn released(&self) -> Option<Timestamp> {
+        (*self).released()
+    }
+    fn effects_strength(&self) -> i32 {
+        (*self).effects_strength()
+    }
+    fn speed_effects(&self) -> Vec<(Timestamp, i32)> {
+        (*self).speed_effects()
+    }
+}
+
 /// Trait for town information required to perform hp computations
 pub trait IDefendingTown: ITownLayout {
     // TO IMPLEMENT
     type AuraId: Ord + PartialEq;
     fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)>;
+    fn shooters_in_range(&self, index: &Self::Index, time: Timestamp)
+        -> Vec<Shooter<Self::AuraId>>;
 
     // PROVIDED
+    /// Like `hp_left` but also considers discrete shots, which can only be computed for an entire group of visitors at once.
+    /// Returns the HP of each visitor in the same order as the input.
+    fn hp_left_in_group<HOBO: IAttackingHobo>(
+        &self,
+        visitors: &[HOBO],
+        now: Timestamp,
+    ) -> Vec<u32> {
+        self.shot_damage(visitors, now)
+            .into_iter()
+            .zip(visitors)
+            .map(|(dmg, v)| self.hp_left(v, now).saturating_sub(dmg as u32))
+            .collect()
+    }
     fn hp_left<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> u32 {
         attacker
             .max_hp()
@@ -46,21 +167,109 @@ pub trait IDefendingTown: ITownLayout {
 
     fn hobo_left_town<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> bool {
         if attacker.hurried() {
-            let time_since_arrival = now - attacker.arrival();
             // +1 for swimming out of sight
             let distance = self.path_straight_through().len() + 1;
-            time_since_arrival.seconds_float() >= distance as f32 / attacker.speed()
+            attacker.distance_walked(attacker.arrival(), now) >= distance as f32
         } else {
             if let Some(released) = self.left_rest_place(attacker) {
-                let time_since_release = now - released;
                 // +1 for swimming out of sight
                 let distance = self.path_from_rest_place().len() + 1;
-                time_since_release.seconds_float() >= distance as f32 / attacker.speed()
+                attacker.distance_walked(released, now) >= distance as f32
             } else {
                 false
             }
         }
     }
+    /// The point in time at which `hobo_left_town` becomes true, up to rounding.
+    /// Returns None for visitors that are still resting and have not been released, yet.
+    fn departure_time<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> Option<Timestamp> {
+        // +1 for swimming out of sight
+        if attacker.hurried() {
+            let distance = self.path_straight_through().len() + 1;
+            Some(attacker.time_after_distance(attacker.arrival(), distance as f32))
+        } else {
+            let distance = self.path_from_rest_place().len() + 1;
+            self.left_rest_place(attacker)
+                .map(|released| attacker.time_after_distance(released, distance as f32))
+        }
+    }
+    /// Computes how much each visitor of a group has been cheered up by discrete-shot buildings until `now`.
+    ///
+    /// Targeting rules:
+    ///   * Shots are fired at the beginning of each cycle, cycles start at multiples of SHOT_CYCLE_SECONDS.
+    ///   * A visitor is a candidate if it is in range and not satisfied at that moment, considering auras, effects and previous shots.
+    ///   * Candidates are served by ascending ID, each building hits a candidate at most once per cycle.
+    ///   * Once a building has used all its shots, it skips the remaining candidates of the cycle.
+    fn shot_damage<HOBO: IAttackingHobo>(&self, visitors: &[HOBO], now: Timestamp) -> Vec<i32> {
+        let mut damage = vec![0; visitors.len()];
+        let mut order: Vec<usize> = (0..visitors.len()).collect();
+        order.sort_by_key(|i| visitors[*i].id());
+
+        let first_arrival = match visitors.iter().map(|v| v.arrival().micros()).min() {
+            Some(t) => t,
+            None => return damage,
+        };
+        let cycle = Timestamp::from_seconds(SHOT_CYCLE_SECONDS).micros();
+        // First cycle start at or after the first arrival
+        let mut t = (first_arrival + cycle - 1).div_euclid(cycle) * cycle;
+        while t <= now.micros() {
+            let tick = Timestamp::from_us(t);
+            let mut shots_used: Vec<(Self::AuraId, usize)> = vec![];
+            for &i in &order {
+                let visitor = &visitors[i];
+                let tile = match self.visitor_tile(visitor, tick) {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+                let mut hp = visitor.max_hp() as i32 - self.total_damage(visitor, tick) - damage[i];
+                for shooter in self.shooters_in_range(tile, tick) {
+                    if hp <= 0 {
+                        break;
+                    }
+                    let used = match shots_used.iter().position(|(id, _)| *id == shooter.id) {
+                        Some(n) => &mut shots_used[n].1,
+                        None => {
+                            shots_used.push((shooter.id, 0));
+                            &mut shots_used.last_mut().unwrap().1
+                        }
+                    };
+                    if *used < shooter.shots_per_cycle {
+                        *used += 1;
+                        damage[i] += shooter.power;
+                        hp -= shooter.power;
+                    }
+                }
+            }
+            t += cycle;
+        }
+        damage
+    }
+    /// The tile a visitor is on at a given time, or None if it is not in town at that time
+    fn visitor_tile<HOBO: IAttackingHobo>(
+        &self,
+        attacker: &HOBO,
+        t: Timestamp,
+    ) -> Option<&'static Self::Index> {
+        if t < attacker.arrival() {
+            return None;
+        }
+        let tiles_walked = |since: Timestamp| attacker.distance_walked(since, t).floor() as usize;
+        if attacker.hurried() {
+            return self
+                .path_straight_through()
+                .get(tiles_walked(attacker.arrival()));
+        }
+        match self.left_rest_place(attacker) {
+            Some(released) if t >= released => {
+                self.path_from_rest_place().get(tiles_walked(released))
+            }
+            _ => {
+                let path = self.path_to_rest_place();
+                let walked = tiles_walked(attacker.arrival()).min(path.len() - 1);
+                path.get(walked)
+            }
+        }
+    }
     fn aura_damage<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> i32 {
         let auras = self.touched_auras(attacker, now);
         let dmg = Self::damage(&auras);
@@ -109,14 +318,12 @@ pub trait IDefendingTown: ITownLayout {
         tiles: &[Self::Index],
     ) -> Vec<(Self::AuraId, i32)> {
         let mut out = vec![];
-        let mut t = start;
-        let t_per_tile = Timestamp::from_float_seconds(1.0 / attacker.speed());
-        for tile in tiles {
+        for (i, tile) in tiles.iter().enumerate() {
+            let t = attacker.time_after_distance(start, i as f32);
             if t > max_t {
                 break;
             }
             out.append(&mut self.auras_in_range(tile, t));
-            t = t + t_per_tile;
         }
         out.sort();
         out.dedup();
@@ -125,9 +332,8 @@ pub trait IDefendingTown: ITownLayout {
     /// The timestamp when the resting place was left by a non-hurried hobo. May differ from hobo.released
     fn left_rest_place<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> Option<Timestamp> {
         attacker.released().map(|released| {
-            let f = self.path_to_rest_place().len() as f32 / attacker.speed();
-            let t = Timestamp::from_float_seconds(f);
-            let started_resting = attacker.arrival() + t;
+            let distance = self.path_to_rest_place().len() as f32;
+            let started_resting = attacker.time_after_distance(attacker.arrival(), distance);
             if released > started_resting {
                 released
             } else {
@@ -5,20 +5,25 @@ use crate::shared_types::Timestamp;
 use std::collections::HashMap;
 
 struct TestHobo {
+    id: i64,
     max_hp: u32,
     speed: f32,
     hurried: bool,
     arrival: Timestamp,
     released: Option<Timestamp>,
     effects_strength: i32,
+    speed_effects: Vec<(Timestamp, i32)>,
 }
 struct TestTown {
     building_auras: HashMap<TownLayoutIndex, Vec<TestAura>>,
+    shooters: HashMap<TownLayoutIndex, Vec<Shooter<usize>>>,
 }
 #[derive(Copy, Clone, Debug)]
 struct TestAura {
     id: usize,
     strength: i32,
+    since: Timestamp,
+    until: Option<Timestamp>,
 }
 
 const Y: usize = TOWN_LANE_Y;
@@ -125,20 +130,127 @@ fn unhurried_hobo_released() {
     assert_eq!(hobo_hp_left, 97);
 }
 
+#[test]
+fn defend_position_next_to_lane() {
+    let mut map = TownMap::new(TownLayout::Basic);
+    assert!(map.can_defend_at((2, Y - 1)));
+    assert!(map.can_defend_at((2, Y + 1)));
+    assert!(!map.can_defend_at((2, Y)));
+    assert!(!map.can_defend_at((2, Y - 2)));
+    assert!(!map.can_defend_at((TOWN_X, Y - 1)));
+    map[(2, Y - 1)] = TownTileType::BUILDING(BuildingType::Tree);
+    assert!(!map.can_defend_at((2, Y - 1)));
+}
+
+#[test]
+fn defender_leaving_mid_visit() {
+    let mut hobo = TestHobo::new();
+    hobo.speed = 0.5;
+    let mut town = TestTown::new();
+    let left = Some(Timestamp::from_seconds(5));
+
+    // The hobo reaches (6, Y) after 4s and (4, Y) after 8s
+    let mut passed_before_leaving = TestAura::new(3);
+    passed_before_leaving.until = left;
+    town.add_aura(passed_before_leaving, &[(6, Y)]);
+    let mut passed_after_leaving = TestAura::new(5);
+    passed_after_leaving.until = left;
+    town.add_aura(passed_after_leaving, &[(4, Y)]);
+    let mut arrived_after_passing = TestAura::new(7);
+    arrived_after_passing.since = Timestamp::from_seconds(6);
+    town.add_aura(arrived_after_passing, &[(6, Y)]);
+
+    let now = Timestamp::from_seconds(10);
+    assert_eq!(town.aura_damage(&hobo, now), 3);
+    assert_eq!(town.hp_left(&hobo, now), 97);
+}
+
+#[test]
+fn slowed_hobo_stays_longer() {
+    let mut hobo = TestHobo::new();
+    hobo.speed = 1.0;
+    hobo.speed_effects = vec![(Timestamp::from_seconds(2), -50)];
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(1), &[(4, Y)]);
+
+    // 2 tiles at full speed, then 2 more tiles at half speed
+    assert_eq!(town.hp_left(&hobo, Timestamp::from_seconds(5)), 100);
+    assert_eq!(town.hp_left(&hobo, Timestamp::from_seconds(6)), 99);
+
+    // 9 tiles plus one to swim out of sight, 8 of them at half speed
+    assert!(!town.hobo_left_town(&hobo, Timestamp::from_seconds(17)));
+    assert!(town.hobo_left_town(&hobo, Timestamp::from_seconds(18)));
+}
+
+#[test]
+fn departure_time_matches_hobo_left_town() {
+    let second = Timestamp::from_seconds(1);
+    let town = TestTown::new();
+
+    let mut hobo = TestHobo::new();
+    hobo.speed_effects = vec![(Timestamp::from_seconds(4), -50)];
+    let departure = town.departure_time(&hobo).unwrap();
+    assert!(!town.hobo_left_town(&hobo, departure - second));
+    assert!(town.hobo_left_town(&hobo, departure + second));
+
+    hobo.hurried = false;
+    assert_eq!(town.departure_time(&hobo), None);
+    hobo.released = Some(Timestamp::from_seconds(30));
+    let departure = town.departure_time(&hobo).unwrap();
+    assert!(!town.hobo_left_town(&hobo, departure - second));
+    assert!(town.hobo_left_town(&hobo, departure + second));
+}
+
+#[test]
+fn shots_target_lowest_id_first() {
+    let now = Timestamp::from_seconds(10);
+    let mut town = TestTown::new();
+    let shooter = Shooter {
+        id: 0,
+        power: 5,
+        shots_per_cycle: 1,
+    };
+    for x in 6..=8 {
+        town.shooters.insert((x, Y), vec![shooter.clone()]);
+    }
+
+    let mut first = TestHobo::new();
+    first.id = 1;
+    let mut second = TestHobo::new();
+    second.id = 2;
+
+    // Hobos walk 2s per tile, hence they are in range for the cycles starting at 0s and 4s
+    let visitors = [second, first];
+    assert_eq!(town.shot_damage(&visitors, now), vec![0, 10]);
+    assert_eq!(town.hp_left_in_group(&visitors, now), vec![100, 90]);
+
+    // Satisfied visitors are no longer targeted
+    let [second, mut first] = visitors;
+    first.max_hp = 5;
+    let visitors = [second, first];
+    assert_eq!(town.shot_damage(&visitors, now), vec![5, 5]);
+    assert_eq!(town.hp_left_in_group(&visitors, now), vec![95, 0]);
+}
+
 impl TestHobo {
     fn new() -> Self {
         TestHobo {
+            id: 0,
             max_hp: 100,
             speed: 0.5,
             hurried: true,
             arrival: Timestamp::from_seconds(0),
             released: None,
             effects_strength: 0,
+            speed_effects: vec![],
         }
     }
 }
 
 impl IAttackingHobo for TestHobo {
+    fn id(&self) -> i64 {
+        self.id
+    }
     fn max_hp(&self) -> u32 {
         self.max_hp
     }
@@ -157,19 +269,29 @@ impl IAttackingHobo for TestHobo {
     fn effects_strength(&self) -> i32 {
         self.effects_strength
     }
+    fn speed_effects(&self) -> Vec<(Timestamp, i32)> {
+        self.speed_effects.clone()
+    }
 }
 impl ITownLayoutMarker for TestTown {
     const LAYOUT: TownLayout = TownLayout::Basic;
 }
 impl IDefendingTown for TestTown {
     type AuraId = usize;
-    fn auras_in_range(&self, index: &Self::Index, _time: Timestamp) -> Vec<(Self::AuraId, i32)> {
+    fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
         if let Some(auras) = self.building_auras.get(index) {
-            auras.iter().map(|aura| (aura.id, aura.strength)).collect()
+            auras
+                .iter()
+                .filter(|aura| aura_active(aura.since, aura.until, time))
+                .map(|aura| (aura.id, aura.strength))
+                .collect()
         } else {
             Vec::new()
         }
     }
+    fn shooters_in_range(&self, index: &Self::Index, _time: Timestamp) -> Vec<Shooter<usize>> {
+        self.shooters.get(index).cloned().unwrap_or_default()
+    }
 }
 impl TestAura {
     pub fn new(strength: i32) -> Self {
@@ -178,13 +300,19 @@ impl TestAura {
         unsafe {
             N = N + 1;
         }
-        TestAura { id, strength }
+        TestAura {
+            id,
+            strength,
+            since: Timestamp::from_seconds(0),
+            until: None,
+        }
     }
 }
 impl TestTown {
     pub fn new() -> Self {
         TestTown {
             building_auras: HashMap::new(),
+            shooters: HashMap::new(),
         }
     }
     fn add_aura(&mut self, aura: TestAura, idx: &[TownLayoutIndex]) {
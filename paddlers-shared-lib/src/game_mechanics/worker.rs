@@ -1,4 +1,5 @@
 use crate::models::{ResourceType, TaskType};
+use crate::shared_types::Timestamp;
 
 pub const fn unit_speed_to_worker_tiles_per_second(base_speed: f32) -> f32 {
     base_speed
@@ -12,6 +13,14 @@ pub const fn hero_mana_regeneration_per_hour() -> i32 {
     10
 }
 
+/// Distance (in tiles) in which a defending worker affects visitors
+pub const DEFENDER_RANGE: f32 = 1.5;
+
+/// Strength of the aura a worker provides while standing in a defend position
+pub const fn defender_aura_strength(level: i32) -> i32 {
+    1 + level / 2
+}
+
 pub fn hero_resource_collection_per_hour(task: TaskType) -> Option<(ResourceType, i32)> {
     match task {
         TaskType::ChopTree => Some((ResourceType::Logs, 5)),
@@ -20,6 +29,26 @@ pub fn hero_resource_collection_per_hour(task: TaskType) -> Option<(ResourceType
     }
 }
 
+/// Income of a regular gain with the given rate per hour, accumulated since `last_update`.
+/// Returns the number of completed intervals and the point in time until which they are accounted for.
+/// A partially completed interval is not counted and remains for later.
+pub fn accumulated_income(
+    last_update: Timestamp,
+    now: Timestamp,
+    per_hour: i32,
+) -> (i64, Timestamp) {
+    let interval = Timestamp::from_millis(3_600_000 / per_hour as i64);
+    let n = (now - last_update).micros() / interval.micros();
+    if n > 0 {
+        (n, last_update + Timestamp::from_us(interval.micros() * n))
+    } else {
+        (0, last_update)
+    }
+}
+
 pub const fn hero_level_exp(now: i32) -> i32 {
     now * 100
 }
+
+#[cfg(test)]
+mod worker_test;
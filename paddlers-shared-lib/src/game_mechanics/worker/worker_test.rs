@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn income_keeps_partial_intervals() {
+    let start = Timestamp::from_seconds(100);
+    // 60 per hour is one per minute
+    let (n, until) = accumulated_income(start, start + Timestamp::from_seconds(150), 60);
+    assert_eq!(n, 2);
+    assert_eq!(until, start + Timestamp::from_seconds(120));
+
+    // Collecting in several steps gives the same as collecting at once
+    let (m, until) = accumulated_income(until, start + Timestamp::from_seconds(185), 60);
+    assert_eq!(n + m, 3);
+    assert_eq!(until, start + Timestamp::from_seconds(180));
+}
+
+#[test]
+fn no_income_before_first_interval() {
+    let start = Timestamp::from_seconds(100);
+    let now = start + Timestamp::from_seconds(59);
+    assert_eq!(accumulated_income(start, now, 60), (0, start));
+    assert_eq!(accumulated_income(now, start, 60), (0, now));
+}
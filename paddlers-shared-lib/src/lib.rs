@@ -21,6 +21,8 @@ pub extern crate strum;
 pub mod macros;
 
 pub mod api;
+pub mod balancing;
+pub mod clock;
 pub mod config;
 pub mod display;
 pub mod graphql_types;
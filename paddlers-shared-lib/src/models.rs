@@ -17,7 +17,8 @@ use ::diesel_derive_enum::DbEnum;
 #[cfg(feature = "sql_db")]
 use super::schema::{
     abilities, attacks, attacks_to_hobos, buildings, effects, hobos, players, resources, rewards,
-    streams, tasks, villages, visit_reports, worker_flags, workers,
+    scheduled_events, season_standings, seasons, streams, tasks, villages, visit_reports,
+    worker_flags, workers, worlds,
 };
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
@@ -48,6 +49,9 @@ pub struct Player {
     pub karma: i64,
     pub display_name: String,
     pub story_state: StoryState,
+    pub world_id: i64,
+    /// Random code to invite friends into the neighbourhood
+    pub invite_code: String,
 }
 
 #[cfg(feature = "sql_db")]
@@ -57,6 +61,8 @@ pub struct NewPlayer {
     pub uuid: uuid::Uuid,
     pub karma: i64,
     pub display_name: String,
+    pub world_id: i64,
+    pub invite_code: String,
 }
 
 #[cfg(feature = "sql_db")]
@@ -99,6 +105,8 @@ pub struct Hobo {
     pub hp: i64,
     /// If in a hurry, hobos will not stop in a town they are visiting but swim through directly
     pub hurried: bool,
+    /// Set while the hobo swims back to its home village after a visit
+    pub returns_at: Option<NaiveDateTime>,
 }
 
 #[cfg(feature = "sql_db")]
@@ -134,7 +142,22 @@ pub struct NewAttack {
 }
 
 #[cfg(feature = "sql_db")]
-#[derive(Debug, Queryable, Insertable)]
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[table_name = "past_attacks"]
+/// Summary of an attack that is over, it has the same ID the attack had
+pub struct PastAttack {
+    pub id: i64,
+    pub departure: NaiveDateTime,
+    pub arrival: NaiveDateTime,
+    pub finished: NaiveDateTime,
+    pub origin_village_id: Option<i64>,
+    pub destination_village_id: i64,
+    pub visitors: i32,
+    pub satisfied_visitors: i32,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Clone, Queryable, Insertable)]
 #[table_name = "attacks_to_hobos"]
 pub struct AttackToHobo {
     pub attack_id: i64,
@@ -252,6 +275,7 @@ pub struct Stream {
     pub id: i64,
     pub start_x: f32,
     pub control_points: Vec<f32>,
+    pub world_id: i64,
 }
 
 #[derive(Insertable, Debug)]
@@ -260,6 +284,7 @@ pub struct Stream {
 pub struct NewStream {
     pub start_x: f32,
     pub control_points: Vec<f32>,
+    pub world_id: i64,
 }
 
 #[derive(Debug, Clone, Copy, Queryable, Identifiable)]
@@ -271,6 +296,7 @@ pub struct Village {
     pub stream_id: i64,
     pub player_id: Option<i64>,
     pub faith: i16,
+    pub world_id: i64,
 }
 
 #[derive(Insertable, Debug)]
@@ -282,6 +308,7 @@ pub struct NewVillage {
     pub stream_id: i64,
     pub player_id: Option<i64>,
     pub faith: Option<i16>,
+    pub world_id: i64,
 }
 
 #[derive(Debug, Clone, Copy, Queryable, AsChangeset)]
@@ -395,3 +422,86 @@ pub struct NewReward {
     pub resource_type: ResourceType,
     pub amount: i64,
 }
+
+#[derive(Debug, Clone, Copy, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+/// An event that the game-master has queued up for delayed execution
+pub struct ScheduledEvent {
+    pub id: i64,
+    pub event_type: ScheduledEventType,
+    pub due: NaiveDateTime,
+    pub task_id: Option<i64>,
+    pub village_id: Option<i64>,
+    pub hobo_id: Option<i64>,
+    pub attack_id: Option<i64>,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "scheduled_events"]
+pub struct NewScheduledEvent {
+    pub event_type: ScheduledEventType,
+    pub due: NaiveDateTime,
+    pub task_id: Option<i64>,
+    pub village_id: Option<i64>,
+    pub hobo_id: Option<i64>,
+    pub attack_id: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg(feature = "sql_db")]
+#[derive(DbEnum)]
+#[DieselType = "Scheduled_event_type"]
+pub enum ScheduledEventType {
+    WorkerTask,
+    CheckRestingVisitors,
+    CheckVisitorHp,
+    CheckAttack,
+}
+
+#[derive(Debug, Clone, Copy, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+/// The world is reset at the end of a season, the current season has no end time
+pub struct Season {
+    pub id: i64,
+    pub map_seed: i64,
+    pub start_time: NaiveDateTime,
+    pub end_time: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "seasons"]
+pub struct NewSeason {
+    pub map_seed: i64,
+    pub start_time: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Queryable, Insertable)]
+#[cfg(feature = "sql_db")]
+#[table_name = "season_standings"]
+/// Final rank and achievements of a player in a past season
+pub struct SeasonStanding {
+    pub season_id: i64,
+    pub player_id: i64,
+    pub rank: i64,
+    pub karma: i64,
+    pub story_state: StoryState,
+    pub village_count: i64,
+    pub world_id: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+/// Worlds are played side by side, each player and village belongs to exactly one of them
+pub struct World {
+    pub id: i64,
+    pub display_name: String,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "worlds"]
+pub struct NewWorld {
+    pub display_name: String,
+}
@@ -1,5 +1,6 @@
 pub use crate::api::error::PadlApiError;
 pub use crate::api::keys::*;
+pub use crate::clock::{Clock, GameClock};
 pub use crate::config::Config;
 pub use crate::models::*;
 pub use crate::shared_types::{PadlId, Timestamp};
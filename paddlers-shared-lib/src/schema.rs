@@ -75,6 +75,23 @@ table! {
         speed -> Float4,
         hp -> Int8,
         hurried -> Bool,
+        returns_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    past_attacks (id) {
+        id -> Int8,
+        departure -> Timestamp,
+        arrival -> Timestamp,
+        finished -> Timestamp,
+        origin_village_id -> Nullable<Int8>,
+        destination_village_id -> Int8,
+        visitors -> Int4,
+        satisfied_visitors -> Int4,
     }
 }
 
@@ -88,6 +105,8 @@ table! {
         karma -> Int8,
         display_name -> Varchar,
         story_state -> Story_state_type,
+        world_id -> Int8,
+        invite_code -> Varchar,
     }
 }
 
@@ -114,6 +133,48 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    scheduled_events (id) {
+        id -> Int8,
+        event_type -> Scheduled_event_type,
+        due -> Timestamp,
+        task_id -> Nullable<Int8>,
+        village_id -> Nullable<Int8>,
+        hobo_id -> Nullable<Int8>,
+        attack_id -> Nullable<Int8>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    season_standings (season_id, player_id) {
+        season_id -> Int8,
+        player_id -> Int8,
+        rank -> Int8,
+        karma -> Int8,
+        story_state -> Story_state_type,
+        village_count -> Int8,
+        world_id -> Int8,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    seasons (id) {
+        id -> Int8,
+        map_seed -> Int8,
+        start_time -> Timestamp,
+        end_time -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -122,6 +183,7 @@ table! {
         id -> Int8,
         start_x -> Float4,
         control_points -> Array<Float4>,
+        world_id -> Int8,
     }
 }
 
@@ -151,6 +213,7 @@ table! {
         stream_id -> Int8,
         player_id -> Nullable<Int8>,
         faith -> Int2,
+        world_id -> Int8,
     }
 }
 
@@ -195,6 +258,16 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    worlds (id) {
+        id -> Int8,
+        display_name -> Varchar,
+    }
+}
+
 joinable!(abilities -> workers (worker_id));
 joinable!(attacks_to_hobos -> attacks (attack_id));
 joinable!(attacks_to_hobos -> hobos (hobo_id));
@@ -203,10 +276,20 @@ joinable!(effects -> hobos (hobo_id));
 joinable!(hobos -> villages (home));
 joinable!(resources -> villages (village_id));
 joinable!(rewards -> visit_reports (visit_report_id));
+joinable!(scheduled_events -> attacks (attack_id));
+joinable!(scheduled_events -> hobos (hobo_id));
+joinable!(scheduled_events -> tasks (task_id));
+joinable!(scheduled_events -> villages (village_id));
+joinable!(season_standings -> players (player_id));
+joinable!(season_standings -> seasons (season_id));
+joinable!(season_standings -> worlds (world_id));
 joinable!(tasks -> hobos (target_hobo_id));
 joinable!(tasks -> workers (worker_id));
+joinable!(players -> worlds (world_id));
+joinable!(streams -> worlds (world_id));
 joinable!(villages -> players (player_id));
 joinable!(villages -> streams (stream_id));
+joinable!(villages -> worlds (world_id));
 joinable!(visit_reports -> villages (village_id));
 joinable!(worker_flags -> workers (worker_id));
 joinable!(workers -> villages (home));
@@ -218,13 +301,18 @@ allow_tables_to_appear_in_same_query!(
     buildings,
     effects,
     hobos,
+    past_attacks,
     players,
     resources,
     rewards,
+    scheduled_events,
+    season_standings,
+    seasons,
     streams,
     tasks,
     villages,
     visit_reports,
     worker_flags,
     workers,
+    worlds,
 );
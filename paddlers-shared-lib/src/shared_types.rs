@@ -85,3 +85,11 @@ impl From<NaiveDateTime> for Timestamp {
         Timestamp::from_us(other.timestamp() * 1_000_000 + other.timestamp_subsec_micros() as i64)
     }
 }
+impl From<Timestamp> for NaiveDateTime {
+    fn from(other: Timestamp) -> Self {
+        NaiveDateTime::from_timestamp(
+            other.micros().div_euclid(1_000_000),
+            (other.micros().rem_euclid(1_000_000) * 1000) as u32,
+        )
+    }
+}
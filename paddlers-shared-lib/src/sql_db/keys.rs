@@ -20,6 +20,21 @@ impl Village {
     pub fn owner(&self) -> Option<PlayerKey> {
         Some(PlayerKey(self.player_id?))
     }
+    pub fn world(&self) -> WorldKey {
+        WorldKey(self.world_id)
+    }
+}
+
+impl Player {
+    pub fn world(&self) -> WorldKey {
+        WorldKey(self.world_id)
+    }
+}
+
+impl Stream {
+    pub fn world(&self) -> WorldKey {
+        WorldKey(self.world_id)
+    }
 }
 
 impl VisitReport {
@@ -36,3 +51,9 @@ impl Attack {
         self.origin_village_id.map(VillageKey)
     }
 }
+
+impl PastAttack {
+    pub fn origin(&self) -> Option<VillageKey> {
+        self.origin_village_id.map(VillageKey)
+    }
+}
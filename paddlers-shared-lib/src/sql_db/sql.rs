@@ -1,9 +1,33 @@
+#[cfg(feature = "game_mechanics")]
+use crate::game_mechanics::worker::*;
 use crate::prelude::*;
 use crate::schema::*;
 use diesel::prelude::*;
 
+/// Income that a worker has gathered since the last update of one of its flags.
+/// It is not stored in the database until the game-master collects it.
+#[cfg(feature = "game_mechanics")]
+#[derive(Debug, Clone, Copy)]
+pub struct PendingIncome {
+    pub flag: WorkerFlag,
+    /// Point in time until which the income is accounted for
+    pub until: chrono::NaiveDateTime,
+    pub income: Income,
+}
+#[cfg(feature = "game_mechanics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Income {
+    Mana(i32),
+    Resource(ResourceType, i64),
+}
+
 pub trait GameDB {
     fn dbconn(&self) -> &PgConnection;
+    /// Current game time, used instead of the DB server time in all queries.
+    /// Implementors with an injected clock should override this.
+    fn now(&self) -> chrono::NaiveDateTime {
+        chrono::Utc::now().naive_utc()
+    }
 
     fn player(&self, player_id: PlayerKey) -> Option<Player> {
         players::table
@@ -19,6 +43,13 @@ pub trait GameDB {
             .optional()
             .expect("Error loading data")
     }
+    fn player_by_invite_code(&self, code: &str) -> Option<Player> {
+        players::table
+            .filter(players::invite_code.eq(code))
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
     fn player_by_village(&self, vid: VillageKey) -> Option<Player> {
         villages::table
             .filter(villages::id.eq(vid.num()))
@@ -77,7 +108,7 @@ pub trait GameDB {
             .inner_join(tasks::table)
             .filter(workers::home.eq(village.num()))
             .filter(tasks::task_type.eq_any(jobs))
-            .filter(tasks::start_time.lt(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.lt(self.now()))
             .select(workers::all_columns)
             .distinct()
             .load::<Worker>(self.dbconn())
@@ -101,6 +132,14 @@ pub trait GameDB {
             .first::<i64>(self.dbconn())
             .expect("Error loading data") as usize
     }
+    fn attack(&self, key: AttackKey) -> Option<Attack> {
+        let results = attacks::table
+            .filter(attacks::id.eq(key.num()))
+            .get_result::<Attack>(self.dbconn())
+            .optional()
+            .expect("Error loading data");
+        results
+    }
     fn attacks(&self, village: VillageKey, min_id: Option<i64>) -> Vec<Attack> {
         let results = attacks::table
             .filter(attacks::destination_village_id.eq(village.num()))
@@ -120,6 +159,38 @@ pub trait GameDB {
             .expect("Error loading data");
         results as usize
     }
+    /// Attacks on the village ordered by ID, starting after the given ID
+    fn attacks_page(&self, village: VillageKey, after: Option<i64>, limit: i64) -> Vec<Attack> {
+        attacks::table
+            .filter(attacks::destination_village_id.eq(village.num()))
+            .filter(attacks::id.gt(after.unwrap_or(0)))
+            .order_by(attacks::id)
+            .limit(limit)
+            .load::<Attack>(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Finished attacks on the village ordered by ID, starting after the given ID
+    fn past_attacks_page(
+        &self,
+        village: VillageKey,
+        after: Option<i64>,
+        limit: i64,
+    ) -> Vec<PastAttack> {
+        past_attacks::table
+            .filter(past_attacks::destination_village_id.eq(village.num()))
+            .filter(past_attacks::id.gt(after.unwrap_or(0)))
+            .order_by(past_attacks::id)
+            .limit(limit)
+            .load::<PastAttack>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn past_attacks_count(&self, village: VillageKey) -> usize {
+        past_attacks::table
+            .filter(past_attacks::destination_village_id.eq(village.num()))
+            .select(diesel::dsl::count(past_attacks::id))
+            .first::<i64>(self.dbconn())
+            .expect("Error loading data") as usize
+    }
     fn attack_hobos(&self, atk: AttackKey) -> Vec<Hobo> {
         let results = attacks_to_hobos::table
             .inner_join(hobos::table)
@@ -151,6 +222,16 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Same as `attack_hobos_with_attack_info` for several attacks at once
+    fn attacks_hobos_with_attack_info(&self, attacks: &[AttackKey]) -> Vec<(Hobo, AttackToHobo)> {
+        let ids: Vec<i64> = attacks.iter().map(AttackKey::num).collect();
+        attacks_to_hobos::table
+            .inner_join(hobos::table)
+            .filter(attacks_to_hobos::attack_id.eq_any(ids))
+            .select((hobos::all_columns, attacks_to_hobos::all_columns))
+            .load::<(Hobo, AttackToHobo)>(self.dbconn())
+            .expect("Error loading data")
+    }
     fn attack_hobos_satisfied(&self, atk: &Attack) -> Vec<Hobo> {
         let results = attacks_to_hobos::table
             .inner_join(hobos::table)
@@ -175,7 +256,7 @@ pub trait GameDB {
             // condition for "resting"
             .filter(hobos::hurried.eq(false))
             .filter(attacks_to_hobos::satisfied.is_null())
-            .filter(attacks::arrival.le(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(attacks::arrival.le(self.now()))
             //
             .order_by(attacks::arrival.asc())
             .select((hobos::all_columns, attacks::id))
@@ -240,6 +321,23 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Tasks of several workers at once, ordered by ID
+    fn workers_tasks(&self, workers: &[WorkerKey]) -> Vec<Task> {
+        let ids: Vec<i64> = workers.iter().map(WorkerKey::num).collect();
+        tasks::table
+            .filter(tasks::worker_id.eq_any(ids))
+            .order_by(tasks::id)
+            .load::<Task>(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Abilities of several workers at once
+    fn workers_abilities(&self, workers: &[WorkerKey]) -> Vec<Ability> {
+        let ids: Vec<i64> = workers.iter().map(WorkerKey::num).collect();
+        abilities::table
+            .filter(abilities::worker_id.eq_any(ids))
+            .load::<Ability>(self.dbconn())
+            .expect("Error loading data")
+    }
     fn worker_ability(&self, worker_id: WorkerKey, ability_type: AbilityType) -> Option<Ability> {
         abilities::table
             .find((ability_type, worker_id.num()))
@@ -250,7 +348,7 @@ pub trait GameDB {
     fn past_worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task> {
         let results = tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
-            .filter(tasks::start_time.lt(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.lt(self.now()))
             .order(tasks::start_time.asc())
             .limit(500)
             .load::<Task>(self.dbconn())
@@ -260,7 +358,7 @@ pub trait GameDB {
     fn earliest_future_task(&self, worker_id: WorkerKey) -> Option<Task> {
         tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
-            .filter(tasks::start_time.ge(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.ge(self.now()))
             .order(tasks::start_time.asc())
             .first(self.dbconn())
             .optional()
@@ -284,7 +382,7 @@ pub trait GameDB {
     fn current_task(&self, worker_id: WorkerKey) -> Option<Task> {
         tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
-            .filter(tasks::start_time.le(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.le(self.now()))
             .order(tasks::start_time.asc())
             .first(self.dbconn())
             .optional()
@@ -306,14 +404,25 @@ pub trait GameDB {
             }
         }
     }
-    fn streams(&self, low_x: f32, high_x: f32) -> Vec<Stream> {
+    fn streams(&self, world: WorldKey, low_x: f32, high_x: f32) -> Vec<Stream> {
         let results = streams::table
+            .filter(streams::world_id.eq(world.num()))
             .filter(streams::start_x.ge(low_x))
             .filter(streams::start_x.le(high_x))
+            .order_by(streams::start_x.asc())
             .load::<Stream>(self.dbconn())
             .expect("Error loading data");
         results
     }
+    /// Width of the world's map, measured from x=0 to the right of the last stream
+    fn map_width(&self, world: WorldKey) -> i32 {
+        let max_x: Option<f32> = streams::table
+            .filter(streams::world_id.eq(world.num()))
+            .select(diesel::dsl::max(streams::start_x))
+            .first(self.dbconn())
+            .expect("Error in look up");
+        max_x.map(|x| x.ceil() as i32 + 1).unwrap_or(0)
+    }
     fn village(&self, village: VillageKey) -> Option<Village> {
         villages::table
             .find(village.num())
@@ -321,8 +430,22 @@ pub trait GameDB {
             .optional()
             .expect("Error loading village")
     }
-    fn village_at(&self, x: f32, y: f32) -> Option<Village> {
+    /// Owners of several villages at once, villages without owner are skipped
+    fn village_owners(&self, villages: &[VillageKey]) -> Vec<(VillageKey, Player)> {
+        let ids: Vec<i64> = villages.iter().map(VillageKey::num).collect();
         villages::table
+            .inner_join(players::table)
+            .filter(villages::id.eq_any(ids))
+            .select((villages::id, players::all_columns))
+            .load::<(i64, Player)>(self.dbconn())
+            .expect("Error loading data")
+            .into_iter()
+            .map(|(vid, player)| (VillageKey(vid), player))
+            .collect()
+    }
+    fn village_at(&self, world: WorldKey, x: f32, y: f32) -> Option<Village> {
+        villages::table
+            .filter(villages::world_id.eq(world.num()))
             .filter(villages::x.ge(x))
             .filter(villages::x.lt(1.0 + x))
             .filter(villages::y.ge(y))
@@ -331,8 +454,9 @@ pub trait GameDB {
             .optional()
             .expect("Error looking up village from position")
     }
-    fn villages(&self, low_x: f32, high_x: f32) -> Vec<Village> {
+    fn villages(&self, world: WorldKey, low_x: f32, high_x: f32) -> Vec<Village> {
         let results = villages::table
+            .filter(villages::world_id.eq(world.num()))
             .filter(villages::x.ge(low_x))
             .filter(villages::x.le(high_x))
             .load::<Village>(self.dbconn())
@@ -359,6 +483,21 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    fn all_anarchist_villages(&self) -> Vec<Village> {
+        let results = villages::table
+            .filter(villages::player_id.is_null())
+            .load::<Village>(self.dbconn())
+            .expect("Error loading data");
+        results
+    }
+    fn anarchist_villages(&self, world: WorldKey) -> Vec<Village> {
+        let results = villages::table
+            .filter(villages::world_id.eq(world.num()))
+            .filter(villages::player_id.is_null())
+            .load::<Village>(self.dbconn())
+            .expect("Error loading data");
+        results
+    }
     fn village_hobos(&self, v: VillageKey) -> Vec<Hobo> {
         let results = hobos::table
             .filter(hobos::home.eq(v.num()))
@@ -368,6 +507,23 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Hobos of the village that are currently at home, neither visiting another village nor on their way back
+    fn idle_hobos(&self, v: VillageKey) -> Vec<Hobo> {
+        let results = hobos::table
+            .filter(hobos::home.eq(v.num()))
+            .filter(diesel::dsl::not(diesel::dsl::exists(
+                attacks_to_hobos::table.filter(attacks_to_hobos::hobo_id.eq(hobos::id)),
+            )))
+            .filter(
+                hobos::returns_at
+                    .is_null()
+                    .or(hobos::returns_at.le(self.now())),
+            )
+            .limit(500)
+            .load::<Hobo>(self.dbconn())
+            .expect("Error loading data");
+        results
+    }
     fn effects_on_hobo(&self, hobo: HoboKey) -> Vec<Effect> {
         let results = effects::table
             .filter(effects::hobo_id.eq(hobo.num()))
@@ -376,6 +532,14 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Effects on several hobos at once
+    fn effects_on_hobos(&self, hobos: &[HoboKey]) -> Vec<Effect> {
+        let ids: Vec<i64> = hobos.iter().map(HoboKey::num).collect();
+        effects::table
+            .filter(effects::hobo_id.eq_any(ids))
+            .load::<Effect>(self.dbconn())
+            .expect("Error loading data")
+    }
     fn hobo_attack_info(&self, hid: HoboKey) -> Vec<(Attack, AttackToHobo)> {
         attacks_to_hobos::table
             .inner_join(attacks::table)
@@ -434,15 +598,92 @@ pub trait GameDB {
             .first(self.dbconn())
             .expect("Error in look up")
     }
-    fn players_sorted_by_karma(&self, start_index: i64, limit: i64) -> Vec<Player> {
-        let results = players::table
+    /// Players of the world ranked by karma, starting after the player with the given karma and ID if any
+    fn players_sorted_by_karma(
+        &self,
+        world: WorldKey,
+        after: Option<(i64, i64)>,
+        limit: i64,
+    ) -> Vec<Player> {
+        let mut query = players::table
+            .filter(players::world_id.eq(world.num()))
+            .into_boxed();
+        if let Some((karma, id)) = after {
+            query = query.filter(
+                players::karma
+                    .lt(karma)
+                    .or(players::karma.eq(karma).and(players::id.gt(id))),
+            );
+        }
+        let results = query
             .order_by(players::karma.desc())
-            .offset(start_index)
+            // Stable order among players with equal karma, for pagination
+            .then_order_by(players::id)
             .limit(limit)
             .load::<Player>(self.dbconn())
             .expect("Error loading data");
         results
     }
+    /// The season that is currently being played, it has no end time
+    fn current_season(&self) -> Option<Season> {
+        seasons::table
+            .filter(seasons::end_time.is_null())
+            .order_by(seasons::id.desc())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading season")
+    }
+    fn season(&self, key: SeasonKey) -> Option<Season> {
+        seasons::table
+            .filter(seasons::id.eq(key.num()))
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading season")
+    }
+    /// Seasons that have ended, the most recent first
+    fn past_seasons(&self) -> Vec<Season> {
+        seasons::table
+            .filter(seasons::end_time.is_not_null())
+            .order_by(seasons::id.desc())
+            .load(self.dbconn())
+            .expect("Error loading seasons")
+    }
+    fn season_standings(
+        &self,
+        season: SeasonKey,
+        world: WorldKey,
+        start_index: i64,
+        limit: i64,
+    ) -> Vec<SeasonStanding> {
+        season_standings::table
+            .filter(season_standings::season_id.eq(season.num()))
+            .filter(season_standings::world_id.eq(world.num()))
+            .order_by(season_standings::rank.asc())
+            .offset(start_index)
+            .limit(limit)
+            .load(self.dbconn())
+            .expect("Error loading season standings")
+    }
+    fn world(&self, key: WorldKey) -> Option<World> {
+        worlds::table
+            .find(key.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading world")
+    }
+    fn worlds(&self) -> Vec<World> {
+        worlds::table
+            .order_by(worlds::id.asc())
+            .load(self.dbconn())
+            .expect("Error loading worlds")
+    }
+    fn world_player_count(&self, world: WorldKey) -> i64 {
+        players::table
+            .filter(players::world_id.eq(world.num()))
+            .select(diesel::dsl::count(players::id))
+            .first(self.dbconn())
+            .expect("Error in look up")
+    }
     fn report(&self, id: VisitReportKey) -> Option<VisitReport> {
         visit_reports::table
             .filter(visit_reports::id.eq(id.num()))
@@ -459,6 +700,23 @@ pub trait GameDB {
             .expect("Error loading visit reports");
         results
     }
+    /// Reports of the village ordered by ID, starting after the given ID
+    fn reports_page(&self, v: VillageKey, after: Option<i64>, limit: i64) -> Vec<VisitReport> {
+        visit_reports::table
+            .filter(visit_reports::village_id.eq(v.num()))
+            .filter(visit_reports::id.gt(after.unwrap_or(0)))
+            .order_by(visit_reports::id)
+            .limit(limit)
+            .load::<VisitReport>(self.dbconn())
+            .expect("Error loading visit reports")
+    }
+    fn reports_count(&self, v: VillageKey) -> usize {
+        visit_reports::table
+            .filter(visit_reports::village_id.eq(v.num()))
+            .select(diesel::dsl::count(visit_reports::id))
+            .first::<i64>(self.dbconn())
+            .expect("Error loading visit reports") as usize
+    }
     fn rewards(&self, vr: VisitReportKey) -> Vec<(ResourceType, i64)> {
         visit_reports::table
             .inner_join(rewards::table)
@@ -478,4 +736,103 @@ pub trait GameDB {
             .load::<(ResourceType, i64)>(self.dbconn())
             .expect("Error loading rewards")
     }
+    /// All reward entries of several reports at once, not summed up per resource
+    fn reports_rewards(&self, reports: &[VisitReportKey]) -> Vec<Reward> {
+        let ids: Vec<i64> = reports.iter().map(VisitReportKey::num).collect();
+        rewards::table
+            .filter(rewards::visit_report_id.eq_any(ids))
+            .load::<Reward>(self.dbconn())
+            .expect("Error loading rewards")
+    }
+    fn worker_flags(&self, worker: WorkerKey) -> Vec<WorkerFlag> {
+        worker_flags::table
+            .filter(worker_flags::worker_id.eq(worker.num()))
+            .get_results(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Flags of several workers at once
+    fn workers_flags(&self, workers: &[WorkerKey]) -> Vec<WorkerFlag> {
+        let ids: Vec<i64> = workers.iter().map(WorkerKey::num).collect();
+        worker_flags::table
+            .filter(worker_flags::worker_id.eq_any(ids))
+            .get_results(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Resources and mana gathered by a worker that have not been stored, yet.
+    /// Income is computed lazily from the worker flags, only for villages that are accessed.
+    #[cfg(feature = "game_mechanics")]
+    fn pending_income(&self, worker: WorkerKey) -> Vec<PendingIncome> {
+        let now: Timestamp = self.now().into();
+        let mut pending = vec![];
+        for flag in self.worker_flags(worker) {
+            let rate = match flag.flag_type {
+                WorkerFlagType::ManaRegeneration => Some((None, hero_mana_regeneration_per_hour())),
+                WorkerFlagType::Work => self
+                    .current_task(worker)
+                    .and_then(|task| hero_resource_collection_per_hour(task.task_type))
+                    .map(|(res, rate)| (Some(res), rate)),
+            };
+            if let Some((resource, per_hour)) = rate {
+                let (n, until) = accumulated_income(flag.last_update.into(), now, per_hour);
+                if n > 0 {
+                    let income = match resource {
+                        Some(res) => Income::Resource(res, n),
+                        None => Income::Mana(n as i32),
+                    };
+                    pending.push(PendingIncome {
+                        flag,
+                        until: until.into(),
+                        income,
+                    });
+                }
+            }
+        }
+        pending
+    }
+    /// Stored resources plus what the workers of the village have gathered since
+    #[cfg(feature = "game_mechanics")]
+    fn resource_with_income(&self, r: ResourceType, v: VillageKey) -> i64 {
+        let pending: i64 = self
+            .workers(v)
+            .iter()
+            .flat_map(|w| self.pending_income(w.key()))
+            .filter_map(|p| match p.income {
+                Income::Resource(res, n) if res == r => Some(n),
+                _ => None,
+            })
+            .sum();
+        self.resource(r, v) + pending
+    }
+    /// Stored mana plus mana regenerated since, limited by the maximum mana
+    #[cfg(feature = "game_mechanics")]
+    fn mana_with_income(&self, worker: &Worker) -> Option<i32> {
+        self.mana_with_flags(worker, &self.worker_flags(worker.key()))
+    }
+    /// Same as `mana_with_income` but with the flags of the worker already loaded
+    #[cfg(feature = "game_mechanics")]
+    fn mana_with_flags(&self, worker: &Worker, flags: &[WorkerFlag]) -> Option<i32> {
+        let now: Timestamp = self.now().into();
+        let pending: i64 = flags
+            .iter()
+            .filter(|flag| flag.flag_type == WorkerFlagType::ManaRegeneration)
+            .map(|flag| {
+                accumulated_income(
+                    flag.last_update.into(),
+                    now,
+                    hero_mana_regeneration_per_hour(),
+                )
+                .0
+            })
+            .sum();
+        worker
+            .mana
+            .map(|mana| hero_max_mana().min(mana + pending as i32))
+    }
+    /// All events stored for delayed execution, sorted by the time they are due
+    fn scheduled_events(&self) -> Vec<ScheduledEvent> {
+        scheduled_events::table
+            .order_by(scheduled_events::due.asc())
+            .load::<ScheduledEvent>(self.dbconn())
+            .expect("Error loading scheduled events")
+    }
 }
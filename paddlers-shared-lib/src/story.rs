@@ -1 +1,2 @@
 pub mod story_state;
+pub mod story_transitions;
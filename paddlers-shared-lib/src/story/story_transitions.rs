@@ -0,0 +1,172 @@
+//! The graph of legal transitions between story states.
+//!
+//! Each state lists its successors together with the preconditions that must hold in the game world
+//! before a player may move on. The game-master uses this to validate transitions requested by clients,
+//! the frontend uses it to decide which transitions it is allowed to request in the first place.
+//!
+//! Checking the preconditions requires a view on the player's game state, which is abstracted by [`IStoryProgress`].
+use super::story_state::StoryState;
+use crate::models::{BuildingType, TaskType};
+
+/// Requirement on the game state that has to be met before a story transition is allowed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StoryCondition {
+    /// At least one building of the given type exists in the player's town
+    BuildingExists(BuildingType),
+    /// The player has collected at least this much karma, which is earned by satisfying visitors
+    MinKarma(i64),
+    /// At least one worker is currently performing the given task
+    WorkerOnTask(TaskType),
+}
+
+/// An edge in the story graph
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StoryTransition {
+    pub to: StoryState,
+    pub conditions: &'static [StoryCondition],
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StoryTransitionError {
+    /// The client assumed a different current state than the one actually stored
+    StateMismatch {
+        expected: StoryState,
+        actual: StoryState,
+    },
+    /// There is no edge between the two states in the story graph
+    InvalidTransition { from: StoryState, to: StoryState },
+    /// The transition exists but the game state does not allow it, yet
+    ConditionNotMet(StoryCondition),
+}
+
+impl StoryState {
+    /// All transitions that lead away from this state, with their preconditions
+    pub fn transitions(&self) -> &'static [StoryTransition] {
+        use StoryCondition::*;
+        match self {
+            StoryState::Initialized => &[StoryTransition {
+                to: StoryState::ServantAccepted,
+                conditions: &[],
+            }],
+            StoryState::ServantAccepted => &[StoryTransition {
+                to: StoryState::TempleBuilt,
+                conditions: &[BuildingExists(BuildingType::Temple)],
+            }],
+            StoryState::TempleBuilt => &[StoryTransition {
+                to: StoryState::VisitorArrived,
+                conditions: &[],
+            }],
+            StoryState::VisitorArrived => &[StoryTransition {
+                to: StoryState::FirstVisitorWelcomed,
+                conditions: &[MinKarma(1)],
+            }],
+            StoryState::FirstVisitorWelcomed => &[StoryTransition {
+                to: StoryState::FlowerPlanted,
+                conditions: &[BuildingExists(BuildingType::BlueFlowers)],
+            }],
+            StoryState::FlowerPlanted => &[StoryTransition {
+                to: StoryState::MoreHappyVisitors,
+                conditions: &[MinKarma(10)],
+            }],
+            StoryState::MoreHappyVisitors => &[StoryTransition {
+                to: StoryState::TreePlanted,
+                conditions: &[BuildingExists(BuildingType::Tree)],
+            }],
+            StoryState::TreePlanted => &[StoryTransition {
+                to: StoryState::StickGatheringStationBuild,
+                conditions: &[BuildingExists(BuildingType::BundlingStation)],
+            }],
+            StoryState::StickGatheringStationBuild => &[StoryTransition {
+                to: StoryState::GatheringSticks,
+                conditions: &[WorkerOnTask(TaskType::GatherSticks)],
+            }],
+            StoryState::GatheringSticks => &[],
+        }
+    }
+    /// Looks up the edge to the given state, if there is one
+    pub fn transition_to(&self, to: StoryState) -> Option<&'static StoryTransition> {
+        self.transitions().iter().find(|t| t.to == to)
+    }
+    pub fn successors(&self) -> impl Iterator<Item = StoryState> {
+        self.transitions().iter().map(|t| t.to)
+    }
+}
+
+/// Game state information required to validate story transitions
+pub trait IStoryProgress {
+    // TO IMPLEMENT
+    fn story_state(&self) -> StoryState;
+    fn karma(&self) -> i64;
+    fn has_building(&self, building_type: BuildingType) -> bool;
+    fn has_worker_on_task(&self, task_type: TaskType) -> bool;
+
+    // PROVIDED
+    fn condition_met(&self, condition: &StoryCondition) -> bool {
+        match condition {
+            StoryCondition::BuildingExists(bt) => self.has_building(*bt),
+            StoryCondition::MinKarma(k) => self.karma() >= *k,
+            StoryCondition::WorkerOnTask(t) => self.has_worker_on_task(*t),
+        }
+    }
+    /// Checks that moving from the current state to `to` is an edge in the story graph and that all its preconditions are met
+    fn check_story_transition(&self, to: StoryState) -> Result<(), StoryTransitionError> {
+        let from = self.story_state();
+        let transition = from
+            .transition_to(to)
+            .ok_or(StoryTransitionError::InvalidTransition { from, to })?;
+        if let Some(c) = transition
+            .conditions
+            .iter()
+            .find(|c| !self.condition_met(c))
+        {
+            return Err(StoryTransitionError::ConditionNotMet(*c));
+        }
+        Ok(())
+    }
+    /// Like `check_story_transition` but also verifies that the caller has the same view of the current state
+    fn check_story_transition_from(
+        &self,
+        before: StoryState,
+        after: StoryState,
+    ) -> Result<(), StoryTransitionError> {
+        let actual = self.story_state();
+        if before != actual {
+            return Err(StoryTransitionError::StateMismatch {
+                expected: before,
+                actual,
+            });
+        }
+        self.check_story_transition(after)
+    }
+    /// All states the player may currently move to
+    fn legal_story_transitions(&self) -> Vec<StoryState> {
+        self.story_state()
+            .transitions()
+            .iter()
+            .filter(|t| t.conditions.iter().all(|c| self.condition_met(c)))
+            .map(|t| t.to)
+            .collect()
+    }
+}
+
+impl std::error::Error for StoryTransitionError {}
+impl std::fmt::Display for StoryTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StoryTransitionError::StateMismatch { expected, actual } => write!(
+                f,
+                "Invalid story state: {:?}, database has: {:?}",
+                expected, actual
+            ),
+            StoryTransitionError::InvalidTransition { from, to } => {
+                write!(f, "No story transition from {:?} to {:?}", from, to)
+            }
+            StoryTransitionError::ConditionNotMet(c) => {
+                write!(f, "Story transition requires {:?}", c)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod story_transitions_test;
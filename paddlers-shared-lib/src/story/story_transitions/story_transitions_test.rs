@@ -0,0 +1,98 @@
+use super::*;
+
+struct TestPlayer {
+    story_state: StoryState,
+    karma: i64,
+    buildings: Vec<BuildingType>,
+    tasks: Vec<TaskType>,
+}
+
+impl IStoryProgress for TestPlayer {
+    fn story_state(&self) -> StoryState {
+        self.story_state
+    }
+    fn karma(&self) -> i64 {
+        self.karma
+    }
+    fn has_building(&self, building_type: BuildingType) -> bool {
+        self.buildings.contains(&building_type)
+    }
+    fn has_worker_on_task(&self, task_type: TaskType) -> bool {
+        self.tasks.contains(&task_type)
+    }
+}
+
+impl TestPlayer {
+    fn new(story_state: StoryState) -> Self {
+        TestPlayer {
+            story_state,
+            karma: 0,
+            buildings: vec![],
+            tasks: vec![],
+        }
+    }
+}
+
+#[test]
+fn cannot_skip_story() {
+    let player = TestPlayer::new(StoryState::Initialized);
+    assert_eq!(
+        player.check_story_transition(StoryState::GatheringSticks),
+        Err(StoryTransitionError::InvalidTransition {
+            from: StoryState::Initialized,
+            to: StoryState::GatheringSticks
+        })
+    );
+    assert_eq!(
+        player.check_story_transition(StoryState::ServantAccepted),
+        Ok(())
+    );
+}
+
+#[test]
+fn preconditions_are_checked() {
+    let mut player = TestPlayer::new(StoryState::ServantAccepted);
+    assert_eq!(
+        player.check_story_transition(StoryState::TempleBuilt),
+        Err(StoryTransitionError::ConditionNotMet(
+            StoryCondition::BuildingExists(BuildingType::Temple)
+        ))
+    );
+    assert!(player.legal_story_transitions().is_empty());
+    player.buildings.push(BuildingType::Temple);
+    assert_eq!(
+        player.check_story_transition(StoryState::TempleBuilt),
+        Ok(())
+    );
+    assert_eq!(
+        player.legal_story_transitions(),
+        vec![StoryState::TempleBuilt]
+    );
+    assert_eq!(
+        player.check_story_transition_from(StoryState::Initialized, StoryState::TempleBuilt),
+        Err(StoryTransitionError::StateMismatch {
+            expected: StoryState::Initialized,
+            actual: StoryState::ServantAccepted,
+        })
+    );
+}
+
+#[test]
+fn story_can_be_completed() {
+    let mut player = TestPlayer::new(StoryState::Initialized);
+    player.karma = 100;
+    player.buildings = vec![
+        BuildingType::Temple,
+        BuildingType::BlueFlowers,
+        BuildingType::Tree,
+        BuildingType::BundlingStation,
+    ];
+    player.tasks = vec![TaskType::GatherSticks];
+    let mut steps = 0;
+    while let Some(next) = player.legal_story_transitions().pop() {
+        player.story_state = next;
+        steps += 1;
+        assert!(steps < 100, "Story graph has a cycle");
+    }
+    assert_eq!(player.story_state, StoryState::GatheringSticks);
+}
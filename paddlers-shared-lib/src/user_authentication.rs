@@ -5,9 +5,21 @@ use serde::{Deserialize, Serialize};
 
 static RSA_PUB_KEY: OnceCell<Vec<u8>> = OnceCell::new();
 
+/// Keycloak realm role that grants access to the admin API
+pub const ADMIN_ROLE: &str = "admin";
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     sub: String,
+    /// Set by Keycloak, holds the realm roles assigned to the user
+    #[serde(default)]
+    realm_access: RealmAccess,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RealmAccess {
+    #[serde(default)]
+    roles: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -15,6 +27,7 @@ struct Claims {
 pub struct PadlUser {
     /// Minimal authenticated user identity. Must remain unique among all services.
     pub uuid: uuid::Uuid,
+    admin: bool,
     private: (),
 }
 
@@ -34,7 +47,22 @@ impl PadlUser {
         let uuid = uuid::Uuid::parse_str(&token_parsed.claims.sub)
             .map_err(|_| AuthenticationError::InvalidSubject)?;
 
-        Ok(PadlUser { uuid, private: () })
+        let admin = token_parsed
+            .claims
+            .realm_access
+            .roles
+            .iter()
+            .any(|role| role == ADMIN_ROLE);
+
+        Ok(PadlUser {
+            uuid,
+            admin,
+            private: (),
+        })
+    }
+    /// True if the token carries the admin role
+    pub fn is_admin(&self) -> bool {
+        self.admin
     }
 }
 